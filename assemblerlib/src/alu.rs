@@ -0,0 +1,83 @@
+use crate::instruction::ALUFunction;
+
+/// The result of one ALU operation, the flags are the ALU outputs CO, ZO and NO.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ALUOutput {
+    pub result: u8,
+    pub carry: bool,
+    pub zero: bool,
+    pub negative: bool
+}
+
+impl ALUOutput {
+    fn new(result: u8, carry: bool) -> ALUOutput {
+        return ALUOutput {
+            result,
+            carry,
+            zero: result == 0,
+            negative: result & 0x80 != 0
+        };
+    }
+}
+
+/// Computes the ALU function of `2iLanguage.txt` for the inputs A and B and the Carry Flag.
+pub fn compute(function: ALUFunction, a: u8, b: u8, carry_in: bool) -> ALUOutput {
+    let add = |carry: bool| {
+        let sum = a as u16 + b as u16 + carry as u16;
+        return (sum as u8, sum > 0xFF);
+    };
+
+    return match function.to_u8() {
+        0b0000 => {
+            let (result, carry) = add(false);
+            ALUOutput::new(result, carry || carry_in)
+        },
+        0b0001 => ALUOutput::new(a, false),
+        0b0010 => ALUOutput::new(!(a | b), false),
+        0b0011 => ALUOutput::new(0, false),
+        0b0100 => {
+            let (result, carry) = add(false);
+            ALUOutput::new(result, carry)
+        },
+        0b0101 => {
+            let (result, carry) = add(true);
+            ALUOutput::new(result, !carry)
+        },
+        0b0110 => {
+            let (result, carry) = add(carry_in);
+            ALUOutput::new(result, carry)
+        },
+        0b0111 => {
+            let (result, carry) = add(!carry_in);
+            ALUOutput::new(result, !carry)
+        },
+        0b1000 => ALUOutput::new(a >> 1, a & 1 == 1),
+        0b1001 => ALUOutput::new(a.rotate_right(1), a & 1 == 1),
+        0b1010 => ALUOutput::new(a >> 1 | (carry_in as u8) << 7, a & 1 == 1),
+        0b1011 => ALUOutput::new(((a as i8) >> 1) as u8, a & 1 == 1),
+        0b1100 => ALUOutput::new(b, false),
+        0b1101 => ALUOutput::new(b, true),
+        0b1110 => ALUOutput::new(b, carry_in),
+        _ => ALUOutput::new(b, !carry_in)
+    };
+}
+
+#[cfg(test)]
+mod alu_tests {
+    use super::*;
+
+    #[test]
+    fn test_subtraction_carry_is_borrow() {
+        let subtract = ALUFunction::from_u8(0b0101);
+        assert_eq!(ALUOutput { result: 0xFF, carry: true, zero: false, negative: true }, compute(subtract, 0x01, !0x02, false));
+        assert_eq!(ALUOutput { result: 0x00, carry: false, zero: true, negative: false }, compute(subtract, 0x02, !0x02, false));
+    }
+
+    #[test]
+    fn test_rotate_right_through_carry() {
+        let rotate = ALUFunction::from_u8(0b1010);
+        assert_eq!(0x80, compute(rotate, 0x01, 0, true).result);
+        assert!(compute(rotate, 0x01, 0, true).carry);
+        assert_eq!(0xC0, compute(ALUFunction::from_u8(0b1011), 0x81, 0, false).result);
+    }
+}
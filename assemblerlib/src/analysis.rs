@@ -0,0 +1,431 @@
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use crate::Assembler;
+use crate::diagnostics::{INVALID_ADDRESS, INVALID_DATA_SYMBOL};
+use crate::dialect::{Dialect, parse_dialect_directive};
+use crate::explanation::format_explanation;
+use crate::expression::is_identifier;
+use crate::instruction::{Instruction, InstructionAddress};
+use crate::parsing::parse_line;
+use crate::pseudo::PSEUDO_MNEMONICS;
+use crate::rtl::is_reserved_operand;
+use crate::translated::{TranslatedLine, TranslatedProgram};
+
+/// The translation stops at the first error, so the line is left out and the source translated again to find more.
+const MAX_DIAGNOSTICS: usize = 20;
+
+const REGISTER_NAMES: [&str; 8] = ["R0", "R1", "R2", "R3", "R4", "R5", "R6", "R7"];
+
+const KEYWORDS: [&str; 19] = [
+    "loop", "while", "if", "else", "break", "goto", "not", "zero", "negative", "carry", "inta", "intb",
+    ".data", ".dialect", ".include", ".define", ".if", ".endif", "use"
+];
+
+/// A position in a source, the line and the character in it are counted from 0 like in the Language Server Protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position
+}
+
+impl Range {
+    fn on_line(line: usize, start: usize, end: usize) -> Range {
+        return Range { start: Position { line, character: start }, end: Position { line, character: end } };
+    }
+}
+
+/// An error of the assembler at the line it was found in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceDiagnostic {
+    pub range: Range,
+    /// The stable code of the error, like `E0003`.
+    pub code: Option<String>,
+    pub message: String
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompletionKind {
+    Mnemonic,
+    Register,
+    Label,
+    Jump,
+    Keyword
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: String
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String
+}
+
+/// An instruction address and the line its instruction is written in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressSymbol {
+    pub name: String,
+    pub detail: String,
+    pub range: Range
+}
+
+/// A `.2ia` file open in an editor, analysed with the assembler itself.
+/// Labels are the instruction addresses that jumps go to and the names of `.data` lines.
+pub struct SourceDocument {
+    name: String,
+    base_directory: PathBuf,
+    text: String,
+    dialect: Dialect
+}
+
+impl SourceDocument {
+    /// `name` is used like a file name in errors, includes are resolved relative to `base_directory`.
+    pub fn new(name: &str, base_directory: &Path, text: &str, dialect: Dialect) -> SourceDocument {
+        return SourceDocument {
+            name: name.to_string(),
+            base_directory: base_directory.to_path_buf(),
+            text: text.to_string(),
+            dialect
+        };
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+    }
+
+    fn translate(&self, text: &str) -> Result<TranslatedProgram, String> {
+        let mut assembler = Assembler::new();
+        assembler.dialect(self.dialect);
+        return assembler.translate_source(&self.name, text, &self.base_directory);
+    }
+
+    fn lines(&self) -> Vec<&str> {
+        return self.text.lines().collect();
+    }
+
+    /// Every error of the source, each at the line it was found in.
+    /// Errors of included files and of structured code as a whole are put on the first line.
+    pub fn diagnostics(&self) -> Vec<SourceDiagnostic> {
+        let mut lines: Vec<String> = self.text.lines().map(|line| line.to_string()).collect();
+        let mut diagnostics = Vec::new();
+
+        while diagnostics.len() < MAX_DIAGNOSTICS {
+            let error = match self.translate(&lines.join("\n")) {
+                Ok(_) => break,
+                Err(error) => error
+            };
+            let (line, description) = self.locate_error(&error);
+            let code = description.split(' ')
+                .find(|code| code.len() == 5 && code.starts_with('E') && code[1..].chars().all(|ch| ch.is_ascii_digit()))
+                .map(|code| code.to_string());
+            let index = line.filter(|index| *index < lines.len()).unwrap_or(0);
+            diagnostics.push(SourceDiagnostic { range: line_range(&lines, index), code, message: description.to_string() });
+
+            match line {
+                Some(index) if index < lines.len() && !lines[index].trim().is_empty() => lines[index].clear(),
+                _ => break
+            }
+        }
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start.line);
+        return diagnostics;
+    }
+
+    /// The line of this document an `Error in FILE:LINE, Description: ...` message points to, and its description.
+    fn locate_error<'e>(&self, error: &'e str) -> (Option<usize>, &'e str) {
+        let (location, description) = match error.strip_prefix("Error in ").and_then(|error| error.split_once(", Description: ")) {
+            Some(split) => split,
+            None => return (None, error)
+        };
+        let line = location.rsplit_once(':')
+            .filter(|(file, _)| *file == self.name)
+            .and_then(|(_, line)| line.parse::<usize>().ok())
+            .map(|line| line.saturating_sub(1));
+        return (line, description);
+    }
+
+    /// The encoded word and the fields of every instruction translated from the line.
+    pub fn hover(&self, position: Position) -> Option<String> {
+        let instructions = match self.translate(&self.text) {
+            Ok(program) => program.lines.iter().zip(program.locations.iter())
+                .filter(|(_, location)| location.file == self.name && location.line == position.line + 1)
+                .filter_map(|(line, _)| match line {
+                    TranslatedLine::InstructionLine(instruction, _) => Some(*instruction),
+                    _ => None
+                })
+                .collect(),
+            // Without a translation of the whole program, a line with address is still translated on its own.
+            Err(_) => self.instruction_of_line(position.line).into_iter().collect::<Vec<Instruction>>()
+        };
+
+        if instructions.is_empty() {
+            return None;
+        }
+        return Some(instructions.iter()
+            .map(|instruction| format!("Word: 0x{:07X}\n{}", instruction.to_word(), format_explanation(instruction, self.dialect)))
+            .collect::<Vec<String>>()
+            .join("\n"));
+    }
+
+    fn instruction_of_line(&self, line: usize) -> Option<Instruction> {
+        let lines = self.lines();
+        let dialect = lines.iter().take(line).rev()
+            .find_map(|line| parse_dialect_directive(line).and_then(|dialect| dialect.ok()))
+            .unwrap_or(self.dialect);
+
+        let mut assembler = Assembler::new();
+        assembler.dialect(dialect);
+        return match assembler.translate_line(lines.get(line)?) {
+            Ok(TranslatedLine::InstructionLine(instruction, _)) => Some(instruction),
+            _ => None
+        };
+    }
+
+    /// The mnemonics of the dialect, registers, labels, jump kinds and keywords, editors filter them by what is typed.
+    pub fn completions(&self) -> Vec<Completion> {
+        let dialect = self.lines().iter()
+            .find_map(|line| parse_dialect_directive(line).and_then(|dialect| dialect.ok()))
+            .unwrap_or(self.dialect);
+        let item = |label: &str, kind: CompletionKind, detail: &str| Completion { label: label.to_string(), kind, detail: detail.to_string() };
+
+        let mut completions: Vec<Completion> = Vec::new();
+        completions.extend(dialect.alu_mnemonics().iter().map(|(name, _)| item(name, CompletionKind::Mnemonic, "ALU Command")));
+        completions.extend(PSEUDO_MNEMONICS.iter().map(|name| item(name, CompletionKind::Mnemonic, "Pseudo Instruction")));
+        completions.extend(dialect.write_mnemonics().iter().map(|(name, _)| item(name, CompletionKind::Mnemonic, "Write Command")));
+        completions.extend(dialect.bus_mnemonics().iter().map(|(name, _)| item(name, CompletionKind::Mnemonic, "Bus Command")));
+        completions.extend(dialect.flag_mnemonics().iter().map(|(name, _)| item(name, CompletionKind::Mnemonic, "Flag Command")));
+        completions.extend(dialect.jump_mnemonics().iter().map(|(name, _)| item(name, CompletionKind::Jump, "Jump Command")));
+        completions.extend(REGISTER_NAMES.iter().map(|name| item(name, CompletionKind::Register, "Register")));
+        completions.extend(self.labels().iter().map(|(name, line, _)| item(name, CompletionKind::Label, self.lines()[*line].trim())));
+        completions.extend(KEYWORDS.iter().map(|name| item(name, CompletionKind::Keyword, "Keyword")));
+
+        // Both dialects spell some commands the same, like `WRITE A`.
+        let mut seen = Vec::new();
+        completions.retain(|completion| match seen.contains(&completion.label) {
+            true => false,
+            false => {
+                seen.push(completion.label.clone());
+                true
+            }
+        });
+        return completions;
+    }
+
+    /// Every label with the line and character range it is defined at.
+    fn labels(&self) -> Vec<(String, usize, Range)> {
+        let mut labels = Vec::new();
+        for (index, line) in self.lines().iter().enumerate() {
+            let code = line.split('#').next().unwrap_or("");
+            let start = code.len() - code.trim_start().len();
+            let trimmed = code.trim();
+
+            if let Some(argument) = trimmed.strip_prefix(".data") {
+                // Like `.data NAME ADDRESS: VALUES`, a data line without name has only the address in front of the colon.
+                let place = argument.split(':').next().unwrap_or("");
+                if let Some((name, _)) = place.trim().split_once(char::is_whitespace).filter(|(name, _)| is_identifier(name)) {
+                    let character = start + ".data".len() + argument.find(name).unwrap_or(0);
+                    labels.push((name.to_string(), index, Range::on_line(index, character, character + name.len())));
+                }
+            } else if let Some((address, _)) = trimmed.split_once(':') {
+                if !trimmed.starts_with('.') && InstructionAddress::try_from(address.trim().to_string()).is_ok() {
+                    labels.push((address.trim().to_string(), index, Range::on_line(index, start, start + address.trim().len())));
+                }
+            }
+        }
+        return labels;
+    }
+
+    /// Where the label at the position is defined.
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let (word, _) = self.word_at(position)?;
+        return self.labels().into_iter()
+            .find(|(name, _, _)| *name == word)
+            .map(|(_, _, range)| range);
+    }
+
+    /// The edits that rename the label at the position everywhere it is defined or used, comments are left as they are.
+    /// Addresses can only be renamed to a free address, data names only to a free name that is no register or constant.
+    pub fn rename(&self, position: Position, new_name: &str) -> Result<Vec<TextEdit>, String> {
+        let (word, _) = self.word_at(position)
+            .ok_or_else(|| "There is no Label at this Position".to_string())?;
+        let labels = self.labels();
+        let is_address = InstructionAddress::try_from(word.clone()).is_ok();
+
+        if !labels.iter().any(|(name, _, _)| *name == word) && !is_address {
+            return Err(format!("{} is no Label", word));
+        }
+        if labels.iter().any(|(name, _, _)| name == new_name) {
+            return Err(format!("{} is already defined", new_name));
+        }
+        match is_address {
+            true if InstructionAddress::try_from(new_name.to_string()).is_err() =>
+                return Err(INVALID_ADDRESS.message(format!("{} is no five Bit Address", new_name))),
+            false if !is_identifier(new_name) || is_reserved_operand(new_name) =>
+                return Err(INVALID_DATA_SYMBOL.message(format!("{} can not be a Data Symbol", new_name))),
+            _ => {}
+        }
+
+        if is_address {
+            self.check_not_conditional_target(&word)?;
+        }
+
+        let mut edits = Vec::new();
+        for (index, line) in self.lines().iter().enumerate() {
+            let code = line.split('#').next().unwrap_or("");
+            // Numbers in directives are values, not addresses of instructions.
+            if is_address && code.trim_start().starts_with('.') {
+                continue;
+            }
+            edits.extend(find_word(code, &word).into_iter()
+                .map(|character| TextEdit { range: Range::on_line(index, character, character + word.len()), new_text: new_name.to_string() }));
+        }
+        return Ok(edits);
+    }
+
+    /// A conditional jump goes to a pair of addresses that only differ in bit 0, renaming one of them would move the
+    /// other target of the jump as well, so the targets of conditional jumps are not renamed.
+    fn check_not_conditional_target(&self, address: &str) -> Result<(), String> {
+        let mut dialect = self.dialect;
+        for (index, line) in self.lines().iter().enumerate() {
+            if let Some(Ok(selected_dialect)) = parse_dialect_directive(line) {
+                dialect = selected_dialect;
+            }
+            let instruction = match parse_line(line, dialect) {
+                Ok(code_line) => code_line.instruction,
+                Err(_) => continue
+            };
+            let conditional = instruction.address_control.ac1 || instruction.address_control.ac0;
+            if conditional && instruction.next_address.to_string()[..4] == address[..4] {
+                return Err(format!("{} is a Target of the conditional Jump on Line {}, the Targets of a Jump are a Pair that is renamed together",
+                    address, index + 1));
+            }
+        }
+        return Ok(());
+    }
+
+    /// The instruction addresses of the program with their lines. Structured code gets the addresses it is placed at,
+    /// if the program does not translate, only the lines with address are listed.
+    pub fn symbols(&self) -> Vec<AddressSymbol> {
+        let lines = self.lines();
+        let symbol = |name: String, line: usize| AddressSymbol {
+            name,
+            detail: lines.get(line).map(|text| text.trim().to_string()).unwrap_or_default(),
+            range: line_range(&lines, line)
+        };
+
+        return match self.translate(&self.text) {
+            Ok(program) => program.lines.iter().zip(program.locations.iter())
+                .filter(|(_, location)| location.file == self.name)
+                .filter_map(|(line, location)| match line {
+                    TranslatedLine::InstructionLine(instruction, _) => Some(symbol(instruction.address.to_string(), location.line - 1)),
+                    _ => None
+                })
+                .collect(),
+            Err(_) => self.labels().into_iter()
+                .filter(|(name, _, _)| InstructionAddress::try_from(name.clone()).is_ok())
+                .map(|(name, line, _)| symbol(name, line))
+                .collect()
+        };
+    }
+
+    /// The word of letters, digits and `_` at the position, with the character it starts at.
+    fn word_at(&self, position: Position) -> Option<(String, usize)> {
+        let line: Vec<char> = self.lines().get(position.line)?.chars().collect();
+        let is_word_char = |ch: &char| ch.is_ascii_alphanumeric() || *ch == '_';
+        let start = (0..position.character.min(line.len())).rev()
+            .take_while(|index| is_word_char(&line[*index]))
+            .last()
+            .unwrap_or(position.character);
+        let word: String = line.iter().skip(start).take_while(|ch| is_word_char(ch)).collect();
+        return match word.is_empty() {
+            true => None,
+            false => Some((word, start))
+        };
+    }
+}
+
+fn line_range<S: AsRef<str>>(lines: &[S], index: usize) -> Range {
+    let line = lines.get(index).map(|line| line.as_ref()).unwrap_or("");
+    let start = line.len() - line.trim_start().len();
+    return Range::on_line(index, start, line.trim_end().len().max(start));
+}
+
+/// Every character a whole word starts at in the text.
+fn find_word(text: &str, word: &str) -> Vec<usize> {
+    let is_word_char = |ch: Option<char>| matches!(ch, Some(ch) if ch.is_ascii_alphanumeric() || ch == '_');
+    return text.match_indices(word)
+        .map(|(index, _)| index)
+        .filter(|index| !is_word_char(text[..*index].chars().last()) && !is_word_char(text[index + word.len()..].chars().next()))
+        .collect();
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use super::*;
+
+    fn document(text: &str) -> SourceDocument {
+        return SourceDocument::new("main.2ia", Path::new("."), text, Dialect::Standard);
+    }
+
+    fn at(line: usize, character: usize) -> Position {
+        return Position { line, character };
+    }
+
+    #[test]
+    fn test_diagnostics_of_every_line() {
+        let diagnostics = document("00000: NOP; JUMP 00001\n00001: MOV R8, R0; JUMP 00010\n00010: NOPE; JUMP 00000\n").diagnostics();
+
+        assert_eq!(2, diagnostics.len());
+        assert_eq!(Some("E0003".to_string()), diagnostics[0].code);
+        assert_eq!(Range::on_line(1, 0, 29), diagnostics[0].range);
+        assert_eq!(2, diagnostics[1].range.start.line);
+        assert!(diagnostics[1].message.contains("did you mean NOP?"));
+        assert!(document("00000: NOP; JUMP 00000").diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_hover_shows_word_and_fields() {
+        let document = document("# Count\nloop {\n    R0 = R0 + 01\n}\n");
+        let hover = document.hover(at(2, 6)).unwrap();
+        assert!(hover.starts_with("Word: 0x"));
+        assert!(hover.contains("MALUS"));
+        assert_eq!(None, document.hover(at(0, 0)));
+    }
+
+    #[test]
+    fn test_labels() {
+        let document = document(".data TABLE 0x10: 1\n00000: R0 = TABLE; goto 00011 # 00011\n00011: NOP; JUMP 00000\n");
+
+        assert_eq!(Some(Range::on_line(2, 0, 5)), document.definition(at(1, 26)));
+        assert_eq!(Some(Range::on_line(0, 6, 11)), document.definition(at(1, 14)));
+        assert!(document.completions().iter().any(|completion| completion.label == "TABLE" && completion.kind == CompletionKind::Label));
+        assert_eq!(vec!["00000", "00011"], document.symbols().iter().map(|symbol| symbol.name.as_str()).collect::<Vec<&str>>());
+
+        let edits = document.rename(at(2, 2), "00101").unwrap();
+        assert_eq!(vec![Range::on_line(1, 24, 29), Range::on_line(2, 0, 5)], edits.iter().map(|edit| edit.range).collect::<Vec<Range>>());
+        assert_eq!(2, document.rename(at(0, 8), "VALUES").unwrap().len());
+        assert!(document.rename(at(0, 8), "R1").is_err());
+        assert!(document.rename(at(2, 2), "00000").is_err());
+    }
+
+    #[test]
+    fn test_conditional_targets_are_not_renamed() {
+        let document = document("00000: R0 = R0 + 01; FLAGS COPY; JUMPZO 00011\n00010: NOP; JUMP 00000\n00011: NOP; JUMP 00011\n\
+            00100: NOP; if Z goto 00111 else 00110\n00110: NOP; JUMP 00000\n00111: NOP; JUMP 00100\n");
+
+        assert!(document.rename(at(2, 2), "00101").unwrap_err().contains("Line 1"));
+        assert!(document.rename(at(1, 2), "01000").is_err());
+        assert!(document.rename(at(3, 34), "01001").unwrap_err().contains("Line 4"));
+        assert_eq!(2, document.rename(at(3, 2), "01000").unwrap().len());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::debugger::format_cycle;
+use crate::emulator::{Cycle, Emulator, MachineState};
+use crate::trace::{Trace, TraceStep};
+use crate::translated::TranslatedProgram;
+
+/// The most cycles one input combination runs for when no number is given.
+pub const DEFAULT_CHECK_CYCLES: u64 = 1000;
+
+/// Which input combinations are checked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CheckInputs {
+    /// All 65536 combinations of the two input ports.
+    Exhaustive,
+    /// A number of random combinations, the same seed checks the same combinations.
+    Random { samples: usize, seed: u64 }
+}
+
+/// When the output port holds the result of a program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CheckCompletion {
+    /// The first write to the output port is the result, for programs that loop forever.
+    FirstWrite,
+    /// The output port when the program halts is the result, for programs that write it step by step.
+    Halt
+}
+
+/// Checks that the output port of a program equals a Rust function of its two input ports, by default FE = f(FC, FD).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CheckSettings {
+    pub input_ports: (u8, u8),
+    pub output_port: u8,
+    pub inputs: CheckInputs,
+    pub completion: CheckCompletion,
+    pub cycles: u64
+}
+
+impl CheckSettings {
+    pub fn new() -> CheckSettings {
+        return CheckSettings {
+            input_ports: (0xFC, 0xFD),
+            output_port: 0xFE,
+            inputs: CheckInputs::Exhaustive,
+            completion: CheckCompletion::FirstWrite,
+            cycles: DEFAULT_CHECK_CYCLES
+        };
+    }
+}
+
+impl Default for CheckSettings {
+    fn default() -> Self {
+        return CheckSettings::new();
+    }
+}
+
+/// The first input combination the program gets wrong, with every cycle it executed for it.
+pub struct Counterexample {
+    pub inputs: (u8, u8),
+    pub expected: u8,
+    /// `None` if the program did not produce a result within the cycles.
+    pub actual: Option<u8>,
+    /// Set if an emulator step failed before the program produced a result, `actual` is then `None`.
+    pub error: Option<String>,
+    pub trace: Trace
+}
+
+/// Runs the program for every input combination and compares its result to `function`,
+/// returns the number of checked combinations or the first counterexample.
+pub fn check_function(program: &TranslatedProgram, settings: &CheckSettings, function: impl Fn(u8, u8) -> u8) -> Result<usize, Box<Counterexample>> {
+    let combinations: Box<dyn Iterator<Item = (u8, u8)>> = match settings.inputs {
+        CheckInputs::Exhaustive => Box::new((0..=255u8).flat_map(|x| (0..=255u8).map(move |y| (x, y)))),
+        CheckInputs::Random { samples, seed } => {
+            let mut random = Random::new(seed);
+            Box::new((0..samples).map(move |_| {
+                let value = random.next();
+                (value as u8, (value >> 8) as u8)
+            }))
+        }
+    };
+
+    let mut checked = 0;
+    for inputs in combinations {
+        let expected = function(inputs.0, inputs.1);
+        let (actual, error) = execute(program, settings, inputs, |_, _| {});
+        if actual != Some(expected) || error.is_some() {
+            let mut trace = Trace { initial: prepare(program, settings, inputs).state, steps: Vec::new() };
+            execute(program, settings, inputs, |cycle, state| trace.steps.push(TraceStep { cycle: *cycle, state: state.clone() }));
+            return Err(Box::new(Counterexample { inputs, expected, actual, error, trace }));
+        }
+        checked += 1;
+    }
+    return Ok(checked);
+}
+
+/// What the counterexample got wrong and every cycle with the fields of its instruction, the ALU values and the registers.
+pub fn format_counterexample(settings: &CheckSettings, counterexample: &Counterexample) -> String {
+    let (x, y) = counterexample.inputs;
+    let actual = match counterexample.actual {
+        Some(actual) => format!("{:02X}", actual),
+        None => "no Result".to_string()
+    };
+    let mut text = format!("Counterexample: {:02X}={:02X} {:02X}={:02X} expected {:02X}={:02X}, got {}\n",
+        settings.input_ports.0, x, settings.input_ports.1, y, settings.output_port, counterexample.expected, actual);
+    if let Some(error) = &counterexample.error {
+        text.push_str(&format!("{}\n", error));
+    }
+
+    let mut address = counterexample.trace.initial.address;
+    for (index, step) in counterexample.trace.steps.iter().enumerate() {
+        text.push_str(&format!("Cycle {} at {}\n", index + 1, address));
+        text.push_str(&format_cycle(&step.cycle));
+        let registers: Vec<String> = step.state.registers.iter().enumerate()
+            .map(|(register, value)| format!("R{}={:02X}", register, value))
+            .collect();
+        text.push_str(&format!("         {}\n", registers.join(" ")));
+        address = step.state.address;
+    }
+    return text;
+}
+
+fn prepare(program: &TranslatedProgram, settings: &CheckSettings, inputs: (u8, u8)) -> Emulator {
+    let mut emulator = Emulator::new(program);
+    emulator.state.memory[settings.input_ports.0 as usize] = inputs.0;
+    emulator.state.memory[settings.input_ports.1 as usize] = inputs.1;
+    return emulator;
+}
+
+/// Runs the program for one input combination until it has a result, returns the result and the error that stopped it.
+fn execute(program: &TranslatedProgram, settings: &CheckSettings, inputs: (u8, u8), mut record: impl FnMut(&Cycle, &MachineState)) -> (Option<u8>, Option<String>) {
+    let mut emulator = prepare(program, settings, inputs);
+    while emulator.state.cycles < settings.cycles {
+        if emulator.is_halted() {
+            return match settings.completion {
+                CheckCompletion::Halt => (Some(emulator.state.memory[settings.output_port as usize]), None),
+                CheckCompletion::FirstWrite => (None, None)
+            };
+        }
+
+        let cycle = match emulator.step() {
+            Ok(cycle) => cycle,
+            Err(error) => return (None, Some(error))
+        };
+        record(&cycle, &emulator.state);
+        match (settings.completion, cycle.bus_write) {
+            (CheckCompletion::FirstWrite, Some((port, value))) if port == settings.output_port => return (Some(value), None),
+            _ => {}
+        }
+    }
+    return (None, None);
+}
+
+/// A xorshift generator, random enough to pick input combinations and the same for every seed on every machine.
+pub(crate) struct Random {
+    state: u64
+}
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Random {
+        // The state must not be 0, it would stay 0.
+        return match seed ^ 0x9E37_79B9_7F4A_7C15 {
+            0 => Random { state: 0x2545_F491_4F6C_DD1D },
+            state => Random { state }
+        };
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+}
+
+#[cfg(test)]
+mod checking_tests {
+    use super::*;
+    use crate::Assembler;
+
+    fn program(routine: &str) -> TranslatedProgram {
+        let source = format!("loop {{\n\
+                R3 = FC\n\
+                R0 = (R3)\n\
+                R3 = FD\n\
+                R1 = (R3)\n\
+                use std::{}\n\
+                R3 = FE\n\
+                (R3) = R0\n\
+            }}", routine);
+        return Assembler::new().translate_program(&source).unwrap();
+    }
+
+    #[test]
+    fn test_exhaustive_check() {
+        assert_eq!(Ok(65536), check_function(&program("xor"), &CheckSettings::new(), |x, y| x ^ y).map_err(|counterexample| counterexample.inputs));
+
+        let settings = CheckSettings::new();
+        let counterexample = check_function(&program("or"), &settings, |x, y| x ^ y).err().unwrap();
+        assert_eq!(((0x01, 0x01), 0x00, Some(0x01)), (counterexample.inputs, counterexample.expected, counterexample.actual));
+        let text = format_counterexample(&settings, &counterexample);
+        assert!(text.starts_with("Counterexample: FC=01 FD=01 expected FE=00, got 01\nCycle 1 at 00000\n"));
+        assert!(text.contains("Bus:     write 01 to (FE)\n"));
+    }
+
+    #[test]
+    fn test_random_and_halting_checks() {
+        let settings = CheckSettings { inputs: CheckInputs::Random { samples: 200, seed: 7 }, ..CheckSettings::new() };
+        assert_eq!(200, check_function(&program("and"), &settings, |x, y| x & y).ok().unwrap());
+
+        let halting = Assembler::new().translate_program("R3 = FE\n(R3) = R3\nloop {\n}").unwrap();
+        let settings = CheckSettings { completion: CheckCompletion::Halt, ..settings };
+        assert_eq!(200, check_function(&halting, &settings, |_, _| 0xFE).ok().unwrap());
+        let settings = CheckSettings { cycles: 1, ..settings };
+        assert_eq!(None, check_function(&halting, &settings, |_, _| 0xFE).err().unwrap().actual);
+    }
+
+    #[test]
+    fn test_random_never_starts_at_zero() {
+        let mut random = Random::new(0x9E37_79B9_7F4A_7C15);
+        assert_ne!(0, random.next());
+        assert_ne!(random.next(), random.next());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::diagnostics::{INVALID_SYMBOL_NAME, UNBALANCED_CONDITIONAL};
+use crate::expression::{evaluate, is_identifier, SymbolTable};
+
+/// What the loader should do with a line after conditional assembly looked at it.
+#[derive(Debug, PartialEq)]
+pub enum LineDisposition {
+    /// The line is in an active region and is no preprocessor directive.
+    Keep,
+    /// The line is in an inactive region and must not be parsed.
+    Skip,
+    /// The line was a preprocessor directive and has been handled.
+    Handled
+}
+
+struct ConditionalBlock {
+    /// Whether the enclosing region is active at all.
+    parent_active: bool,
+    /// Whether one of the branches of this block has already been taken.
+    branch_taken: bool,
+    /// Whether the current branch of this block is active.
+    active: bool,
+    seen_else: bool
+}
+
+/// Tracks `.if`/`.elif`/`.else`/`.endif`, `.ifdef`/`.ifndef` and `.define` while the source is loaded.
+pub struct ConditionalAssembly {
+    symbols: SymbolTable,
+    blocks: Vec<ConditionalBlock>
+}
+
+impl ConditionalAssembly {
+    pub fn new(symbols: SymbolTable) -> ConditionalAssembly {
+        return ConditionalAssembly {
+            symbols,
+            blocks: Vec::new()
+        };
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.blocks.last().map(|block| block.active).unwrap_or(true);
+    }
+
+    /// The number of currently open conditional blocks.
+    pub fn depth(&self) -> usize {
+        return self.blocks.len();
+    }
+
+    pub fn process_line(&mut self, line: &str) -> Result<LineDisposition, String> {
+        let line_without_comment = line.split('#').next().unwrap_or("").trim();
+        let (directive, argument) = match line_without_comment.find(char::is_whitespace) {
+            Some(index) => (&line_without_comment[..index], line_without_comment[index..].trim()),
+            None => (line_without_comment, "")
+        };
+
+        match directive {
+            ".if" => self.open_block(|symbols| Ok(evaluate(argument, symbols)? != 0))?,
+            ".ifdef" => self.open_block(|symbols| Ok(symbols.contains_key(parse_symbol_name(argument)?)))?,
+            ".ifndef" => self.open_block(|symbols| Ok(!symbols.contains_key(parse_symbol_name(argument)?)))?,
+            ".elif" => self.continue_block(".elif", |symbols| Ok(evaluate(argument, symbols)? != 0))?,
+            ".else" => {
+                self.continue_block(".else", |_| Ok(true))?;
+                if let Some(block) = self.blocks.last_mut() {
+                    block.seen_else = true;
+                }
+            },
+            ".endif" => {
+                self.blocks.pop().ok_or_else(|| UNBALANCED_CONDITIONAL.message(".endif without .if"))?;
+            },
+            _ if !self.is_active() => return Ok(LineDisposition::Skip),
+            ".define" => self.define(argument)?,
+            _ => return Ok(LineDisposition::Keep)
+        }
+
+        return Ok(LineDisposition::Handled);
+    }
+
+    fn open_block<F>(&mut self, condition: F) -> Result<(), String>
+        where F: FnOnce(&SymbolTable) -> Result<bool, String> {
+        let parent_active = self.is_active();
+        let active = parent_active && condition(&self.symbols)?;
+
+        self.blocks.push(ConditionalBlock {
+            parent_active,
+            branch_taken: active,
+            active,
+            seen_else: false
+        });
+        return Ok(());
+    }
+
+    fn continue_block<F>(&mut self, directive: &str, condition: F) -> Result<(), String>
+        where F: FnOnce(&SymbolTable) -> Result<bool, String> {
+        let symbols = &self.symbols;
+        let block = self.blocks.last_mut()
+            .ok_or_else(|| UNBALANCED_CONDITIONAL.message(format!("{} without .if", directive)))?;
+
+        if block.seen_else {
+            return Err(UNBALANCED_CONDITIONAL.message(format!("{} after .else", directive)));
+        }
+
+        block.active = block.parent_active && !block.branch_taken && condition(symbols)?;
+        block.branch_taken |= block.active;
+        return Ok(());
+    }
+
+    fn define(&mut self, argument: &str) -> Result<(), String> {
+        let (name, value) = match argument.find(char::is_whitespace) {
+            Some(index) => (&argument[..index], evaluate(&argument[index..], &self.symbols)?),
+            None => (argument, 1)
+        };
+
+        self.symbols.insert(parse_symbol_name(name)?.to_string(), value);
+        return Ok(());
+    }
+}
+
+fn parse_symbol_name(name: &str) -> Result<&str, String> {
+    if !is_identifier(name) {
+        return Err(INVALID_SYMBOL_NAME.message(format!("Invalid Symbol Name {}", name)));
+    }
+    return Ok(name);
+}
+
+/// Parses a command-line style `NAME=VALUE` or `NAME` definition, `NAME` alone defines the symbol as 1.
+pub fn parse_definition(definition: &str) -> Result<(String, i64), String> {
+    let mut split_definition = definition.splitn(2, '=');
+    let name = parse_symbol_name(split_definition.next().unwrap_or("").trim())?;
+    let value = match split_definition.next() {
+        Some(value) => evaluate(value, &SymbolTable::new())?,
+        None => 1
+    };
+    return Ok((name.to_string(), value));
+}
+
+#[cfg(test)]
+mod conditional_tests {
+    use super::*;
+
+    fn kept_lines(program: &str, symbols: SymbolTable) -> Result<Vec<String>, String> {
+        let mut conditional_assembly = ConditionalAssembly::new(symbols);
+        let mut lines = Vec::new();
+        for line in program.lines() {
+            if conditional_assembly.process_line(line)? == LineDisposition::Keep {
+                lines.push(line.trim().to_string());
+            }
+        }
+        return Ok(lines);
+    }
+
+    const PROGRAM: &str = "
+        .if LEVEL == 1
+        one
+        .elif LEVEL == 2
+        two
+        .else
+        other
+        .endif
+        .ifdef DEBUG
+        debug
+        .endif";
+
+    #[test]
+    fn test_branches() {
+        let mut symbols = SymbolTable::new();
+        symbols.insert("LEVEL".to_string(), 2);
+        assert_eq!(vec!["", "two"], kept_lines(PROGRAM, symbols.clone()).unwrap());
+
+        symbols.insert("LEVEL".to_string(), 5);
+        symbols.insert("DEBUG".to_string(), 1);
+        assert_eq!(vec!["", "other", "debug"], kept_lines(PROGRAM, symbols).unwrap());
+    }
+
+    #[test]
+    fn test_inactive_region_is_not_evaluated() {
+        let program = ".if 0\n.if UNDEFINED\n.define X 1\n.endif\n.endif\n.ifndef X\nkept\n.endif";
+        assert_eq!(vec!["kept"], kept_lines(program, SymbolTable::new()).unwrap());
+    }
+
+    #[test]
+    fn test_unbalanced_directives() {
+        assert!(kept_lines(".endif", SymbolTable::new()).is_err());
+        assert!(kept_lines(".if 1\n.else\n.elif 1\n.endif", SymbolTable::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_definition() {
+        assert_eq!(Ok(("DEBUG".to_string(), 1)), parse_definition("DEBUG"));
+        assert_eq!(Ok(("PORT".to_string(), 0xFC)), parse_definition("PORT=0xFC"));
+        assert!(parse_definition("1X=2").is_err());
+    }
+}
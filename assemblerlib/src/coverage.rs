@@ -0,0 +1,248 @@
+use crate::debugger::condition_name;
+use crate::emulator::{Cycle, CONTROL_STORE_SIZE};
+use crate::instruction::{Instruction, InstructionAddress};
+use crate::library::is_library_source;
+use crate::source::SourceLocation;
+use crate::translated::{TranslatedLine, TranslatedProgram};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverageFormat {
+    Listing,
+    Lcov
+}
+
+impl CoverageFormat {
+    pub const ALL: [CoverageFormat; 2] = [CoverageFormat::Listing, CoverageFormat::Lcov];
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            CoverageFormat::Listing => "listing",
+            CoverageFormat::Lcov => "lcov"
+        };
+    }
+
+    pub fn from_name(name: &str) -> Result<CoverageFormat, String> {
+        return CoverageFormat::ALL.iter()
+            .find(|format| format.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown Coverage Format {}", name));
+    }
+}
+
+/// How often every address of the control store was executed and where its conditional jump went.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Coverage {
+    pub executions: [u64; CONTROL_STORE_SIZE],
+    /// How often the successor with NA0 = 0 and the one with NA0 = 1 were selected.
+    pub branches: [[u64; 2]; CONTROL_STORE_SIZE]
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        return Coverage { executions: [0; CONTROL_STORE_SIZE], branches: [[0; 2]; CONTROL_STORE_SIZE] };
+    }
+
+    pub fn record(&mut self, cycle: &Cycle) {
+        let address = cycle.instruction.address.to_u8() as usize;
+        self.executions[address] += 1;
+        self.branches[address][cycle.next_address.bit0 as usize] += 1;
+    }
+
+    /// Adds the counts of another run of the same program, like another test case.
+    pub fn merge(&mut self, other: &Coverage) {
+        for address in 0..CONTROL_STORE_SIZE {
+            self.executions[address] += other.executions[address];
+            self.branches[address][0] += other.branches[address][0];
+            self.branches[address][1] += other.branches[address][1];
+        }
+    }
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        return Coverage::new();
+    }
+}
+
+pub fn format_coverage(program: &TranslatedProgram, coverage: &Coverage, format: CoverageFormat) -> String {
+    return match format {
+        CoverageFormat::Listing => format_listing(program, coverage),
+        CoverageFormat::Lcov => format_lcov(program, coverage)
+    };
+}
+
+/// The instructions of the program with the source location they were translated from.
+fn instructions(program: &TranslatedProgram) -> impl Iterator<Item = (&Instruction, &SourceLocation)> {
+    return program.lines.iter().zip(program.locations.iter())
+        .filter_map(|(line, location)| match line {
+            TranslatedLine::InstructionLine(instruction, _) => Some((instruction, location)),
+            _ => None
+        });
+}
+
+/// The two addresses a conditional jump can go to.
+fn successors(instruction: &Instruction) -> [InstructionAddress; 2] {
+    let mut successors = [instruction.next_address; 2];
+    successors[0].bit0 = false;
+    successors[1].bit0 = true;
+    return successors;
+}
+
+/// The translated program with the executions of every instruction in front of it like gcov, `#####` for instructions
+/// that never ran, and whether both successors of every conditional jump were taken, followed by a summary.
+fn format_listing(program: &TranslatedProgram, coverage: &Coverage) -> String {
+    let mut listing = String::new();
+    for (line, location) in program.lines.iter().zip(program.locations.iter()) {
+        let instruction = match line {
+            TranslatedLine::InstructionLine(instruction, _) => instruction,
+            _ => {
+                listing.push_str(&format!("{:>7}: {}\t{}\n", "-", location, line));
+                continue;
+            }
+        };
+
+        let address = instruction.address.to_u8() as usize;
+        let executions = match coverage.executions[address] {
+            0 => "#####".to_string(),
+            executions => executions.to_string()
+        };
+        listing.push_str(&format!("{:>7}: {}\t{}\n", executions, location, line));
+        if let Some(condition) = condition_name(instruction) {
+            let branches: Vec<String> = successors(instruction).iter().zip(coverage.branches[address].iter())
+                .map(|(successor, taken)| match taken {
+                    0 => format!("{} not taken", successor),
+                    taken => format!("{} taken {}x", successor, taken)
+                })
+                .collect();
+            listing.push_str(&format!("{:>7}  branch on {}: {}\n", "", condition, branches.join(", ")));
+        }
+    }
+
+    let (executed, instructions) = instructions(program)
+        .fold((0, 0), |(executed, instructions), (instruction, _)| {
+            (executed + (coverage.executions[instruction.address.to_u8() as usize] > 0) as usize, instructions + 1)
+        });
+    let (taken, branches) = instructions_with_branches(program)
+        .fold((0, 0), |(taken, branches), (instruction, _)| {
+            let address = instruction.address.to_u8() as usize;
+            (taken + coverage.branches[address].iter().filter(|taken| **taken > 0).count(), branches + 2)
+        });
+    listing.push_str(&format!("Instructions: {} of {} executed\nBranches:     {} of {} taken\n", executed, instructions, taken, branches));
+    return listing;
+}
+
+fn instructions_with_branches(program: &TranslatedProgram) -> impl Iterator<Item = (&Instruction, &SourceLocation)> {
+    return instructions(program).filter(|(instruction, _)| condition_name(instruction).is_some());
+}
+
+/// One lcov record per source file. The execution count of a line is the sum of the instructions translated from it,
+/// every conditional jump is a block numbered by its address with the successors NA0 = 0 and NA0 = 1 as branches.
+/// Library routines are left out, lcov tools can not open their files.
+fn format_lcov(program: &TranslatedProgram, coverage: &Coverage) -> String {
+    let mut files: Vec<&str> = Vec::new();
+    for (_, location) in instructions(program) {
+        if !is_library_source(&location.file) && !files.contains(&location.file.as_str()) {
+            files.push(&location.file);
+        }
+    }
+
+    let mut lcov = String::new();
+    for file in files {
+        lcov.push_str(&format!("TN:\nSF:{}\n", file));
+
+        let mut lines: Vec<(usize, u64)> = Vec::new();
+        let (mut branches, mut taken) = (0, 0);
+        for (instruction, location) in instructions(program).filter(|(_, location)| location.file == file) {
+            let address = instruction.address.to_u8() as usize;
+            let executions = coverage.executions[address];
+            match lines.iter_mut().find(|(line, _)| *line == location.line) {
+                Some((_, count)) => *count += executions,
+                None => lines.push((location.line, executions))
+            }
+
+            if condition_name(instruction).is_some() {
+                for (branch, count) in coverage.branches[address].iter().enumerate() {
+                    let count = match (executions, count) {
+                        (0, _) => "-".to_string(),
+                        (_, count) => count.to_string()
+                    };
+                    lcov.push_str(&format!("BRDA:{},{},{},{}\n", location.line, address, branch, count));
+                }
+                branches += 2;
+                taken += coverage.branches[address].iter().filter(|count| **count > 0).count();
+            }
+        }
+        lcov.push_str(&format!("BRF:{}\nBRH:{}\n", branches, taken));
+
+        lines.sort_by_key(|(line, _)| *line);
+        for (line, count) in &lines {
+            lcov.push_str(&format!("DA:{},{}\n", line, count));
+        }
+        lcov.push_str(&format!("LF:{}\nLH:{}\nend_of_record\n", lines.len(), lines.iter().filter(|(_, count)| *count > 0).count()));
+    }
+    return lcov;
+}
+
+#[cfg(test)]
+mod coverage_tests {
+    use super::*;
+    use crate::Assembler;
+    use crate::emulator::Emulator;
+
+    /// Counts R1 down from 2, the jump on the zero output takes both successors, the interrupt test only one.
+    const PROGRAM: &str = "R1 = 02\n\
+        loop {\n\
+            R1 = R1 + FF; FLAGS COPY\n\
+            if zero {\n\
+                loop {\n\
+                }\n\
+            }\n\
+            NOP\n\
+            if inta {\n\
+                R2 = 01\n\
+            }\n\
+        }";
+
+    fn coverage() -> (TranslatedProgram, Coverage) {
+        let program = Assembler::new().translate_program(PROGRAM).unwrap();
+        let mut emulator = Emulator::new(&program);
+        let mut coverage = Coverage::new();
+        while !emulator.is_halted() {
+            coverage.record(&emulator.step().unwrap());
+        }
+        return (program, coverage);
+    }
+
+    #[test]
+    fn test_listing_marks_branches() {
+        let (program, coverage) = coverage();
+        let listing = format_coverage(&program, &coverage, CoverageFormat::Listing);
+
+        assert!(listing.contains("branch on INTA: "));
+        assert!(listing.contains("#####: "));
+        assert!(listing.contains(" taken 1x, "));
+        assert!(listing.ends_with("Branches:     3 of 4 taken\n"));
+    }
+
+    #[test]
+    fn test_lcov_records() {
+        let (program, mut coverage) = coverage();
+        coverage.merge(&coverage.clone());
+        let lcov = format_coverage(&program, &coverage, CoverageFormat::Lcov);
+
+        assert!(lcov.starts_with("TN:\nSF:"));
+        assert!(lcov.contains("DA:1,2\n"));
+        assert!(lcov.contains("BRF:4\nBRH:3\n"));
+        assert!(lcov.ends_with("end_of_record\n"));
+    }
+
+    #[test]
+    fn test_lcov_leaves_out_library_routines() {
+        let program = Assembler::new().translate_program("use std::mul\nR0 = 03\nloop {\n}").unwrap();
+        let lcov = format_coverage(&program, &Coverage::new(), CoverageFormat::Lcov);
+
+        assert!(program.locations.iter().any(|location| location.file == "std::mul"));
+        assert_eq!(1, lcov.matches("SF:").count());
+        assert!(!lcov.contains("std::"));
+    }
+}
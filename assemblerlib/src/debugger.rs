@@ -0,0 +1,385 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Formatter;
+use crate::emulator::{Cycle, Emulator, MachineState};
+use crate::expression::SymbolTable;
+use crate::instruction::{Instruction, InstructionAddress};
+use crate::source::{SourceLine, SourceLocation};
+use crate::translated::{TranslatedLine, TranslatedProgram};
+
+/// Something of the machine whose value is watched or set, written like `R3`, `CF` or `(10)`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Register(u8),
+    CarryFlag,
+    ZeroFlag,
+    NegativeFlag,
+    /// A byte on the bus, with its address.
+    Bus(u8)
+}
+
+impl Target {
+    /// Parses `R0`..`R7`, `CF`, `ZF`, `NF` or a bus address in parentheses or as `mem[10]`. The address is hexadecimal
+    /// like the constants of register transfers, or the name of a `.data` line.
+    pub fn parse(text: &str, symbols: &SymbolTable) -> Result<Target, String> {
+        let text = text.trim();
+        let uppercase_text = text.to_ascii_uppercase();
+        match uppercase_text.as_str() {
+            "CF" | "C" => return Ok(Target::CarryFlag),
+            "ZF" | "Z" => return Ok(Target::ZeroFlag),
+            "NF" | "N" => return Ok(Target::NegativeFlag),
+            _ => {}
+        }
+        if let Some(register) = uppercase_text.strip_prefix('R') {
+            if let Ok(register @ 0..=7) = register.parse::<u8>() {
+                return Ok(Target::Register(register));
+            }
+        }
+        let bus_address = text.strip_prefix('(').and_then(|text| text.strip_suffix(')'))
+            .or_else(|| text.strip_prefix("mem[").and_then(|text| text.strip_suffix(']')));
+        if let Some(address) = bus_address {
+            return Ok(Target::Bus(parse_byte(address, symbols)?));
+        }
+        if symbols.contains_key(text) {
+            return Ok(Target::Bus(parse_byte(text, symbols)?));
+        }
+        return Err(format!("Unknown Target {}, expected R0..R7, CF, ZF, NF or a Bus Address like (10)", text));
+    }
+
+    pub fn value(&self, state: &MachineState) -> u8 {
+        return match self {
+            Target::Register(register) => state.registers[*register as usize],
+            Target::CarryFlag => state.carry_flag as u8,
+            Target::ZeroFlag => state.zero_flag as u8,
+            Target::NegativeFlag => state.negative_flag as u8,
+            Target::Bus(address) => state.memory[*address as usize]
+        };
+    }
+
+    /// Sets the value, flags are set by any value but 0.
+    pub fn set(&self, state: &mut MachineState, value: u8) {
+        match self {
+            Target::Register(register) => state.registers[*register as usize] = value,
+            Target::CarryFlag => state.carry_flag = value != 0,
+            Target::ZeroFlag => state.zero_flag = value != 0,
+            Target::NegativeFlag => state.negative_flag = value != 0,
+            Target::Bus(address) => state.memory[*address as usize] = value
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            Target::Register(register) => write!(f, "R{}", register),
+            Target::CarryFlag => write!(f, "CF"),
+            Target::ZeroFlag => write!(f, "ZF"),
+            Target::NegativeFlag => write!(f, "NF"),
+            Target::Bus(address) => write!(f, "({:02X})", address)
+        };
+    }
+}
+
+/// Parses a byte in hexadecimal, with or without `0x`, in binary with `0b`, or the address of a `.data` line.
+pub fn parse_byte(text: &str, symbols: &SymbolTable) -> Result<u8, String> {
+    let text = text.trim();
+    if let Some(value) = symbols.get(text) {
+        return Ok(*value as u8);
+    }
+    let lowercase_text = text.to_ascii_lowercase();
+    let parsed = match lowercase_text.strip_prefix("0b") {
+        Some(binary) => u8::from_str_radix(binary, 2),
+        None => u8::from_str_radix(lowercase_text.strip_prefix("0x").unwrap_or(&lowercase_text), 16)
+    };
+    return parsed.map_err(|_| format!("Invalid Byte {}, expected 00..FF or the Name of a .data Line", text));
+}
+
+/// Why running stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Breakpoint(InstructionAddress),
+    /// A watched target changed, with the value before and after the cycle.
+    Watchpoint(Target, u8, u8),
+    Halted(InstructionAddress),
+    /// The cycle limit was reached.
+    Limit(u64)
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            Stop::Breakpoint(address) => write!(f, "Breakpoint at {}", address),
+            Stop::Watchpoint(target, before, after) => write!(f, "Watchpoint {} changed from {:02X} to {:02X}", target, before, after),
+            Stop::Halted(address) => write!(f, "Halted at {}", address),
+            Stop::Limit(cycles) => write!(f, "Stopped after {} Cycles", cycles)
+        };
+    }
+}
+
+/// An emulator with breakpoints and watchpoints that knows the source line of every instruction.
+pub struct Debugger {
+    pub emulator: Emulator,
+    program: TranslatedProgram,
+    /// The name of the translated file, a source line without a file name is a line of this file.
+    main_file: String,
+    sources: Vec<SourceLine>,
+    breakpoints: Vec<InstructionAddress>,
+    watchpoints: Vec<Target>
+}
+
+impl Debugger {
+    /// `sources` are the lines the program was translated from, they are shown next to the instructions.
+    pub fn new(program: TranslatedProgram, main_file: &str, sources: Vec<SourceLine>) -> Debugger {
+        return Debugger {
+            emulator: Emulator::new(&program),
+            program,
+            main_file: main_file.to_string(),
+            sources,
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new()
+        };
+    }
+
+    pub fn breakpoints(&self) -> &[InstructionAddress] {
+        return &self.breakpoints;
+    }
+
+    pub fn watchpoints(&self) -> &[Target] {
+        return &self.watchpoints;
+    }
+
+    pub fn symbols(&self) -> &SymbolTable {
+        return &self.program.symbols;
+    }
+
+    /// Adds a breakpoint on an instruction address like `00011`, or on the instruction translated from a source line,
+    /// given as `LINE` of the main file or as `FILE:LINE`. Returns the address of the breakpoint.
+    pub fn add_breakpoint(&mut self, label: &str) -> Result<InstructionAddress, String> {
+        let address = self.resolve_label(label.trim())?;
+        if !self.breakpoints.contains(&address) {
+            self.breakpoints.push(address);
+        }
+        return Ok(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, label: &str) -> Result<InstructionAddress, String> {
+        let address = self.resolve_label(label.trim())?;
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| *breakpoint != address);
+        return match self.breakpoints.len() < count {
+            true => Ok(address),
+            false => Err(format!("There is no Breakpoint at {}", address))
+        };
+    }
+
+    pub fn add_watchpoint(&mut self, target: &str) -> Result<Target, String> {
+        let target = Target::parse(target, &self.program.symbols)?;
+        if !self.watchpoints.contains(&target) {
+            self.watchpoints.push(target);
+        }
+        return Ok(target);
+    }
+
+    pub fn remove_watchpoint(&mut self, target: &str) -> Result<Target, String> {
+        let target = Target::parse(target, &self.program.symbols)?;
+        let count = self.watchpoints.len();
+        self.watchpoints.retain(|watchpoint| *watchpoint != target);
+        return match self.watchpoints.len() < count {
+            true => Ok(target),
+            false => Err(format!("There is no Watchpoint on {}", target))
+        };
+    }
+
+    /// Sets a register, a flag or a byte on the bus, like `set R3 42`.
+    pub fn poke(&mut self, target: &str, value: &str) -> Result<Target, String> {
+        let target = Target::parse(target, &self.program.symbols)?;
+        let value = parse_byte(value, &self.program.symbols)?;
+        target.set(&mut self.emulator.state, value);
+        return Ok(target);
+    }
+
+    /// What the next cycle will do, without doing it.
+    pub fn preview(&self) -> Result<Cycle, String> {
+        return self.emulator.clone().step();
+    }
+
+    pub fn step(&mut self) -> Result<Cycle, String> {
+        return self.emulator.step();
+    }
+
+    /// Executes at least one cycle and stops before an instruction with a breakpoint, after a cycle that changed
+    /// a watched target, at a halt or after `cycles` cycles.
+    pub fn resume(&mut self, cycles: u64) -> Result<Stop, String> {
+        for executed in 0..cycles {
+            if self.emulator.is_halted() {
+                return Ok(Stop::Halted(self.emulator.state.address));
+            }
+            if executed > 0 && self.breakpoints.contains(&self.emulator.state.address) {
+                return Ok(Stop::Breakpoint(self.emulator.state.address));
+            }
+
+            let before: Vec<u8> = self.watchpoints.iter().map(|target| target.value(&self.emulator.state)).collect();
+            self.emulator.step()?;
+            for (target, before) in self.watchpoints.iter().zip(before) {
+                let after = target.value(&self.emulator.state);
+                if after != before {
+                    return Ok(Stop::Watchpoint(*target, before, after));
+                }
+            }
+        }
+
+        return Ok(match self.breakpoints.contains(&self.emulator.state.address) {
+            true => Stop::Breakpoint(self.emulator.state.address),
+            false => Stop::Limit(cycles)
+        });
+    }
+
+    /// The location and text of the source line the instruction at the address was translated from.
+    pub fn source_line(&self, address: InstructionAddress) -> Option<(&SourceLocation, &str)> {
+        let location = self.program.lines.iter().zip(self.program.locations.iter())
+            .find(|(line, _)| matches!(line, TranslatedLine::InstructionLine(instruction, _) if instruction.address == address))
+            .map(|(_, location)| location)?;
+        let text = self.sources.iter()
+            .find(|source_line| source_line.location == *location)
+            .map(|source_line| source_line.text.trim())
+            .unwrap_or("");
+        return Some((location, text));
+    }
+
+    fn resolve_label(&self, label: &str) -> Result<InstructionAddress, String> {
+        if label.len() == 5 && label.chars().all(|ch| ch == '0' || ch == '1') {
+            return InstructionAddress::try_from(label.to_string()).map_err(|error| error.description);
+        }
+
+        let (file, line) = match label.rsplit_once(':') {
+            Some((file, line)) => (Some(file), line),
+            None => (None, label)
+        };
+        let line: usize = line.parse()
+            .map_err(|_| format!("Unknown Label {}, expected an Address like 00011 or a Source Line like 12 or FILE:12", label))?;
+
+        return self.program.lines.iter().zip(self.program.locations.iter())
+            .find_map(|(translated, location)| match translated {
+                TranslatedLine::InstructionLine(instruction, _) if location.line == line
+                    && file.map_or(location.file == self.main_file, |file| location.file.ends_with(file)) => Some(instruction.address),
+                _ => None
+            })
+            .ok_or_else(|| format!("There is no Instruction on Line {}", label));
+    }
+}
+
+/// The name of the signal that decides NA0 of the next address, `None` for unconditional jumps.
+pub fn condition_name(instruction: &Instruction) -> Option<&'static str> {
+    return match (instruction.address_control.ac1, instruction.address_control.ac0, instruction.next_address.bit0) {
+        (false, false, _) => None,
+        (false, true, false) => Some("INTA"),
+        (false, true, true) => Some("CF"),
+        (true, false, false) => Some("CO"),
+        (true, false, true) => Some("ZO"),
+        (true, true, false) => Some("NO"),
+        (true, true, true) => Some("INTB")
+    };
+}
+
+/// The ALU inputs and outputs of a cycle, what it wrote and the address it goes to.
+pub fn format_cycle(cycle: &Cycle) -> String {
+    let output = &cycle.output;
+    let mut text = format!("ALU:     A={:02X} B={:02X} -> F={:02X} CO={} ZO={} NO={}\n",
+        cycle.a, cycle.b, output.result, output.carry as u8, output.zero as u8, output.negative as u8);
+    if let Some((address, value)) = cycle.bus_read {
+        text.push_str(&format!("Bus:     read {:02X} from ({:02X})\n", value, address));
+    }
+    if let Some((address, value)) = cycle.bus_write {
+        text.push_str(&format!("Bus:     write {:02X} to ({:02X})\n", value, address));
+    }
+    if let Some(register) = cycle.written_register {
+        text.push_str(&format!("Write:   R{} = {:02X}\n", register, output.result));
+    }
+    if cycle.instruction.alu_control.cf {
+        text.push_str(&format!("Flags:   CF={} ZF={} NF={}\n", output.carry as u8, output.zero as u8, output.negative as u8));
+    }
+    text.push_str(&match condition_name(&cycle.instruction) {
+        Some(condition) => format!("Next:    {} = {}, jumps to {}\n", condition, cycle.next_address.bit0 as u8, cycle.next_address),
+        None => format!("Next:    jumps to {}\n", cycle.next_address)
+    });
+    return text;
+}
+
+#[cfg(test)]
+mod debugger_tests {
+    use super::*;
+    use crate::Assembler;
+    use crate::source::{load_string, TemporaryDirectory, ANONYMOUS_SOURCE};
+    use std::path::Path;
+
+    const PROGRAM: &str = ".data COUNT 0xF8: 3\n\
+        R0 = COUNT\n\
+        R1 = (R0)\n\
+        loop {\n\
+            R1 = R1 + FF; FLAGS COPY\n\
+            if zero {\n\
+                break\n\
+            }\n\
+        }\n\
+        (R0) = R1\n\
+        loop {\n\
+        }";
+
+    fn debugger() -> Debugger {
+        let program = Assembler::new().translate_program(PROGRAM).unwrap();
+        let sources = load_string(ANONYMOUS_SOURCE, PROGRAM, Path::new("."), &SymbolTable::new()).unwrap();
+        return Debugger::new(program, ANONYMOUS_SOURCE, sources);
+    }
+
+    #[test]
+    fn test_breakpoints_and_watchpoints() {
+        let mut debugger = debugger();
+        let decrement = debugger.add_breakpoint("5").unwrap();
+        assert_eq!(Some("R1 = R1 + FF; FLAGS COPY"), debugger.source_line(decrement).map(|(_, text)| text));
+
+        assert_eq!(Stop::Breakpoint(decrement), debugger.resume(100).unwrap());
+        assert_eq!(3, debugger.emulator.state.registers[1]);
+        assert_eq!(Stop::Breakpoint(decrement), debugger.resume(100).unwrap());
+        assert_eq!(2, debugger.emulator.state.registers[1]);
+
+        debugger.remove_breakpoint(&decrement.to_string()).unwrap();
+        assert!(debugger.remove_breakpoint("5").is_err());
+        debugger.add_watchpoint("COUNT").unwrap();
+        assert_eq!(Stop::Watchpoint(Target::Bus(0xF8), 3, 0), debugger.resume(100).unwrap());
+        assert!(matches!(debugger.resume(100).unwrap(), Stop::Halted(_)));
+    }
+
+    #[test]
+    fn test_poke_and_preview() {
+        let mut debugger = debugger();
+        debugger.resume(2).unwrap();
+        debugger.poke("R1", "1").unwrap();
+        debugger.poke("mem[COUNT]", "0x7F").unwrap();
+        assert_eq!(0x7F, debugger.emulator.state.memory[0xF8]);
+
+        let preview = debugger.preview().unwrap();
+        assert_eq!(2, debugger.emulator.state.cycles);
+        assert_eq!(preview, debugger.step().unwrap());
+        assert_eq!(Some("ZO"), condition_name(&preview.instruction));
+        assert!(format_cycle(&preview).contains("ZO = 1, jumps to"));
+
+        assert!(debugger.poke("R8", "00").is_err());
+        assert!(debugger.poke("R1", "100").is_err());
+        assert!(debugger.add_breakpoint("99").is_err());
+    }
+
+    #[test]
+    fn test_lines_without_file_are_in_the_main_file() {
+        let directory = TemporaryDirectory::new("debugger");
+        std::fs::write(directory.join("setup.2ia"), "R2 = 01\nR3 = 02").unwrap();
+        let source = ".include \"setup.2ia\"\nR4 = 03\nloop {\n}";
+
+        let program = Assembler::new().translate_source("main.2ia", source, &directory.path).unwrap();
+        let sources = load_string("main.2ia", source, &directory.path, &SymbolTable::new()).unwrap();
+        let mut debugger = Debugger::new(program, "main.2ia", sources);
+        let address = debugger.add_breakpoint("2").unwrap();
+        assert_eq!(Some("R4 = 03"), debugger.source_line(address).map(|(_, text)| text));
+        assert_eq!(Stop::Breakpoint(address), debugger.resume(100).unwrap());
+        assert_eq!([0, 0, 1, 2, 0], debugger.emulator.state.registers[..5]);
+    }
+}
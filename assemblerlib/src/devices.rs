@@ -0,0 +1,312 @@
+use crate::memory::IO_PORTS;
+
+/// How many cycles a button stays pressed after a key press, terminals do not tell when a key is released.
+const BUTTON_HOLD_CYCLES: u32 = 100;
+
+/// The segments a, b, c, d, e, f and g (bits 0 to 6) that show the hexadecimal digits on a 7-segment display.
+const DIGIT_SEGMENTS: [u8; 16] = [0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F, 0x77, 0x7C, 0x39, 0x5E, 0x79, 0x71];
+
+/// Something on the bus that answers reads and writes of its ports instead of the memory, like the I/O ports FC..FF.
+/// Devices are cloned with the emulator, so a preview of the next cycle does not change them.
+pub trait BusDevice {
+    fn name(&self) -> &str;
+
+    fn handles(&self, address: u8) -> bool;
+
+    /// The byte the program reads from the port.
+    fn read(&mut self, address: u8) -> u8;
+
+    /// A byte the program writes to the port, input devices ignore it.
+    fn write(&mut self, address: u8, value: u8);
+
+    /// Called at the end of every cycle, for devices that change over time.
+    fn tick(&mut self) {}
+
+    /// Handles a key pressed in a live view, returns whether the device used it.
+    fn press(&mut self, _key: char) -> bool {
+        return false;
+    }
+
+    /// The lines that show the device in a live view.
+    fn render(&self) -> Vec<String>;
+
+    fn clone_device(&self) -> Box<dyn BusDevice>;
+}
+
+impl Clone for Box<dyn BusDevice> {
+    fn clone(&self) -> Self {
+        return self.clone_device();
+    }
+}
+
+/// Eight switches that are read as one byte, the keys 0 to 7 toggle the switch of that bit.
+#[derive(Clone)]
+pub struct Switches {
+    pub port: u8,
+    pub value: u8
+}
+
+impl BusDevice for Switches {
+    fn name(&self) -> &str {
+        return "Switches";
+    }
+
+    fn handles(&self, address: u8) -> bool {
+        return address == self.port;
+    }
+
+    fn read(&mut self, _address: u8) -> u8 {
+        return self.value;
+    }
+
+    fn write(&mut self, _address: u8, _value: u8) {}
+
+    fn press(&mut self, key: char) -> bool {
+        return match key.to_digit(10) {
+            Some(bit @ 0..=7) => {
+                self.value ^= 1 << bit;
+                true
+            },
+            _ => false
+        };
+    }
+
+    fn render(&self) -> Vec<String> {
+        return vec![format!("Switches {:02X}: {} (keys 7..0)", self.port, bits(self.value, '1', '0'))];
+    }
+
+    fn clone_device(&self) -> Box<dyn BusDevice> {
+        return Box::new(self.clone());
+    }
+}
+
+/// Four push buttons in the bits 0 to 3, the keys a to d press them for a moment.
+#[derive(Clone)]
+pub struct Buttons {
+    pub port: u8,
+    /// The cycles every button stays pressed.
+    pub held: [u32; 4]
+}
+
+impl Buttons {
+    pub fn new(port: u8) -> Buttons {
+        return Buttons { port, held: [0; 4] };
+    }
+
+    fn value(&self) -> u8 {
+        return self.held.iter().enumerate()
+            .filter(|(_, held)| **held > 0)
+            .fold(0, |value, (button, _)| value | 1 << button);
+    }
+}
+
+impl BusDevice for Buttons {
+    fn name(&self) -> &str {
+        return "Buttons";
+    }
+
+    fn handles(&self, address: u8) -> bool {
+        return address == self.port;
+    }
+
+    fn read(&mut self, _address: u8) -> u8 {
+        return self.value();
+    }
+
+    fn write(&mut self, _address: u8, _value: u8) {}
+
+    fn tick(&mut self) {
+        self.held.iter_mut().for_each(|held| *held = held.saturating_sub(1));
+    }
+
+    fn press(&mut self, key: char) -> bool {
+        return match "abcd".find(key.to_ascii_lowercase()) {
+            Some(button) => {
+                self.held[button] = BUTTON_HOLD_CYCLES;
+                true
+            },
+            None => false
+        };
+    }
+
+    fn render(&self) -> Vec<String> {
+        let buttons: Vec<String> = ['a', 'b', 'c', 'd'].iter().zip(self.held.iter())
+            .rev()
+            .map(|(name, held)| match *held > 0 {
+                true => format!("[{}]", name.to_ascii_uppercase()),
+                false => format!(" {} ", name)
+            })
+            .collect();
+        return vec![format!("Buttons  {:02X}: {} (keys d..a)", self.port, buttons.join(""))];
+    }
+
+    fn clone_device(&self) -> Box<dyn BusDevice> {
+        return Box::new(self.clone());
+    }
+}
+
+/// Eight LEDs that show the last byte written to the port.
+#[derive(Clone)]
+pub struct Leds {
+    pub port: u8,
+    pub value: u8
+}
+
+impl BusDevice for Leds {
+    fn name(&self) -> &str {
+        return "LEDs";
+    }
+
+    fn handles(&self, address: u8) -> bool {
+        return address == self.port;
+    }
+
+    fn read(&mut self, _address: u8) -> u8 {
+        return self.value;
+    }
+
+    fn write(&mut self, _address: u8, value: u8) {
+        self.value = value;
+    }
+
+    fn render(&self) -> Vec<String> {
+        return vec![format!("LEDs     {:02X}: {}", self.port, bits(self.value, '●', '○'))];
+    }
+
+    fn clone_device(&self) -> Box<dyn BusDevice> {
+        return Box::new(self.clone());
+    }
+}
+
+/// Two 7-segment digits behind a hexadecimal decoder, they show the last byte written to the port.
+#[derive(Clone)]
+pub struct SevenSegment {
+    pub port: u8,
+    pub value: u8
+}
+
+impl BusDevice for SevenSegment {
+    fn name(&self) -> &str {
+        return "7-Segment";
+    }
+
+    fn handles(&self, address: u8) -> bool {
+        return address == self.port;
+    }
+
+    fn read(&mut self, _address: u8) -> u8 {
+        return self.value;
+    }
+
+    fn write(&mut self, _address: u8, value: u8) {
+        self.value = value;
+    }
+
+    fn render(&self) -> Vec<String> {
+        let digits = [DIGIT_SEGMENTS[(self.value >> 4) as usize], DIGIT_SEGMENTS[(self.value & 0xF) as usize]];
+        let segment = |segments: u8, bit: u8, symbol: char| match segments & 1 << bit {
+            0 => ' ',
+            _ => symbol
+        };
+        let rows: Vec<String> = (0..3)
+            .map(|row| digits.iter()
+                .map(|segments| match row {
+                    0 => format!(" {} ", segment(*segments, 0, '_')),
+                    1 => format!("{}{}{}", segment(*segments, 5, '|'), segment(*segments, 6, '_'), segment(*segments, 1, '|')),
+                    _ => format!("{}{}{}", segment(*segments, 4, '|'), segment(*segments, 3, '_'), segment(*segments, 2, '|'))
+                })
+                .collect::<Vec<String>>()
+                .join(" "))
+            .collect();
+        return vec![
+            format!("7-Seg    {:02X}: {}", self.port, rows[0]),
+            format!("             {}", rows[1]),
+            format!("             {}", rows[2])
+        ];
+    }
+
+    fn clone_device(&self) -> Box<dyn BusDevice> {
+        return Box::new(self.clone());
+    }
+}
+
+/// The devices of the lab board: switches on FC, buttons on FD, LEDs on FE and the 7-segment display on FF.
+pub fn standard_board() -> Vec<Box<dyn BusDevice>> {
+    let mut ports = IO_PORTS;
+    let mut port = || ports.next().unwrap_or(0xFF);
+    return vec![
+        Box::new(Switches { port: port(), value: 0 }),
+        Box::new(Buttons::new(port())),
+        Box::new(Leds { port: port(), value: 0 }),
+        Box::new(SevenSegment { port: port(), value: 0 })
+    ];
+}
+
+/// The bits of a byte from bit 7 down to bit 0, in groups of four.
+fn bits(value: u8, one: char, zero: char) -> String {
+    let symbols: Vec<char> = (0..8).rev()
+        .map(|bit| match value & 1 << bit {
+            0 => zero,
+            _ => one
+        })
+        .collect();
+    return format!("{} {}", symbols[..4].iter().collect::<String>(), symbols[4..].iter().collect::<String>());
+}
+
+#[cfg(test)]
+mod devices_tests {
+    use super::*;
+    use crate::Assembler;
+    use crate::emulator::Emulator;
+
+    /// Counts on the LEDs while button a is held and shows the switches on the 7-segment display.
+    const COUNTER: &str = "R1 = 00\n\
+        loop {\n\
+            R0 = FD\n\
+            R2 = (R0); FLAGS COPY\n\
+            if not zero {\n\
+                R1 = R1 + 01\n\
+            }\n\
+            R0 = FE\n\
+            (R0) = R1\n\
+            R0 = FC\n\
+            R2 = (R0)\n\
+            R0 = FF\n\
+            (R0) = R2\n\
+        }";
+
+    fn board() -> Emulator {
+        let mut emulator = Emulator::new(&Assembler::new().translate_program(COUNTER).unwrap());
+        standard_board().into_iter().for_each(|device| emulator.attach(device));
+        return emulator;
+    }
+
+    #[test]
+    fn test_counter_on_the_board() {
+        let mut emulator = board();
+        assert!(emulator.press('a'));
+        assert!(emulator.press('3'));
+        assert!(!emulator.press('x'));
+
+        let before = emulator.clone();
+        emulator.run(20).unwrap();
+        assert_eq!(before.state.cycles, 0);
+        assert_eq!(vec!["LEDs     FE: ○○○○ ○○●○"], emulator.devices()[2].render());
+        assert_eq!(0x08, emulator.state.memory[0xFF]);
+
+        emulator.run(400).unwrap();
+        assert_eq!(vec!["Buttons  FD:  d  c  b  a  (keys d..a)"], emulator.devices()[1].render());
+        assert_eq!(vec!["Switches FC: 0000 1000 (keys 7..0)"], emulator.devices()[0].render());
+    }
+
+    #[test]
+    fn test_seven_segment_digits() {
+        let mut display = SevenSegment { port: 0xFF, value: 0 };
+        display.write(0xFF, 0x2F);
+        assert_eq!(vec![
+            "7-Seg    FF:  _   _ ".to_string(),
+            "              _| |_ ".to_string(),
+            "             |_  |  ".to_string()
+        ], display.render());
+    }
+}
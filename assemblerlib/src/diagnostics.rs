@@ -0,0 +1,469 @@
+use std::fmt::Display;
+
+/// A kind of error with a code that stays the same between versions, so that documentation can refer to it.
+/// Codes of instruction lines start at E0001, of includes, conditional assembly and directives at E0101,
+/// of structured code at E0201, of data sections at E0301, of the tools working on source files at E0401
+/// and of running programs at E0501.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub explanation: &'static str,
+    /// Source that causes the diagnostic.
+    pub wrong: &'static str,
+    /// The same source, corrected.
+    pub right: &'static str
+}
+
+impl Diagnostic {
+    /// The message of an error of this kind, like `E0003 invalid register: R8`.
+    pub fn message<T: Display>(&self, detail: T) -> String {
+        return format!("{} {}: {}", self.code, self.name, detail);
+    }
+}
+
+pub(crate) const MISSING_ADDRESS: Diagnostic = Diagnostic {
+    code: "E0001",
+    name: "missing address",
+    explanation: "Every Line of a .2i File starts with the five Bit Address of its Instruction, followed by a colon and the 25 Instruction Bits.",
+    wrong: "00 00001 00 000 0000 01 00 0001 0",
+    right: "00000: 00 00001 00 000 0000 01 00 0001 0"
+};
+
+pub(crate) const INVALID_ADDRESS: Diagnostic = Diagnostic {
+    code: "E0002",
+    name: "invalid address",
+    explanation: "Instruction Addresses and Jump Targets are written as exactly five binary Digits, from 00000 to 11111.",
+    wrong: "0001: NOP; JUMP 00010",
+    right: "00001: NOP; JUMP 00010"
+};
+
+pub(crate) const INVALID_REGISTER: Diagnostic = Diagnostic {
+    code: "E0003",
+    name: "invalid register",
+    explanation: "The 2i has the eight Registers R0 to R7, they are written as R and a single Digit.",
+    wrong: "00000: MOV R8, R0; JUMP 00000",
+    right: "00000: MOV R3, R0; JUMP 00000"
+};
+
+pub(crate) const INVALID_CONSTANT: Diagnostic = Diagnostic {
+    code: "E0004",
+    name: "invalid constant",
+    explanation: "A Constant is the 4 Bit Register Address B, sign extended to 8 Bits. It is written as four binary Digits in Commands \
+        and as two hexadecimal Digits in Register Transfers, where only 00 to 07 and F8 to FF can be sign extended.",
+    wrong: "00000: R0 = 10; goto 00001",
+    right: "00000: R0 = 07; goto 00001"
+};
+
+pub(crate) const WRONG_COMMAND_COUNT: Diagnostic = Diagnostic {
+    code: "E0005",
+    name: "wrong command count",
+    explanation: "A Code Line has an ALU Command, a Write Command, a Bus Command, a Flag Command and a Jump Command, separated by ;. \
+        Pseudo Instructions and Register Transfers replace the first four Commands, the Jump Command is always last.",
+    wrong: "00000: ADD R0 R1; WRITE A; JUMP 00001",
+    right: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"
+};
+
+pub(crate) const UNKNOWN_ALU_COMMAND: Diagnostic = Diagnostic {
+    code: "E0006",
+    name: "unknown alu command",
+    explanation: "The ALU Command must be one of the ALU Functions of the Dialect, like ADD or PASSB in the standard Dialect.",
+    wrong: "00000: MUL R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001",
+    right: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"
+};
+
+pub(crate) const WRONG_OPERANDS: Diagnostic = Diagnostic {
+    code: "E0007",
+    name: "wrong operands",
+    explanation: "ALU Commands and Pseudo Instructions take a fixed Number of Operands. Shifts take one Register, the other ALU Commands \
+        a Register and a Register or Constant. Bus Addresses of Pseudo Instructions are a Register in Brackets, like [R0].",
+    wrong: "00000: ADD R0; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001",
+    right: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"
+};
+
+pub(crate) const UNKNOWN_WRITE_COMMAND: Diagnostic = Diagnostic {
+    code: "E0008",
+    name: "unknown write command",
+    explanation: "The Write Command selects which Register the ALU Result is written to, WRITE A, WRITE B or WRITE OFF in the standard Dialect.",
+    wrong: "00000: ADD R0 R1; WRITE C; BUS OFF; FLAGS KEEP; JUMP 00001",
+    right: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"
+};
+
+pub(crate) const UNKNOWN_BUS_COMMAND: Diagnostic = Diagnostic {
+    code: "E0009",
+    name: "unknown bus command",
+    explanation: "The Bus Command is BUS READ, BUS WRITE or BUS OFF in the standard Dialect.",
+    wrong: "00000: ADD R0 R1; WRITE A; BUS ON; FLAGS KEEP; JUMP 00001",
+    right: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"
+};
+
+pub(crate) const UNKNOWN_FLAG_COMMAND: Diagnostic = Diagnostic {
+    code: "E0010",
+    name: "unknown flag command",
+    explanation: "The Flag Command is FLAGS COPY or FLAGS KEEP in the standard Dialect.",
+    wrong: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS SET; JUMP 00001",
+    right: "00000: ADD R0 R1; WRITE A; BUS OFF; FLAGS COPY; JUMP 00001"
+};
+
+pub(crate) const INVALID_JUMP: Diagnostic = Diagnostic {
+    code: "E0011",
+    name: "invalid jump",
+    explanation: "The Jump Command is a Jump Type followed by the Next Address, like JUMP 00001 or JUMPZO 00011, \
+        or goto NA or if CONDITION goto NA else NA.",
+    wrong: "00000: NOP; JMP 00001",
+    right: "00000: NOP; JUMP 00001"
+};
+
+pub(crate) const UNKNOWN_PSEUDO_INSTRUCTION: Diagnostic = Diagnostic {
+    code: "E0012",
+    name: "unknown pseudo instruction",
+    explanation: "A Code Line with a single Command in front of the Jump is a Pseudo Instruction: MOV, LDI, CLR, IN, OUT, CLC, SETC or NOP.",
+    wrong: "00000: MOVE R0, R1; JUMP 00001",
+    right: "00000: MOV R0, R1; JUMP 00001"
+};
+
+pub(crate) const IMPOSSIBLE_TRANSFER: Diagnostic = Diagnostic {
+    code: "E0013",
+    name: "impossible transfer",
+    explanation: "A Register Transfer must be done by one Instruction. Only the A or B Register can be written, \
+        only the A Input can be read from the Bus and only the B Input can be a Constant.",
+    wrong: "00000: R2 = R0 + R1; goto 00001",
+    right: "00000: R1 = R0 + R1; goto 00001"
+};
+
+pub(crate) const INVALID_TRANSFER: Diagnostic = Diagnostic {
+    code: "E0014",
+    name: "invalid transfer",
+    explanation: "A Register Transfer assigns a Register, (Register) or F an Expression of the ALU, like R0 + R1 + C, R0 NOR R1 or ASR R0.",
+    wrong: "00000: R0 = R0 * R1; goto 00001",
+    right: "00000: R0 = R0 + R1; goto 00001"
+};
+
+pub(crate) const INVALID_WORD: Diagnostic = Diagnostic {
+    code: "E0015",
+    name: "invalid word",
+    explanation: "An Instruction Word has 25 Bits, written as binary Digits in .2i Files and as binary or hexadecimal Number for explain.",
+    wrong: "00000: 00 00001 00 000 0000 01 00 0001",
+    right: "00000: 00 00001 00 000 0000 01 00 0001 0"
+};
+
+pub(crate) const NO_SOURCE_LINE: Diagnostic = Diagnostic {
+    code: "E0016",
+    name: "no source line",
+    explanation: "Words that no Command of the Language produces, like enabling the Bus for reading while the A Input \
+        comes from a Register, can not be disassembled.",
+    wrong: "00000: 00 00001 01 000 0001 00 00 0000 0",
+    right: "00000: 00 00001 01 000 0001 00 10 0000 0"
+};
+
+pub(crate) const MISSING_FILE: Diagnostic = Diagnostic {
+    code: "E0101",
+    name: "missing file",
+    explanation: "An included File is looked up relative to the File that includes it and must be readable.",
+    wrong: ".include \"mth.2ia\"",
+    right: ".include \"math.2ia\""
+};
+
+pub(crate) const INCLUDE_CYCLE: Diagnostic = Diagnostic {
+    code: "E0102",
+    name: "include cycle",
+    explanation: "A File must not include itself, directly or through other Files. The Message lists the Files of the Cycle.",
+    wrong: "# a.2ia\n.include \"b.2ia\"\n# b.2ia\n.include \"a.2ia\"",
+    right: "# a.2ia\n.include \"b.2ia\"\n# b.2ia\n00000: NOP; JUMP 00000"
+};
+
+pub(crate) const INVALID_INCLUDE: Diagnostic = Diagnostic {
+    code: "E0103",
+    name: "invalid include",
+    explanation: "The Path of an .include is enclosed in double Quotes.",
+    wrong: ".include lib.2ia",
+    right: ".include \"lib.2ia\""
+};
+
+pub(crate) const UNBALANCED_CONDITIONAL: Diagnostic = Diagnostic {
+    code: "E0104",
+    name: "unbalanced conditional",
+    explanation: "Every .if, .ifdef and .ifndef is closed by an .endif in the same File, .elif and .else are only allowed in front of it.",
+    wrong: ".if 1\n00000: NOP; JUMP 00000",
+    right: ".if 1\n00000: NOP; JUMP 00000\n.endif"
+};
+
+pub(crate) const INVALID_SYMBOL_NAME: Diagnostic = Diagnostic {
+    code: "E0105",
+    name: "invalid symbol name",
+    explanation: "Symbol Names start with a Letter or _ and continue with Letters, Digits and _.",
+    wrong: ".define 1X 2",
+    right: ".define X1 2"
+};
+
+pub(crate) const INVALID_EXPRESSION: Diagnostic = Diagnostic {
+    code: "E0106",
+    name: "invalid expression",
+    explanation: "Expressions are made of Numbers, Symbols, defined(NAME), Parentheses and the Operators of C, and must not divide by zero.",
+    wrong: ".if 1 +\n.endif",
+    right: ".if 1 + 1\n.endif"
+};
+
+pub(crate) const UNKNOWN_SYMBOL: Diagnostic = Diagnostic {
+    code: "E0107",
+    name: "unknown symbol",
+    explanation: "A Symbol must be defined before it is used, with -D, .define or as the Name of a .data Line.",
+    wrong: ".if SPEED > 2\n.endif",
+    right: ".define SPEED 3\n.if SPEED > 2\n.endif"
+};
+
+pub(crate) const UNKNOWN_DIALECT: Diagnostic = Diagnostic {
+    code: "E0108",
+    name: "unknown dialect",
+    explanation: "A File selects its Dialect with .dialect standard or .dialect course.",
+    wrong: ".dialect lecture",
+    right: ".dialect course"
+};
+
+pub(crate) const UNKNOWN_ROUTINE: Diagnostic = Diagnostic {
+    code: "E0109",
+    name: "unknown routine",
+    explanation: "use loads one Routine of the Standard Library, written as std::NAME.",
+    wrong: "use std::div",
+    right: "use std::mul"
+};
+
+pub(crate) const UNKNOWN_CONDITION: Diagnostic = Diagnostic {
+    code: "E0201",
+    name: "unknown condition",
+    explanation: "if and while test zero, negative, carry, carry flag, inta or intb, optionally negated with not.",
+    wrong: "R0 = R0 + FF; FLAGS COPY\nif positive {\n    R1 = R0\n}",
+    right: "R0 = R0 + FF; FLAGS COPY\nif negative {\n    R1 = R0\n}"
+};
+
+pub(crate) const UNBALANCED_BLOCK: Diagnostic = Diagnostic {
+    code: "E0202",
+    name: "unbalanced block",
+    explanation: "Every Block of structured Code opened with { is closed with }, an else Block follows an if Block.",
+    wrong: "loop {\n    R0 = R0 + 01",
+    right: "loop {\n    R0 = R0 + 01\n}"
+};
+
+pub(crate) const BREAK_OUTSIDE_LOOP: Diagnostic = Diagnostic {
+    code: "E0203",
+    name: "break outside loop",
+    explanation: "break leaves the innermost loop or while Block, so it must be inside of one.",
+    wrong: "R0 = R0 + 01\nbreak",
+    right: "loop {\n    R0 = R0 + 01; FLAGS COPY\n    if zero {\n        break\n    }\n}"
+};
+
+pub(crate) const UNRESOLVED_JUMP: Diagnostic = Diagnostic {
+    code: "E0204",
+    name: "unresolved jump",
+    explanation: "The Compiler of structured Code lost the Target of a Jump. This is a Bug of the Assembler and not of the Program, \
+        please report the Program that causes it.",
+    wrong: "",
+    right: ""
+};
+
+pub(crate) const ADDRESS_CONFLICT: Diagnostic = Diagnostic {
+    code: "E0205",
+    name: "address conflict",
+    explanation: "Structured Code starts at 00000 and Branches from it need 00001, Lines with Address must not use these Addresses.",
+    wrong: "00000: NOP; JUMP 00000\nR0 = R0 + 01",
+    right: "R0 = R0 + 01\n00010: NOP; JUMP 00010"
+};
+
+pub(crate) const CONTROL_STORE_FULL: Diagnostic = Diagnostic {
+    code: "E0206",
+    name: "control store full",
+    explanation: "The Control Store has 32 Addresses, the structured Code must fit into the ones Lines with Address leave free.",
+    wrong: "# 33 Lines of structured Code",
+    right: "# at most 32 Lines of structured Code"
+};
+
+pub(crate) const CONDITION_WITHOUT_INSTRUCTION: Diagnostic = Diagnostic {
+    code: "E0207",
+    name: "condition without instruction",
+    explanation: "The Condition of an if or while tests the Instruction right in front of it, so a Block can not start with one.",
+    wrong: "if zero {\n    R1 = R0\n}",
+    right: "R0 = R0 + 00; FLAGS COPY\nif zero {\n    R1 = R0\n}"
+};
+
+pub(crate) const DATA_ON_IO_PORT: Diagnostic = Diagnostic {
+    code: "E0301",
+    name: "data on i/o port",
+    explanation: "The Bus Addresses FC to FF are the I/O Ports, .data can not place Bytes there.",
+    wrong: ".data 0xFC: 1",
+    right: ".data 0xF0: 1"
+};
+
+pub(crate) const DATA_OVERLAP: Diagnostic = Diagnostic {
+    code: "E0302",
+    name: "data overlap",
+    explanation: "Every Byte of the Memory Image is placed by one .data Line only.",
+    wrong: ".data 0x10: 1, 2\n.data 0x11: 3",
+    right: ".data 0x10: 1, 2\n.data 0x12: 3"
+};
+
+pub(crate) const DATA_OUT_OF_RANGE: Diagnostic = Diagnostic {
+    code: "E0303",
+    name: "data out of range",
+    explanation: "Data Addresses are Bus Addresses 00 to FF and Data Values are Bytes from -128 to 255.",
+    wrong: ".data 0x10: 256",
+    right: ".data 0x10: 255"
+};
+
+pub(crate) const INVALID_DATA_DIRECTIVE: Diagnostic = Diagnostic {
+    code: "E0304",
+    name: "invalid data directive",
+    explanation: "A Data Line is written as .data [NAME] ADDRESS: VALUE, VALUE, ...",
+    wrong: ".data 0x10 1, 2",
+    right: ".data 0x10: 1, 2"
+};
+
+pub(crate) const INVALID_DATA_SYMBOL: Diagnostic = Diagnostic {
+    code: "E0305",
+    name: "invalid data symbol",
+    explanation: "The Name of a .data Line must not be a Register, Constant or Operator of Register Transfers and must not be defined already.",
+    wrong: ".data R1 0x10: 1",
+    right: ".data TABLE 0x10: 1"
+};
+
+pub(crate) const FORMAT_CHANGES_MEANING: Diagnostic = Diagnostic {
+    code: "E0401",
+    name: "format changes meaning",
+    explanation: "The formatted Source would translate to other Instructions than the original Source, so the File is left as it is. \
+        This is a Bug of the Formatter and not of the Program, please report the Program that causes it.",
+    wrong: "",
+    right: ""
+};
+
+pub(crate) const INVALID_TEST_CASE: Diagnostic = Diagnostic {
+    code: "E0402",
+    name: "invalid test case",
+    explanation: "A `# TEST` Comment could not be read. A Test Case has a Name, a Setup and the expected Values, \
+        like `# TEST add: mem[FC]=3 mem[FD]=5 -> mem[FE]=8 within 200 cycles`. The Setup and the Values are TARGET=VALUE Pairs \
+        with R0..R7, CF, ZF, NF or mem[ADDRESS] and hexadecimal Values, `within N cycles` is optional.",
+    wrong: "# TEST add: mem[FC]=3 mem[FD]=5 mem[FE]=8\nloop {\n}",
+    right: "# TEST add: mem[FC]=3 mem[FD]=5 -> mem[FE]=8\nloop {\n}"
+};
+
+pub(crate) const INVALID_STIMULUS: Diagnostic = Diagnostic {
+    code: "E0403",
+    name: "invalid stimulus",
+    explanation: "A Line of a Stimulus File could not be read. Every Line is `at CYCLE:` or `read COUNT:` followed by \
+        PORT=VALUE Pairs for the I/O Ports FC to FF, INTA=0, INTA=1 or INTB, or `out:` followed by the expected PORT=VALUE Writes.",
+    wrong: "at 10: 10=05",
+    right: "at 10: FC=05"
+};
+
+pub(crate) const MISSING_INSTRUCTION: Diagnostic = Diagnostic {
+    code: "E0501",
+    name: "missing instruction",
+    explanation: "The running Program jumped to an Address of the Control Store that has no Instruction. \
+        Every Jump Target needs an Instruction, conditional Jumps need one at both Targets.",
+    wrong: "00000: NOP; JUMP 00001",
+    right: "00000: NOP; JUMP 00001\n00001: NOP; JUMP 00000"
+};
+
+/// Every diagnostic, ordered by code.
+pub static DIAGNOSTICS: &[&Diagnostic] = &[
+    &MISSING_ADDRESS,
+    &INVALID_ADDRESS,
+    &INVALID_REGISTER,
+    &INVALID_CONSTANT,
+    &WRONG_COMMAND_COUNT,
+    &UNKNOWN_ALU_COMMAND,
+    &WRONG_OPERANDS,
+    &UNKNOWN_WRITE_COMMAND,
+    &UNKNOWN_BUS_COMMAND,
+    &UNKNOWN_FLAG_COMMAND,
+    &INVALID_JUMP,
+    &UNKNOWN_PSEUDO_INSTRUCTION,
+    &IMPOSSIBLE_TRANSFER,
+    &INVALID_TRANSFER,
+    &INVALID_WORD,
+    &NO_SOURCE_LINE,
+    &MISSING_FILE,
+    &INCLUDE_CYCLE,
+    &INVALID_INCLUDE,
+    &UNBALANCED_CONDITIONAL,
+    &INVALID_SYMBOL_NAME,
+    &INVALID_EXPRESSION,
+    &UNKNOWN_SYMBOL,
+    &UNKNOWN_DIALECT,
+    &UNKNOWN_ROUTINE,
+    &UNKNOWN_CONDITION,
+    &UNBALANCED_BLOCK,
+    &BREAK_OUTSIDE_LOOP,
+    &UNRESOLVED_JUMP,
+    &ADDRESS_CONFLICT,
+    &CONTROL_STORE_FULL,
+    &CONDITION_WITHOUT_INSTRUCTION,
+    &DATA_ON_IO_PORT,
+    &DATA_OVERLAP,
+    &DATA_OUT_OF_RANGE,
+    &INVALID_DATA_DIRECTIVE,
+    &INVALID_DATA_SYMBOL,
+    &FORMAT_CHANGES_MEANING,
+    &INVALID_TEST_CASE,
+    &INVALID_STIMULUS,
+    &MISSING_INSTRUCTION
+];
+
+/// Looks a diagnostic up by its code, ignoring case.
+pub fn find_diagnostic(code: &str) -> Option<&'static Diagnostic> {
+    return DIAGNOSTICS.iter()
+        .find(|diagnostic| diagnostic.code.eq_ignore_ascii_case(code.trim()))
+        .copied();
+}
+
+/// The long explanation of a diagnostic with its wrong and right example, if it has examples.
+pub fn format_diagnostic(diagnostic: &Diagnostic) -> String {
+    let indent = |source: &str| source.lines().map(|line| format!("    {}\n", line)).collect::<String>();
+    let mut explanation = format!("{} {}\n\n{}\n", diagnostic.code, diagnostic.name, diagnostic.explanation);
+    if !diagnostic.wrong.is_empty() {
+        explanation.push_str(&format!("\nWrong:\n{}\nRight:\n{}", indent(diagnostic.wrong), indent(diagnostic.right)));
+    }
+    return explanation;
+}
+
+#[cfg(test)]
+mod diagnostics_tests {
+    use super::*;
+    use crate::Assembler;
+    use crate::dialect::Dialect;
+    use crate::disassembly::disassemble_program;
+    use crate::translated::TranslatedProgram;
+
+    /// Diagnostics whose examples are no single .2ia program: .2i lines, several files, a placeholder or none,
+    /// stimulus lines, or programs that translate and fail when they are tested or run.
+    const NOT_PROGRAM_EXAMPLES: [&str; 10] = ["E0001", "E0015", "E0016", "E0102", "E0204", "E0206", "E0401", "E0402", "E0403", "E0501"];
+
+    #[test]
+    fn test_examples_show_the_diagnostic() {
+        for diagnostic in DIAGNOSTICS.iter().filter(|diagnostic| !NOT_PROGRAM_EXAMPLES.contains(&diagnostic.code)) {
+            let error = Assembler::new().translate_program(diagnostic.wrong).err()
+                .unwrap_or_else(|| panic!("{} translates", diagnostic.code));
+            assert!(error.contains(&format!("{} {}: ", diagnostic.code, diagnostic.name)), "{} reports {}", diagnostic.code, error);
+
+            if diagnostic.code != MISSING_FILE.code && diagnostic.code != INVALID_INCLUDE.code {
+                assert!(Assembler::new().translate_program(diagnostic.right).is_ok(), "{} is not fixed", diagnostic.code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_word_examples_show_the_diagnostic() {
+        for diagnostic in [&MISSING_ADDRESS, &INVALID_WORD, &NO_SOURCE_LINE] {
+            let translate = |source: &str| TranslatedProgram::parse("example.2i", source)
+                .and_then(|program| disassemble_program(&program, Dialect::Standard));
+            assert!(translate(diagnostic.wrong).unwrap_err().contains(diagnostic.code));
+            assert!(translate(diagnostic.right).is_ok(), "{} is not fixed", diagnostic.code);
+        }
+    }
+
+    #[test]
+    fn test_codes_are_unique_and_ordered() {
+        assert!(DIAGNOSTICS.windows(2).all(|pair| pair[0].code < pair[1].code));
+        assert_eq!(Some(&INVALID_REGISTER), find_diagnostic("e0003"));
+        assert!(format_diagnostic(&INVALID_REGISTER).starts_with("E0003 invalid register\n\n"));
+        assert!(find_diagnostic("E9999").is_none());
+    }
+}
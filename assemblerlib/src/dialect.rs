@@ -0,0 +1,276 @@
+use crate::diagnostics::UNKNOWN_DIALECT;
+use crate::suggestion::{closest, did_you_mean};
+use crate::instruction::RegisterAddress;
+use crate::parsing::{ALUCommand, WriteCommand, BusCommand, FlagCommand, JumpType, RegisterOrConstant};
+
+/// The spelling of mnemonics a source file is written in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    /// The spelling of the README: `PASSA`, `BSETC`, `ADDSC`, `WRITE A`, `JUMPCF`.
+    Standard,
+    /// The spelling of `2iLanguage.txt` and `Assembler Parts.txt`: `A`, `B SETC`, `ADCS`, `WRITE`, `TEST CF JMP`.
+    Course
+}
+
+pub(crate) enum ALUMnemonic {
+    Single(fn(RegisterAddress) -> ALUCommand),
+    Double(fn(RegisterAddress, RegisterOrConstant) -> ALUCommand)
+}
+
+/// Directive that selects the dialect for the rest of the file it appears in.
+pub const DIALECT_DIRECTIVE: &str = ".dialect";
+
+// The first spelling of a command in each table is the one the disassembler writes.
+
+static STANDARD_ALU_MNEMONICS: [(&str, ALUMnemonic); 21] = [
+    ("ZERO", ALUMnemonic::Double(ALUCommand::Zero)),
+    ("PASSA", ALUMnemonic::Double(ALUCommand::PassA)),
+    ("PASSB", ALUMnemonic::Double(ALUCommand::PassB)),
+    ("BSETC", ALUMnemonic::Double(ALUCommand::PassBSetC)),
+    ("BHOLDC", ALUMnemonic::Double(ALUCommand::PassBHoldC)),
+    ("BINVC", ALUMnemonic::Double(ALUCommand::PassBInvertC)),
+    ("COM", ALUMnemonic::Single(ALUCommand::Complement)),
+    ("NOR", ALUMnemonic::Double(ALUCommand::Nor)),
+    ("ADDH", ALUMnemonic::Double(ALUCommand::AddHoldC)),
+    ("ADD", ALUMnemonic::Double(ALUCommand::Add)),
+    ("ADDS", ALUMnemonic::Double(ALUCommand::AddSub)),
+    ("ADDC", ALUMnemonic::Double(ALUCommand::AddC)),
+    ("ADDSC", ALUMnemonic::Double(ALUCommand::AddSubC)),
+    ("ASR", ALUMnemonic::Double(ALUCommand::ArithShiftRight)),
+    ("LSLH", ALUMnemonic::Single(ALUCommand::LogicShiftLeftHoldC)),
+    ("LSL", ALUMnemonic::Single(ALUCommand::LogicShiftLeft)),
+    ("LSR", ALUMnemonic::Double(ALUCommand::LogicShiftRight)),
+    ("SL1", ALUMnemonic::Single(ALUCommand::ShiftLeftAppend1)),
+    ("RR", ALUMnemonic::Double(ALUCommand::RotateRight)),
+    ("RRC", ALUMnemonic::Double(ALUCommand::RotateRightCarry)),
+    ("RLC", ALUMnemonic::Single(ALUCommand::RotateLeftCarry)),
+];
+
+static COURSE_ALU_MNEMONICS: [(&str, ALUMnemonic); 24] = [
+    ("0", ALUMnemonic::Double(ALUCommand::Zero)),
+    ("A", ALUMnemonic::Double(ALUCommand::PassA)),
+    ("B", ALUMnemonic::Double(ALUCommand::PassB)),
+    ("B CLC", ALUMnemonic::Double(ALUCommand::PassB)),
+    ("B SETC", ALUMnemonic::Double(ALUCommand::PassBSetC)),
+    ("B H", ALUMnemonic::Double(ALUCommand::PassBHoldC)),
+    ("B INVC", ALUMnemonic::Double(ALUCommand::PassBInvertC)),
+    ("COM", ALUMnemonic::Single(ALUCommand::Complement)),
+    ("NOR", ALUMnemonic::Double(ALUCommand::Nor)),
+    ("ADDH", ALUMnemonic::Double(ALUCommand::AddHoldC)),
+    ("ADD", ALUMnemonic::Double(ALUCommand::Add)),
+    ("ADDS", ALUMnemonic::Double(ALUCommand::AddSub)),
+    ("ADC", ALUMnemonic::Double(ALUCommand::AddC)),
+    ("ADDC", ALUMnemonic::Double(ALUCommand::AddC)),
+    ("ADCS", ALUMnemonic::Double(ALUCommand::AddSubC)),
+    ("ADDCS", ALUMnemonic::Double(ALUCommand::AddSubC)),
+    ("ASR", ALUMnemonic::Double(ALUCommand::ArithShiftRight)),
+    ("LSLH", ALUMnemonic::Single(ALUCommand::LogicShiftLeftHoldC)),
+    ("LSL", ALUMnemonic::Single(ALUCommand::LogicShiftLeft)),
+    ("LSR", ALUMnemonic::Double(ALUCommand::LogicShiftRight)),
+    ("SL1", ALUMnemonic::Single(ALUCommand::ShiftLeftAppend1)),
+    ("RR", ALUMnemonic::Double(ALUCommand::RotateRight)),
+    ("RRC", ALUMnemonic::Double(ALUCommand::RotateRightCarry)),
+    ("RLC", ALUMnemonic::Single(ALUCommand::RotateLeftCarry)),
+];
+
+static STANDARD_WRITE_MNEMONICS: [(&str, WriteCommand); 3] = [
+    ("WRITE A", WriteCommand::WriteA),
+    ("WRITE B", WriteCommand::WriteB),
+    ("WRITE OFF", WriteCommand::WriteOff),
+];
+
+static COURSE_WRITE_MNEMONICS: [(&str, WriteCommand); 4] = [
+    ("WRITE A", WriteCommand::WriteA),
+    ("WRITE B", WriteCommand::WriteB),
+    ("WRITE", WriteCommand::WriteA),
+    ("WRITE OFF", WriteCommand::WriteOff),
+];
+
+static BUS_MNEMONICS: [(&str, BusCommand); 3] = [
+    ("BUS READ", BusCommand::BusRead),
+    ("BUS WRITE", BusCommand::BusWrite),
+    ("BUS OFF", BusCommand::BusOff),
+];
+
+static FLAG_MNEMONICS: [(&str, FlagCommand); 2] = [
+    ("FLAGS COPY", FlagCommand::UpdateFlags),
+    ("FLAGS KEEP", FlagCommand::KeepFlags),
+];
+
+static STANDARD_JUMP_MNEMONICS: [(&str, JumpType); 7] = [
+    ("JUMP", JumpType::Jump),
+    ("JUMPINTA", JumpType::TestIntAJump),
+    ("JUMPINTB", JumpType::TestIntBJump),
+    ("JUMPCF", JumpType::TestCFJump),
+    ("JUMPCO", JumpType::TestCOJump),
+    ("JUMPZO", JumpType::TestZOJump),
+    ("JUMPNO", JumpType::TestNOJump),
+];
+
+static COURSE_JUMP_MNEMONICS: [(&str, JumpType); 7] = [
+    ("JMP", JumpType::Jump),
+    ("TEST INTA JMP", JumpType::TestIntAJump),
+    ("TEST INTB JMP", JumpType::TestIntBJump),
+    ("TEST CF JMP", JumpType::TestCFJump),
+    ("TEST CO JMP", JumpType::TestCOJump),
+    ("TEST ZO JMP", JumpType::TestZOJump),
+    ("TEST NO JMP", JumpType::TestNOJump),
+];
+
+impl Dialect {
+    pub const ALL: [Dialect; 2] = [Dialect::Standard, Dialect::Course];
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            Dialect::Standard => "standard",
+            Dialect::Course => "course"
+        };
+    }
+
+    pub fn from_name(name: &str) -> Result<Dialect, String> {
+        return Dialect::ALL.iter()
+            .find(|dialect| dialect.name() == name.trim())
+            .copied()
+            .ok_or_else(|| UNKNOWN_DIALECT.message(format!("Unknown Dialect {}, expected standard or course{}",
+                name.trim(), did_you_mean(closest(name, Dialect::ALL.iter().map(|dialect| dialect.name()))))));
+    }
+
+    pub(crate) fn alu_mnemonics(self) -> &'static [(&'static str, ALUMnemonic)] {
+        return match self {
+            Dialect::Standard => &STANDARD_ALU_MNEMONICS,
+            Dialect::Course => &COURSE_ALU_MNEMONICS
+        };
+    }
+
+    pub(crate) fn write_mnemonics(self) -> &'static [(&'static str, WriteCommand)] {
+        return match self {
+            Dialect::Standard => &STANDARD_WRITE_MNEMONICS,
+            Dialect::Course => &COURSE_WRITE_MNEMONICS
+        };
+    }
+
+    pub(crate) fn bus_mnemonics(self) -> &'static [(&'static str, BusCommand)] {
+        return &BUS_MNEMONICS;
+    }
+
+    pub(crate) fn flag_mnemonics(self) -> &'static [(&'static str, FlagCommand)] {
+        return &FLAG_MNEMONICS;
+    }
+
+    pub(crate) fn jump_mnemonics(self) -> &'static [(&'static str, JumpType)] {
+        return match self {
+            Dialect::Standard => &STANDARD_JUMP_MNEMONICS,
+            Dialect::Course => &COURSE_JUMP_MNEMONICS
+        };
+    }
+
+    /// The spelling this dialect uses for an ALU command, without operands.
+    pub(crate) fn alu_mnemonic(self, alu_command: &ALUCommand) -> &'static str {
+        let (a, b) = alu_command.operands();
+        return self.alu_mnemonics().iter()
+            .find(|(_, mnemonic)| match (mnemonic, b) {
+                (ALUMnemonic::Single(command), None) => command(a) == *alu_command,
+                (ALUMnemonic::Double(command), Some(b)) => command(a, b) == *alu_command,
+                _ => false
+            })
+            .map(|(name, _)| *name)
+            .unwrap_or("?");
+    }
+
+    pub(crate) fn write_mnemonic(self, write_command: WriteCommand) -> &'static str {
+        return find_name(self.write_mnemonics(), write_command);
+    }
+
+    pub(crate) fn bus_mnemonic(self, bus_command: BusCommand) -> &'static str {
+        return find_name(self.bus_mnemonics(), bus_command);
+    }
+
+    pub(crate) fn flag_mnemonic(self, flag_command: FlagCommand) -> &'static str {
+        return find_name(self.flag_mnemonics(), flag_command);
+    }
+
+    pub(crate) fn jump_mnemonic(self, jump_type: JumpType) -> &'static str {
+        return find_name(self.jump_mnemonics(), jump_type);
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        return Dialect::Standard;
+    }
+}
+
+fn find_name<T: PartialEq + Copy>(mnemonics: &'static [(&'static str, T)], command: T) -> &'static str {
+    return mnemonics.iter()
+        .find(|(_, mnemonic_command)| *mnemonic_command == command)
+        .map(|(name, _)| *name)
+        .unwrap_or("?");
+}
+
+/// Looks up a whitespace separated mnemonic, repeated whitespace between its words is ignored.
+pub(crate) fn find_mnemonic<T: Copy>(mnemonics: &'static [(&'static str, T)], words: &[&str]) -> Option<T> {
+    return mnemonics.iter()
+        .find(|(name, _)| name.split(' ').eq(words.iter().copied()))
+        .map(|(_, command)| *command);
+}
+
+/// Returns the dialect named by a `.dialect NAME` line, or `None` if the line is no dialect directive.
+pub fn parse_dialect_directive(line: &str) -> Option<Result<Dialect, String>> {
+    let line_without_comment = line.split('#').next().unwrap_or("").trim();
+    let mut words = line_without_comment.split_whitespace();
+
+    if words.next() != Some(DIALECT_DIRECTIVE) {
+        return None;
+    }
+
+    return Some(match (words.next(), words.next()) {
+        (Some(name), None) => Dialect::from_name(name),
+        _ => Err(UNKNOWN_DIALECT.message("Expected exactly one Dialect Name"))
+    });
+}
+
+#[cfg(test)]
+mod dialect_tests {
+    use super::*;
+    use crate::parsing::parse_line;
+
+    fn assemble(line: &str, dialect: Dialect) -> String {
+        return format!("{}", parse_line(line, dialect).unwrap().instruction);
+    }
+
+    #[test]
+    fn test_course_spellings_match_standard() {
+        let spellings = [
+            ("0 R1 R2; WRITE; BUS OFF; FLAGS KEEP; JMP 00001", "ZERO R1 R2; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"),
+            ("A R1 R2; WRITE B; BUS OFF; FLAGS KEEP; JMP 00001", "PASSA R1 R2; WRITE B; BUS OFF; FLAGS KEEP; JUMP 00001"),
+            ("B  CLC R1 0011; WRITE A; BUS OFF; FLAGS KEEP; JMP 00001", "PASSB R1 0011; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"),
+            ("B SETC R1 R2; WRITE OFF; BUS OFF; FLAGS COPY; TEST CF JMP 00110", "BSETC R1 R2; WRITE OFF; BUS OFF; FLAGS COPY; JUMPCF 00110"),
+            ("B H R1 R2; WRITE OFF; BUS OFF; FLAGS COPY; TEST INTA JMP 00110", "BHOLDC R1 R2; WRITE OFF; BUS OFF; FLAGS COPY; JUMPINTA 00110"),
+            ("B INVC R1 R2; WRITE OFF; BUS OFF; FLAGS COPY; TEST NO JMP 00110", "BINVC R1 R2; WRITE OFF; BUS OFF; FLAGS COPY; JUMPNO 00110"),
+            ("ADC R1 R2; WRITE A; BUS OFF; FLAGS COPY; TEST ZO JMP 00110", "ADDC R1 R2; WRITE A; BUS OFF; FLAGS COPY; JUMPZO 00110"),
+            ("ADCS R1 R2; WRITE A; BUS OFF; FLAGS COPY; TEST CO JMP 00110", "ADDSC R1 R2; WRITE A; BUS OFF; FLAGS COPY; JUMPCO 00110"),
+            ("ADDCS R1 R2; WRITE A; BUS OFF; FLAGS COPY; TEST CO JMP 00110", "ADDSC R1 R2; WRITE A; BUS OFF; FLAGS COPY; JUMPCO 00110"),
+        ];
+
+        for (course, standard) in spellings.iter() {
+            assert_eq!(
+                assemble(&format!("00000: {}", standard), Dialect::Standard),
+                assemble(&format!("00000: {}", course), Dialect::Course),
+                "{}", course
+            );
+        }
+    }
+
+    #[test]
+    fn test_dialects_are_not_mixed() {
+        assert!(parse_line("00000: A R1 R2; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001", Dialect::Standard).is_err());
+        assert!(parse_line("00000: PASSA R1 R2; WRITE A; BUS OFF; FLAGS KEEP; JMP 00001", Dialect::Course).is_err());
+    }
+
+    #[test]
+    fn test_dialect_directive() {
+        assert_eq!(Some(Ok(Dialect::Course)), parse_dialect_directive(".dialect course # from the course tables"));
+        assert!(parse_dialect_directive(".dialect klingon").unwrap().is_err());
+        assert!(parse_dialect_directive("00000: NOP; JUMP 00000").is_none());
+    }
+}
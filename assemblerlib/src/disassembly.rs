@@ -0,0 +1,205 @@
+use crate::diagnostics::NO_SOURCE_LINE;
+use crate::instruction::{Instruction, RegisterAddress};
+use crate::dialect::{Dialect, DIALECT_DIRECTIVE};
+use crate::parsing::{Operation, ALUCommand, WriteCommand, BusCommand, FlagCommand, JumpCommand, JumpType, RegisterOrConstant};
+use crate::parsing::RegisterOrConstant::{Register, Constant};
+use crate::pseudo::{PseudoInstruction, register_name};
+use crate::translated::{TranslatedProgram, TranslatedLine};
+
+/// Turns an instruction back into a `.2ia` source line in the given dialect, using a pseudo instruction where one fits exactly.
+pub fn disassemble_instruction(instruction: &Instruction, dialect: Dialect) -> Result<String, String> {
+    let (operation, jump_command) = decode_instruction(instruction)?;
+
+    let operation_string = match PseudoInstruction::from_operation(&operation) {
+        Some(pseudo_instruction) => format!("{}", pseudo_instruction),
+        None => format_operation(&operation, dialect)
+    };
+
+    return Ok(format!("{}: {}; {}", instruction.address, operation_string, format_jump_command(&jump_command, dialect)));
+}
+
+/// Turns a translated program back into `.2ia` source, comments and empty lines are kept.
+/// Source in any dialect but the standard one starts with the `.dialect` directive it needs.
+pub fn disassemble_program(program: &TranslatedProgram, dialect: Dialect) -> Result<String, String> {
+    let mut source = String::new();
+    if dialect != Dialect::Standard {
+        source.push_str(&format!("{} {}\n", DIALECT_DIRECTIVE, dialect.name()));
+    }
+    for (line, location) in program.lines.iter().zip(program.locations.iter()) {
+        match line {
+            TranslatedLine::InstructionLine(instruction, comment) => {
+                let instruction_string = disassemble_instruction(instruction, dialect)
+                    .map_err(|err| format!("Error in {}, Description: {}", location, err))?;
+                source.push_str(&instruction_string);
+                if let Some(comment) = comment {
+                    source.push_str(&format!(" #{}", comment));
+                }
+            },
+            TranslatedLine::CommentLine(comment) => source.push_str(&format!("#{}", comment)),
+            TranslatedLine::EmptyLine => {}
+        }
+        source.push('\n');
+    }
+    return Ok(source);
+}
+
+/// Splits an instruction into the commands it was assembled from.
+/// Fails for instruction words that no source line assembles to.
+pub(crate) fn decode_instruction(instruction: &Instruction) -> Result<(Operation, JumpCommand), String> {
+    let operation = Operation {
+        alu_command: decode_alu_command(instruction),
+        write_command: match (instruction.register_control.rwe, instruction.register_control.rws) {
+            (false, _) => WriteCommand::WriteOff,
+            (true, false) => WriteCommand::WriteA,
+            (true, true) => WriteCommand::WriteB
+        },
+        bus_command: match (instruction.bus_control.bus_en, instruction.bus_control.bus_wr) {
+            (true, false) => BusCommand::BusRead,
+            (true, true) => BusCommand::BusWrite,
+            (false, _) => BusCommand::BusOff
+        },
+        flag_command: match instruction.alu_control.cf {
+            true => FlagCommand::UpdateFlags,
+            false => FlagCommand::KeepFlags
+        }
+    };
+
+    let jump_command = JumpCommand {
+        jump_type: decode_jump_type(instruction),
+        next_address: instruction.next_address
+    };
+
+    if operation.to_instruction(instruction.address, &jump_command) != *instruction {
+        return Err(NO_SOURCE_LINE.message(format!("The Instruction {} can not be expressed as a Source Line", instruction)));
+    }
+
+    return Ok((operation, jump_command));
+}
+
+fn decode_alu_command(instruction: &Instruction) -> ALUCommand {
+    let a = instruction.content_a;
+    let b = match instruction.register_address_control.acb {
+        true => Constant(instruction.content_b),
+        false => Register(RegisterAddress::from_u8(instruction.content_b.to_u8()))
+    };
+    let single = b == Register(a) && !instruction.content_b.b3;
+
+    return match instruction.alu_function.to_u8() {
+        0b0000 if single => ALUCommand::LogicShiftLeftHoldC(a),
+        0b0000 => ALUCommand::AddHoldC(a, b),
+        0b0001 => ALUCommand::PassA(a, b),
+        0b0010 if single => ALUCommand::Complement(a),
+        0b0010 => ALUCommand::Nor(a, b),
+        0b0011 => ALUCommand::Zero(a, b),
+        0b0100 if single => ALUCommand::LogicShiftLeft(a),
+        0b0100 => ALUCommand::Add(a, b),
+        0b0101 if single => ALUCommand::ShiftLeftAppend1(a),
+        0b0101 => ALUCommand::AddSub(a, b),
+        0b0110 if single => ALUCommand::RotateLeftCarry(a),
+        0b0110 => ALUCommand::AddC(a, b),
+        0b0111 => ALUCommand::AddSubC(a, b),
+        0b1000 => ALUCommand::LogicShiftRight(a, b),
+        0b1001 => ALUCommand::RotateRight(a, b),
+        0b1010 => ALUCommand::RotateRightCarry(a, b),
+        0b1011 => ALUCommand::ArithShiftRight(a, b),
+        0b1100 => ALUCommand::PassB(a, b),
+        0b1101 => ALUCommand::PassBSetC(a, b),
+        0b1110 => ALUCommand::PassBHoldC(a, b),
+        _ => ALUCommand::PassBInvertC(a, b)
+    };
+}
+
+fn decode_jump_type(instruction: &Instruction) -> JumpType {
+    return match (instruction.address_control.ac1, instruction.address_control.ac0, instruction.next_address.bit0) {
+        (false, false, _) => JumpType::Jump,
+        (false, true, false) => JumpType::TestIntAJump,
+        (false, true, true) => JumpType::TestCFJump,
+        (true, false, false) => JumpType::TestCOJump,
+        (true, false, true) => JumpType::TestZOJump,
+        (true, true, false) => JumpType::TestNOJump,
+        (true, true, true) => JumpType::TestIntBJump
+    };
+}
+
+pub(crate) fn format_operation(operation: &Operation, dialect: Dialect) -> String {
+    return format!(
+        "{}; {}; {}; {}",
+        format_alu_command(&operation.alu_command, dialect),
+        dialect.write_mnemonic(operation.write_command),
+        dialect.bus_mnemonic(operation.bus_command),
+        dialect.flag_mnemonic(operation.flag_command)
+    );
+}
+
+pub(crate) fn format_alu_command(alu_command: &ALUCommand, dialect: Dialect) -> String {
+    let mnemonic = dialect.alu_mnemonic(alu_command);
+    return match alu_command.operands() {
+        (a, Some(b)) => format!("{} {} {}", mnemonic, register_name(a), format_register_or_constant(&b)),
+        (r, None) => format!("{} {}", mnemonic, register_name(r))
+    };
+}
+
+pub(crate) fn format_register_or_constant(value: &RegisterOrConstant) -> String {
+    return match value {
+        Register(register) => register_name(*register),
+        Constant(constant) => format!("{}", constant)
+    };
+}
+
+pub(crate) fn format_jump_command(jump_command: &JumpCommand, dialect: Dialect) -> String {
+    return format!("{} {}", dialect.jump_mnemonic(jump_command.jump_type), jump_command.next_address);
+}
+
+#[cfg(test)]
+mod disassembly_tests {
+    use super::*;
+    use crate::instruction::InstructionAddress;
+    use crate::parsing::parse_line;
+
+    fn round_trip(line: &str) -> String {
+        return round_trip_in(line, Dialect::Standard, Dialect::Standard);
+    }
+
+    fn round_trip_in(line: &str, input_dialect: Dialect, output_dialect: Dialect) -> String {
+        let instruction = parse_line(line, input_dialect).unwrap().instruction;
+        let disassembled = disassemble_instruction(&instruction, output_dialect).unwrap();
+        assert_eq!(instruction, parse_line(&disassembled, output_dialect).unwrap().instruction, "{}", disassembled);
+        return disassembled;
+    }
+
+    #[test]
+    fn test_example_program_round_trips() {
+        let example = include_str!("../../Examples/Example 2ia Code.2ia");
+        let disassembled: Vec<String> = example.lines()
+            .map(|line| round_trip(line.split('#').next().unwrap()))
+            .collect();
+
+        assert_eq!("00000: LDI R0, 1100; JUMP 00001", disassembled[0]);
+        assert_eq!("00001: IN R0, [R0]; JUMP 00010", disassembled[1]);
+        assert_eq!("00100: LSL R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00101", disassembled[4]);
+        assert_eq!("01001: OUT [R1], R0; JUMP 00000", disassembled[9]);
+    }
+
+    #[test]
+    fn test_conditional_jumps_round_trip() {
+        for jump in ["JUMPINTA 00100", "JUMPINTB 00101", "JUMPCF 00101", "JUMPCO 00100", "JUMPZO 00101", "JUMPNO 00100"].iter() {
+            let line = format!("00010: ADDSC R1 R2; WRITE A; BUS OFF; FLAGS COPY; {}", jump);
+            assert_eq!(line, round_trip(&line));
+        }
+    }
+
+    #[test]
+    fn test_course_dialect_output() {
+        let line = "00010: ADDSC R1 R2; WRITE A; BUS OFF; FLAGS COPY; JUMPCF 00101";
+        assert_eq!("00010: ADCS R1 R2; WRITE A; BUS OFF; FLAGS COPY; TEST CF JMP 00101", round_trip_in(line, Dialect::Standard, Dialect::Course));
+
+        let line = "00011: B SETC R3 1111; WRITE OFF; BUS OFF; FLAGS KEEP; JMP 00000";
+        assert_eq!("00011: BSETC R3 1111; WRITE OFF; BUS OFF; FLAGS KEEP; JUMP 00000", round_trip_in(line, Dialect::Course, Dialect::Standard));
+    }
+
+    #[test]
+    fn test_unrepresentable_word_is_rejected() {
+        let instruction = Instruction::from_word(InstructionAddress::from_u8(0), 1 << 6);
+        assert!(disassemble_instruction(&instruction, Dialect::Standard).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::dialect::Dialect;
+use crate::disassembly::disassemble_instruction;
+use crate::instruction::Instruction;
+use crate::rtl::describe_instruction;
+use crate::structured::CONTROL_STORE_WORDS;
+use crate::translated::{TranslatedProgram, TranslatedLine};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Markdown,
+    Html
+}
+
+impl DocumentFormat {
+    pub const ALL: [DocumentFormat; 2] = [DocumentFormat::Markdown, DocumentFormat::Html];
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            DocumentFormat::Markdown => "markdown",
+            DocumentFormat::Html => "html"
+        };
+    }
+
+    pub fn from_name(name: &str) -> Result<DocumentFormat, String> {
+        return DocumentFormat::ALL.iter()
+            .find(|format| format.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown Document Format {}", name));
+    }
+
+    /// The file type of documents in this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        return match self {
+            DocumentFormat::Markdown => "md",
+            DocumentFormat::Html => "html"
+        };
+    }
+}
+
+/// The parts of a document, rendered the same way in every format.
+enum Block {
+    Heading(String),
+    List(Vec<String>),
+    Code(String),
+    Table(Vec<&'static str>, Vec<Vec<String>>)
+}
+
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }\n\
+    table { border-collapse: collapse; }\n\
+    th, td { border: 1px solid #999; padding: 0.2em 0.5em; text-align: left; }\n\
+    pre, td { font-family: monospace; }";
+
+/// Renders a page with the metadata, the listing and a table of every instruction with its decoded fields,
+/// where it jumps to and where it is reached from.
+pub fn document_program(title: &str, program: &TranslatedProgram, format: DocumentFormat) -> String {
+    let mut blocks = vec![Block::Heading("Metadata".to_string())];
+    let metadata_fields = [
+        ("Inputs", &program.metadata.inputs),
+        ("Outputs", &program.metadata.outputs),
+        ("Behaviour", &program.metadata.behaviour)
+    ];
+    blocks.push(Block::List(metadata_fields.iter()
+        .map(|(name, parts)| match parts.is_empty() {
+            true => format!("{}: not documented", name),
+            false => format!("{}: {}", name, parts.join("; "))
+        })
+        .collect()));
+
+    blocks.push(Block::Heading("Listing".to_string()));
+    blocks.push(Block::Code(program.listing()));
+
+    blocks.push(Block::Heading("Instructions".to_string()));
+    blocks.push(Block::Table(
+        vec!["Address", "Word", "Operation", "Jump", "Register Transfer", "Jumps To", "Reached From"],
+        instruction_rows(program)
+    ));
+
+    if !program.memory.is_empty() {
+        blocks.push(Block::Heading("Memory Image".to_string()));
+        blocks.push(Block::Code(program.memory.to_string()));
+    }
+
+    return match format {
+        DocumentFormat::Markdown => render_markdown(title, &blocks),
+        DocumentFormat::Html => render_html(title, &blocks)
+    };
+}
+
+fn instruction_rows(program: &TranslatedProgram) -> Vec<Vec<String>> {
+    let mut control_store: Vec<Option<&Instruction>> = vec![None; CONTROL_STORE_WORDS];
+    for line in &program.lines {
+        if let TranslatedLine::InstructionLine(instruction, _) = line {
+            control_store[instruction.address.to_u8() as usize] = Some(instruction);
+        }
+    }
+
+    let mut reached_from: Vec<Vec<String>> = vec![Vec::new(); CONTROL_STORE_WORDS];
+    for instruction in control_store.iter().flatten() {
+        for target in instruction.jump_targets() {
+            reached_from[target.to_u8() as usize].push(instruction.address.to_string());
+        }
+    }
+
+    return control_store.iter().flatten()
+        .map(|instruction| {
+            let word = instruction.to_string();
+            let word = word.split_once(": ").map(|(_, bits)| bits).unwrap_or(&word).to_string();
+            let (operation, jump) = match disassemble_instruction(instruction, Dialect::Standard) {
+                Ok(source) => {
+                    let code = source.split_once(": ").map(|(_, code)| code).unwrap_or(&source).to_string();
+                    match code.rsplit_once(';') {
+                        Some((operation, jump)) => (operation.trim().to_string(), jump.trim().to_string()),
+                        None => (code, String::new())
+                    }
+                },
+                Err(_) => ("-".to_string(), "-".to_string())
+            };
+            let jumps_to: Vec<String> = instruction.jump_targets().iter()
+                .map(|target| match control_store[target.to_u8() as usize] {
+                    Some(_) => target.to_string(),
+                    None => format!("{} (missing)", target)
+                })
+                .collect();
+            let reached_from = &reached_from[instruction.address.to_u8() as usize];
+
+            return vec![
+                instruction.address.to_string(),
+                word,
+                operation,
+                jump,
+                describe_instruction(instruction).unwrap_or_else(|_| "-".to_string()),
+                jumps_to.join(", "),
+                match reached_from.is_empty() {
+                    true => "-".to_string(),
+                    false => reached_from.join(", ")
+                }
+            ];
+        })
+        .collect();
+}
+
+fn render_markdown(title: &str, blocks: &[Block]) -> String {
+    let mut document = format!("# {} #\n", title);
+    for block in blocks {
+        document.push('\n');
+        match block {
+            Block::Heading(heading) => document.push_str(&format!("## {} ##\n", heading)),
+            Block::List(items) => items.iter().for_each(|item| document.push_str(&format!("- {}\n", item))),
+            Block::Code(code) => document.push_str(&format!("```\n{}```\n", code)),
+            Block::Table(header, rows) => {
+                document.push_str(&format!("| {} |\n", header.join(" | ")));
+                document.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                    document.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+        }
+    }
+    return document;
+}
+
+fn render_html(title: &str, blocks: &[Block]) -> String {
+    let title = escape_html(title);
+    let mut document = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+    for block in blocks {
+        match block {
+            Block::Heading(heading) => document.push_str(&format!("<h2>{}</h2>\n", escape_html(heading))),
+            Block::List(items) => {
+                document.push_str("<ul>\n");
+                items.iter().for_each(|item| document.push_str(&format!("<li>{}</li>\n", escape_html(item))));
+                document.push_str("</ul>\n");
+            },
+            Block::Code(code) => document.push_str(&format!("<pre>{}</pre>\n", escape_html(code))),
+            Block::Table(header, rows) => {
+                document.push_str("<table>\n<tr>");
+                header.iter().for_each(|cell| document.push_str(&format!("<th>{}</th>", escape_html(cell))));
+                document.push_str("</tr>\n");
+                for row in rows {
+                    document.push_str("<tr>");
+                    row.iter().for_each(|cell| document.push_str(&format!("<td>{}</td>", escape_html(cell))));
+                    document.push_str("</tr>\n");
+                }
+                document.push_str("</table>\n");
+            }
+        }
+    }
+    document.push_str("</body>\n</html>\n");
+    return document;
+}
+
+fn escape_html(text: &str) -> String {
+    return text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod documentation_tests {
+    use super::*;
+
+    const PROGRAM: &str = "# INPUTS: A in FC\n\
+        # BEHAVIOUR: Counts down\n\
+        00000: R0 = FC; goto 00001\n\
+        00001: R0 = R0 + FF; FLAGS COPY; if Z goto 00011 else 00010";
+
+    #[test]
+    fn test_markdown_cross_references_jumps() {
+        let program = crate::Assembler::new().translate_program(PROGRAM).unwrap();
+        let document = document_program("countdown", &program, DocumentFormat::Markdown);
+
+        assert!(document.starts_with("# countdown #\n"));
+        assert!(document.contains("- Inputs: A in FC\n- Outputs: not documented\n- Behaviour: Counts down\n"));
+        assert!(document.contains("| 00000 | 00 00001 00 000 1100 01 01 1100 0 | LDI R0, 1100 | JUMP 00001 | R0 = FC | 00001 | - |\n"));
+        assert!(document.contains("| ADD R0 1111; WRITE A; BUS OFF; FLAGS COPY | JUMPZO 00011 |"));
+        assert!(document.contains("| 00010 (missing), 00011 (missing) | 00000 |\n"));
+    }
+
+    #[test]
+    fn test_html_is_escaped() {
+        let program = crate::Assembler::new().translate_program("# BEHAVIOUR: R1 <- R0\n00000: R1 = R0; goto 00000").unwrap();
+        let document = document_program("a & b", &program, DocumentFormat::Html);
+
+        assert!(document.contains("<title>a &amp; b</title>"));
+        assert!(document.contains("<li>Behaviour: R1 &lt;- R0</li>"));
+        assert!(document.ends_with("</html>\n"));
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::alu::{compute, ALUOutput};
+use crate::devices::BusDevice;
+use crate::diagnostics::MISSING_INSTRUCTION;
+use crate::instruction::{Instruction, InstructionAddress};
+use crate::translated::{TranslatedLine, TranslatedProgram};
+
+pub const CONTROL_STORE_SIZE: usize = 32;
+pub const MEMORY_SIZE: usize = 256;
+const BYTES_PER_ROW: usize = 16;
+
+/// Everything of the 2i that changes from cycle to cycle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachineState {
+    pub registers: [u8; 8],
+    pub carry_flag: bool,
+    pub zero_flag: bool,
+    pub negative_flag: bool,
+    /// The address of the instruction that is executed in the next cycle.
+    pub address: InstructionAddress,
+    /// The 256 bytes on the bus, addressed through register A.
+    pub memory: Vec<u8>,
+    /// The level interrupt input, tested by MAC 01 with NA0 = 0.
+    pub interrupt_a: bool,
+    /// Set by a rising edge of the edge interrupt input, tested and cleared by MAC 11 with NA0 = 1.
+    pub interrupt_b: bool,
+    pub cycles: u64
+}
+
+impl MachineState {
+    pub fn new() -> MachineState {
+        return MachineState {
+            registers: [0; 8],
+            carry_flag: false,
+            zero_flag: false,
+            negative_flag: false,
+            address: InstructionAddress::from_u8(0),
+            memory: vec![0; MEMORY_SIZE],
+            interrupt_a: false,
+            interrupt_b: false,
+            cycles: 0
+        };
+    }
+}
+
+impl Default for MachineState {
+    fn default() -> Self {
+        return MachineState::new();
+    }
+}
+
+/// The cycle count, the next address, the registers, the flags and the rows of memory that are not all 00.
+impl fmt::Display for MachineState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cycles:  {}", self.cycles)?;
+        writeln!(f, "Address: {}", self.address)?;
+        let registers: Vec<String> = self.registers.iter().enumerate()
+            .map(|(i, value)| format!("R{}={:02X}", i, value))
+            .collect();
+        writeln!(f, "Registers: {}", registers.join(" "))?;
+        writeln!(f, "Flags:   C={} Z={} N={}", self.carry_flag as u8, self.zero_flag as u8, self.negative_flag as u8)?;
+        writeln!(f, "Memory:")?;
+        for (row, bytes) in self.memory.chunks(BYTES_PER_ROW).enumerate() {
+            if bytes.iter().any(|byte| *byte != 0) {
+                let values: Vec<String> = bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+                writeln!(f, "{:02X}: {}", row * BYTES_PER_ROW, values.join(" "))?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// What one cycle did, for tools that show the machine at work.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub instruction: Instruction,
+    /// The ALU inputs A and B.
+    pub a: u8,
+    pub b: u8,
+    pub output: ALUOutput,
+    /// The register that was written, if any.
+    pub written_register: Option<u8>,
+    /// The bus address and the byte that was read or written, if the bus was used.
+    pub bus_read: Option<(u8, u8)>,
+    pub bus_write: Option<(u8, u8)>,
+    /// The address the address logic selected, NA with NA0 replaced by the tested condition.
+    pub next_address: InstructionAddress
+}
+
+/// Executes a translated program cycle by cycle, like the 2i hardware does.
+#[derive(Clone)]
+pub struct Emulator {
+    control_store: Vec<Option<Instruction>>,
+    pub state: MachineState,
+    /// The level of the edge interrupt input, a rising edge sets `interrupt_b`.
+    interrupt_b_input: bool,
+    devices: Vec<Box<dyn BusDevice>>,
+    /// The registers and flags after a cycle of a jump to itself that changed nothing, it would change nothing again with them.
+    idle: Option<([u8; 8], [bool; 3])>
+}
+
+impl Emulator {
+    /// Loads the instructions into the control store and the `.data` bytes into memory, everything else starts at 0.
+    pub fn new(program: &TranslatedProgram) -> Emulator {
+        let mut control_store = vec![None; CONTROL_STORE_SIZE];
+        for line in &program.lines {
+            if let TranslatedLine::InstructionLine(instruction, _) = line {
+                control_store[instruction.address.to_u8() as usize] = Some(*instruction);
+            }
+        }
+
+        let mut state = MachineState::new();
+        for address in 0..MEMORY_SIZE {
+            state.memory[address] = program.memory.get(address as u8).unwrap_or(0);
+        }
+        return Emulator { control_store, state, interrupt_b_input: false, devices: Vec::new(), idle: None };
+    }
+
+    /// The instruction that is executed in the next cycle.
+    pub fn instruction(&self) -> Option<&Instruction> {
+        return self.control_store[self.state.address.to_u8() as usize].as_ref();
+    }
+
+    /// Whether the next instruction jumps to itself without a condition and executing it changes nothing any more,
+    /// like the empty `loop {}` that ends structured code. A jump to itself that counts or writes the bus keeps running.
+    pub fn is_halted(&self) -> bool {
+        let instruction = match self.instruction() {
+            Some(instruction) => instruction,
+            None => return false
+        };
+        if instruction.address_control.ac1 || instruction.address_control.ac0 || instruction.next_address != instruction.address {
+            return false;
+        }
+
+        let writes_bus = instruction.bus_control.bus_en && instruction.bus_control.bus_wr;
+        if !instruction.register_control.rwe && !writes_bus && !instruction.alu_control.cf {
+            return true;
+        }
+        return self.idle == Some((self.state.registers, self.flags()));
+    }
+
+    fn flags(&self) -> [bool; 3] {
+        return [self.state.carry_flag, self.state.zero_flag, self.state.negative_flag];
+    }
+
+    /// Puts a device on the bus, it answers reads and writes of its ports instead of the memory.
+    pub fn attach(&mut self, device: Box<dyn BusDevice>) {
+        self.devices.push(device);
+    }
+
+    pub fn devices(&self) -> &[Box<dyn BusDevice>] {
+        return &self.devices;
+    }
+
+    /// Passes a key pressed in a live view to the devices, returns whether one of them used it.
+    pub fn press(&mut self, key: char) -> bool {
+        return self.devices.iter_mut().filter_map(|device| device.press(key).then_some(())).count() > 0;
+    }
+
+    /// Sets the level of the edge interrupt input, a change from low to high is remembered until a jump tests it.
+    pub fn set_interrupt_b(&mut self, level: bool) {
+        if level && !self.interrupt_b_input {
+            self.state.interrupt_b = true;
+        }
+        self.interrupt_b_input = level;
+    }
+
+    /// Executes the next instruction. The register, the bus, the flag register and the next address are written at the
+    /// end of the cycle, so a jump on the Carry Flag tests the flag from before the instruction.
+    pub fn step(&mut self) -> Result<Cycle, String> {
+        let before = (self.state.registers, self.flags());
+        let instruction = *self.instruction()
+            .ok_or_else(|| MISSING_INSTRUCTION.message(format!("There is no Instruction at {}, the Program jumps there after {} Cycles",
+                self.state.address, self.state.cycles)))?;
+        let state = &mut self.state;
+
+        let bus_address = state.registers[instruction.content_a.to_u8() as usize];
+        let bus_enabled = instruction.bus_control.bus_en;
+        let bus_read = bus_enabled && !instruction.bus_control.bus_wr;
+        let mut device = self.devices.iter_mut().find(|device| device.handles(bus_address));
+
+        // A device port reads as what the device answers, the memory keeps the last value for the state display.
+        if let Some(device) = device.as_deref_mut().filter(|_| bus_read || instruction.register_address_control.aca) {
+            state.memory[bus_address as usize] = device.read(bus_address);
+        }
+
+        let a = match instruction.register_address_control.aca {
+            true => state.memory[bus_address as usize],
+            false => state.registers[instruction.content_a.to_u8() as usize]
+        };
+        let b = match instruction.register_address_control.acb {
+            true => instruction.content_b.to_constant_value(),
+            false => state.registers[(instruction.content_b.to_u8() & 0b111) as usize]
+        };
+        let output = compute(instruction.alu_function, a, b, state.carry_flag);
+
+        let condition = match (instruction.address_control.ac1, instruction.address_control.ac0, instruction.next_address.bit0) {
+            (false, false, bit0) => bit0,
+            (false, true, false) => state.interrupt_a,
+            (false, true, true) => state.carry_flag,
+            (true, false, false) => output.carry,
+            (true, false, true) => output.zero,
+            (true, true, false) => output.negative,
+            (true, true, true) => std::mem::replace(&mut state.interrupt_b, false)
+        };
+
+        let written_register = match instruction.register_control.rwe {
+            true => Some(match instruction.register_control.rws {
+                true => instruction.content_b.to_u8() & 0b111,
+                false => instruction.content_a.to_u8()
+            }),
+            false => None
+        };
+        if let Some(register) = written_register {
+            state.registers[register as usize] = output.result;
+        }
+
+        let bus_write = match bus_enabled && instruction.bus_control.bus_wr {
+            true => {
+                let unchanged = state.memory[bus_address as usize] == output.result;
+                state.memory[bus_address as usize] = output.result;
+                if let Some(device) = device {
+                    device.write(bus_address, output.result);
+                }
+                Some((bus_address, output.result, unchanged))
+            },
+            false => None
+        };
+
+        if instruction.alu_control.cf {
+            state.carry_flag = output.carry;
+            state.zero_flag = output.zero;
+            state.negative_flag = output.negative;
+        }
+
+        let mut next_address = instruction.next_address;
+        next_address.bit0 = condition;
+        state.address = next_address;
+        state.cycles += 1;
+        self.devices.iter_mut().for_each(|device| device.tick());
+
+        let cycle = Cycle {
+            instruction,
+            a,
+            b,
+            output,
+            written_register,
+            bus_read: match bus_read {
+                true => Some((bus_address, state.memory[bus_address as usize])),
+                false => None
+            },
+            bus_write: bus_write.map(|(address, value, _)| (address, value)),
+            next_address
+        };
+
+        // A cycle that read the bus is never idle, the inputs may change.
+        let reads_bus = bus_read || instruction.register_address_control.aca;
+        let after = (self.state.registers, self.flags());
+        self.idle = match next_address == instruction.address && !reads_bus && before == after && matches!(bus_write, None | Some((_, _, true))) {
+            true => Some(after),
+            false => None
+        };
+        return Ok(cycle);
+    }
+
+    /// Executes up to the given number of cycles, it stops early when the program halts.
+    pub fn run(&mut self, cycles: u64) -> Result<(), String> {
+        for _ in 0..cycles {
+            if self.is_halted() {
+                break;
+            }
+            self.step()?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod emulator_tests {
+    use super::*;
+    use crate::Assembler;
+
+    fn emulator(source: &str) -> Emulator {
+        return Emulator::new(&Assembler::new().translate_program(source).unwrap());
+    }
+
+    #[test]
+    fn test_bus_and_registers() {
+        // Adds the two bytes at 10 and 11 and writes the sum to 12.
+        let mut emulator = emulator(".data 0x10: 0x30, 0x12\n\
+            00000: PASSB R1 0000; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001\n\
+            00001: LDI R0, 0100; JUMP 00010\n\
+            00010: LSL R0; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00011\n\
+            00011: LSL R0; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00100\n\
+            00100: IN R2, [R0]; JUMP 00101\n\
+            00101: R0 = R0 + 01; JUMP 00110\n\
+            00110: IN R3, [R0]; JUMP 00111\n\
+            00111: R0 = R0 + 01; JUMP 01000\n\
+            01000: ADD R2 R3; WRITE A; BUS OFF; FLAGS COPY; JUMP 01001\n\
+            01001: OUT [R0], R2; JUMP 01010\n\
+            01010: NOP; JUMP 01010");
+        emulator.run(100).unwrap();
+
+        assert!(emulator.is_halted());
+        assert_eq!(10, emulator.state.cycles);
+        assert_eq!(0x42, emulator.state.memory[0x12]);
+        assert_eq!([0x12, 0x00, 0x42, 0x12], emulator.state.registers[..4]);
+        assert!(!emulator.state.carry_flag && !emulator.state.zero_flag);
+    }
+
+    #[test]
+    fn test_conditional_jumps() {
+        // Counts R0 down from 3, the jump on the Zero Out leaves the loop, then the empty loop halts.
+        let mut emulator = emulator("R0 = 03\nloop {\n    R0 = R0 + FF; FLAGS COPY\n    if zero {\n        break\n    }\n}\nR1 = R1 + 01\nloop {\n}\n");
+        emulator.run(100).unwrap();
+        assert!(emulator.is_halted());
+        assert_eq!([0x00, 0x01], emulator.state.registers[..2]);
+        assert!(emulator.state.zero_flag);
+
+        let mut interrupted = self::emulator("00000: NOP; JUMPINTB 00010\n00010: NOP; JUMP 00000\n00011: CLC; JUMP 00011");
+        interrupted.run(5).unwrap();
+        assert!(!interrupted.is_halted());
+        interrupted.set_interrupt_b(true);
+        interrupted.run(5).unwrap();
+        assert!(interrupted.is_halted());
+        assert!(!interrupted.state.interrupt_b);
+    }
+
+    #[test]
+    fn test_jumps_to_itself_that_do_work() {
+        let mut counter = emulator("00000: R0 = R0 + 01; JUMP 00000");
+        assert!(!counter.is_halted());
+        counter.run(10).unwrap();
+        assert_eq!((10, 0x0A), (counter.state.cycles, counter.state.registers[0]));
+
+        // The loop halts once writing the same value again changed nothing.
+        let mut output = emulator("R0 = FE\nR1 = 05\nloop {\n    (R0) = R1\n}");
+        output.run(10).unwrap();
+        assert!(output.is_halted());
+        assert_eq!((4, 0x05), (output.state.cycles, output.state.memory[0xFE]));
+        output.state.registers[1] = 0x06;
+        assert!(!output.is_halted());
+        output.run(10).unwrap();
+        assert_eq!(0x06, output.state.memory[0xFE]);
+    }
+
+    #[test]
+    fn test_missing_instruction() {
+        let mut wrong = emulator(MISSING_INSTRUCTION.wrong);
+        assert!(wrong.run(10).unwrap_err().starts_with(MISSING_INSTRUCTION.code));
+        assert_eq!(1, wrong.state.cycles);
+        assert!(emulator(MISSING_INSTRUCTION.right).run(10).is_ok());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::checking::Random;
+use crate::emulator::Emulator;
+use crate::stimulus::{PortWrite, Stimulus, StimulusEvent, Trigger};
+use crate::translated::TranslatedProgram;
+
+/// The most input combinations that are all tried, larger input spaces are sampled.
+pub const MAX_EXHAUSTIVE_INPUTS: usize = 65536;
+
+/// The most cycles every program runs for one input combination when no number is given.
+pub const DEFAULT_EQUIVALENCE_CYCLES: u64 = 1000;
+
+/// The input combinations tried for a larger input space when no number is given.
+pub const DEFAULT_SAMPLES: usize = 10000;
+
+/// The name of the schedule without interrupts that is always tried first.
+pub const NO_INTERRUPTS: &str = "no Interrupts";
+
+/// Compares two programs over the values of input ports and interrupt schedules, like `FC` and `FD` without interrupts.
+pub struct EquivalenceSettings {
+    pub input_ports: Vec<u8>,
+    /// Named stimuli with interrupts, and inputs that override the tried values, tried after the schedule without interrupts.
+    pub schedules: Vec<(String, Stimulus)>,
+    /// The most cycles every program runs for one input combination.
+    pub cycles: u64,
+    pub samples: usize,
+    pub seed: u64
+}
+
+impl EquivalenceSettings {
+    pub fn new() -> EquivalenceSettings {
+        return EquivalenceSettings {
+            input_ports: vec![0xFC, 0xFD],
+            schedules: Vec::new(),
+            cycles: DEFAULT_EQUIVALENCE_CYCLES,
+            samples: DEFAULT_SAMPLES,
+            seed: 1
+        };
+    }
+}
+
+impl Default for EquivalenceSettings {
+    fn default() -> Self {
+        return EquivalenceSettings::new();
+    }
+}
+
+/// What a program did for one input combination.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Behaviour {
+    /// The writes to the I/O ports, in order.
+    pub writes: Vec<PortWrite>,
+    pub halted: bool,
+    /// The emulator error that ended the run early, a program that stops with an error differs from one that does not.
+    pub error: Option<String>
+}
+
+impl Behaviour {
+    fn outputs(&self) -> Vec<(u8, u8)> {
+        return self.writes.iter().map(|write| (write.port, write.value)).collect();
+    }
+}
+
+/// An input combination the programs behave differently for.
+pub struct Difference {
+    /// Every input port with its value.
+    pub inputs: Vec<(u8, u8)>,
+    pub schedule: String,
+    pub behaviours: [Behaviour; 2],
+    /// The index of the first write that differs, or the number of writes if only one program goes on.
+    pub first_difference: usize
+}
+
+pub enum Equivalence {
+    /// No difference was found for this many input combinations, `exhaustive` if they were all there are.
+    Equivalent { checked: usize, exhaustive: bool },
+    Different(Box<Difference>)
+}
+
+/// Runs both programs for every input combination and schedule and compares the values they write to the I/O ports,
+/// so the programs may use different addresses, registers and numbers of cycles.
+/// Input spaces up to `MAX_EXHAUSTIVE_INPUTS` combinations per schedule are tried completely with the fewest set bits first,
+/// so the first difference is a minimal one, larger ones are sampled and a difference is shrunk by clearing bits.
+pub fn check_equivalence(programs: [&TranslatedProgram; 2], settings: &EquivalenceSettings) -> Equivalence {
+    let mut schedules = vec![(NO_INTERRUPTS.to_string(), Stimulus::default())];
+    schedules.extend(settings.schedules.iter().cloned());
+
+    let size = 256usize.checked_pow(settings.input_ports.len() as u32).unwrap_or(usize::MAX);
+    let exhaustive = size <= MAX_EXHAUSTIVE_INPUTS;
+    let combinations: Vec<Vec<u8>> = match exhaustive {
+        true => {
+            let mut combinations: Vec<Vec<u8>> = (0..size)
+                .map(|index| (0..settings.input_ports.len()).map(|port| (index >> (8 * port)) as u8).rev().collect())
+                .collect();
+            combinations.sort_by_key(|values| (bits(values), values.clone()));
+            combinations
+        },
+        false => {
+            let mut random = Random::new(settings.seed);
+            (0..settings.samples)
+                .map(|_| settings.input_ports.iter().map(|_| random.next() as u8).collect())
+                .collect()
+        }
+    };
+
+    let mut checked = 0;
+    for (name, schedule) in &schedules {
+        for values in &combinations {
+            if let Some(difference) = compare(programs, settings, schedule, values) {
+                let values = match exhaustive {
+                    true => values.clone(),
+                    false => shrink(programs, settings, schedule, values.clone())
+                };
+                let behaviours = run_both(programs, settings, schedule, &values);
+                let first_difference = first_difference(&behaviours).unwrap_or(difference);
+                return Equivalence::Different(Box::new(Difference {
+                    inputs: settings.input_ports.iter().copied().zip(values).collect(),
+                    schedule: name.clone(),
+                    behaviours,
+                    first_difference
+                }));
+            }
+            checked += 1;
+        }
+    }
+    return Equivalence::Equivalent { checked, exhaustive };
+}
+
+/// The inputs, the writes of both programs and where they differ, `names` are the names of the programs.
+pub fn format_difference(names: [&str; 2], difference: &Difference) -> String {
+    let inputs: Vec<String> = difference.inputs.iter().map(|(port, value)| format!("{:02X}={:02X}", port, value)).collect();
+    let mut text = format!("Different for {} with {}\n", inputs.join(" "), difference.schedule);
+    for (name, behaviour) in names.iter().zip(difference.behaviours.iter()) {
+        let writes: Vec<String> = behaviour.writes.iter().map(|write| format!("{:02X}={:02X}", write.port, write.value)).collect();
+        let end = match (&behaviour.error, behaviour.halted) {
+            (Some(error), _) => format!(", stopped: {}", error),
+            (None, true) => ", halted".to_string(),
+            (None, false) => String::new()
+        };
+        text.push_str(&format!("{}: {}{}\n", name, writes.join(" "), end));
+    }
+    text.push_str(&format!("The first Difference is Write {}\n", difference.first_difference + 1));
+    return text;
+}
+
+fn bits(values: &[u8]) -> u32 {
+    return values.iter().map(|value| value.count_ones()).sum();
+}
+
+fn run(program: &TranslatedProgram, settings: &EquivalenceSettings, schedule: &Stimulus, values: &[u8]) -> Behaviour {
+    // The tried values come first, so the inputs of the schedule win.
+    let mut stimulus = Stimulus::default();
+    for (port, value) in settings.input_ports.iter().zip(values.iter()) {
+        stimulus.events.push((Trigger::Cycle(0), StimulusEvent::Input(*port, *value)));
+    }
+    stimulus.events.extend(schedule.events.iter().copied());
+
+    let mut emulator = Emulator::new(program);
+    let (writes, error) = stimulus.run_until_error(&mut emulator, settings.cycles);
+    return Behaviour { writes, halted: emulator.is_halted(), error };
+}
+
+fn run_both(programs: [&TranslatedProgram; 2], settings: &EquivalenceSettings, schedule: &Stimulus, values: &[u8]) -> [Behaviour; 2] {
+    return programs.map(|program| run(program, settings, schedule, values));
+}
+
+/// Where the writes of two behaviours differ. A program that is still running after the cycles may just be slower,
+/// so only the writes both made are compared, unless the other program halted or stopped with an error.
+fn first_difference(behaviours: &[Behaviour; 2]) -> Option<usize> {
+    let outputs = [behaviours[0].outputs(), behaviours[1].outputs()];
+    let common = outputs[0].len().min(outputs[1].len());
+    if let Some(index) = (0..common).find(|index| outputs[0][*index] != outputs[1][*index]) {
+        return Some(index);
+    }
+
+    let ended = |behaviour: &Behaviour| behaviour.halted || behaviour.error.is_some();
+    let longer = match outputs[0].len() > outputs[1].len() {
+        true => 0,
+        false => 1
+    };
+    let shorter_ended = outputs[0].len() != outputs[1].len() && ended(&behaviours[1 - longer]);
+    let both_ended_differently = ended(&behaviours[0]) && ended(&behaviours[1]) && behaviours[0].error.is_some() != behaviours[1].error.is_some();
+    return match shorter_ended || both_ended_differently {
+        true => Some(common),
+        false => None
+    };
+}
+
+fn compare(programs: [&TranslatedProgram; 2], settings: &EquivalenceSettings, schedule: &Stimulus, values: &[u8]) -> Option<usize> {
+    return first_difference(&run_both(programs, settings, schedule, values));
+}
+
+/// Clears the bits of the values one at a time while the programs still differ.
+fn shrink(programs: [&TranslatedProgram; 2], settings: &EquivalenceSettings, schedule: &Stimulus, mut values: Vec<u8>) -> Vec<u8> {
+    for index in 0..values.len() {
+        for bit in (0..8).rev() {
+            if values[index] & 1 << bit == 0 {
+                continue;
+            }
+            let mut smaller = values.clone();
+            smaller[index] &= !(1 << bit);
+            if compare(programs, settings, schedule, &smaller).is_some() {
+                values = smaller;
+            }
+        }
+    }
+    return values;
+}
+
+#[cfg(test)]
+mod equivalence_tests {
+    use super::*;
+    use crate::Assembler;
+
+    /// Writes FC + FD to FE once.
+    const ADD: &str = "R0 = FC\n\
+        R1 = (R0)\n\
+        R0 = FD\n\
+        R1 = (R0) + R1\n\
+        R0 = FE\n\
+        (R0) = R1\n\
+        loop {\n\
+        }";
+
+    /// The same with other registers and a bug for sums with bit 7 set.
+    const BROKEN_ADD: &str = "R5 = FC\n\
+        R6 = (R5)\n\
+        R5 = FD\n\
+        R6 = (R5) + R6\n\
+        if negative {\n\
+            R6 = R6 + 01\n\
+        }\n\
+        R5 = FE\n\
+        (R5) = R6\n\
+        loop {\n\
+        }";
+
+    fn translate(source: &str) -> TranslatedProgram {
+        return Assembler::new().translate_program(source).unwrap();
+    }
+
+    #[test]
+    fn test_equivalent_programs() {
+        let reordered = translate("R2 = FD\nR3 = (R2)\nR2 = FC\nR3 = (R2) + R3\nR2 = FE\n(R2) = R3\nloop {\n}");
+        match check_equivalence([&translate(ADD), &reordered], &EquivalenceSettings::new()) {
+            Equivalence::Equivalent { checked, exhaustive } => assert_eq!((65536, true), (checked, exhaustive)),
+            Equivalence::Different(difference) => panic!("{}", format_difference(["add", "reordered"], &difference))
+        }
+    }
+
+    #[test]
+    fn test_minimal_difference() {
+        let programs = [&translate(ADD), &translate(BROKEN_ADD)];
+        let difference = match check_equivalence(programs, &EquivalenceSettings::new()) {
+            Equivalence::Different(difference) => difference,
+            Equivalence::Equivalent { .. } => panic!("The programs are different")
+        };
+        assert_eq!(vec![(0xFC, 0x00), (0xFD, 0x80)], difference.inputs);
+        assert_eq!("Different for FC=00 FD=80 with no Interrupts\nadd: FE=80, halted\nbroken: FE=81, halted\nThe first Difference is Write 1\n",
+            format_difference(["add", "broken"], &difference));
+
+        let settings = EquivalenceSettings { input_ports: vec![0xFC, 0xFD, 0xFF], samples: 50, ..EquivalenceSettings::new() };
+        match check_equivalence(programs, &settings) {
+            Equivalence::Different(difference) => assert_eq!(vec![(0xFC, 0x00), (0xFD, 0x80), (0xFF, 0x00)], difference.inputs),
+            Equivalence::Equivalent { .. } => panic!("The programs are different")
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::convert::TryFrom;
+use crate::diagnostics::{INVALID_ADDRESS, INVALID_WORD};
+use crate::dialect::Dialect;
+use crate::disassembly::disassemble_instruction;
+use crate::instruction::{Instruction, InstructionAddress, INSTRUCTION_WORD_BITS};
+use crate::parsing::parse_line;
+use crate::rtl::describe_instruction;
+
+/// One hardware field of an instruction word, named and numbered like in `2iLanguage.txt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldExplanation {
+    pub name: &'static str,
+    pub bits: &'static str,
+    pub value: String,
+    pub meaning: String
+}
+
+/// What every ALU function computes, indexed by MALUS3..MALUS0.
+const ALU_FUNCTIONS: [&str; 16] = [
+    "ADDH: F = A + B, Carry Out is Carry In OR the Overflow Carry",
+    "PASSA: F = A, Carry Out is 0",
+    "NOR: F = A NOR B, Carry Out is 0",
+    "ZERO: F = 0, Carry Out is 0",
+    "ADD: F = A + B, Carry Out is the Overflow Carry",
+    "ADDS: F = A + B + 1, Carry Out is the negated Overflow Carry",
+    "ADDC: F = A + B + Carry Flag, Carry Out is the Overflow Carry",
+    "ADDSC: F = A + B + NOT Carry Flag, Carry Out is the negated Overflow Carry",
+    "LSR: F = A >> 1, 0 is shifted in, Carry Out is A(0)",
+    "RR: F = A >> 1, A(0) is shifted in, Carry Out is A(0)",
+    "RRC: F = A >> 1, the Carry Flag is shifted in, Carry Out is A(0)",
+    "ASR: F = A >> 1, A(7) is shifted in, Carry Out is A(0)",
+    "PASSB: F = B, Carry Out is 0",
+    "BSETC: F = B, Carry Out is 1",
+    "BHOLDC: F = B, Carry Out is the Carry Flag",
+    "BINVC: F = B, Carry Out is the negated Carry Flag"
+];
+
+/// Reads what the explain command is given: a `.2ia` line, or a word in binary (`00 00010 01 ...`) or hexadecimal (`0x00900E2`).
+/// Words may be prefixed with their address like in `.2i` files, lines and words without one are at 00000.
+pub fn parse_explain_input(input: &str, dialect: Dialect) -> Result<Instruction, String> {
+    let input = input.trim();
+    let (address, rest) = match input.split_once(':') {
+        Some((address, rest)) if address.trim().len() == 5 => (
+            InstructionAddress::try_from(address.trim().to_string())
+                .map_err(|err| INVALID_ADDRESS.message(format!("Error Parsing Instruction Address: {}", err.description)))?,
+            rest.trim()
+        ),
+        _ => (InstructionAddress::from_u8(0), input)
+    };
+
+    if rest.contains(';') {
+        return parse_line(&format!("{}: {}", address, rest), dialect).map(|code_line| code_line.instruction);
+    }
+
+    let digits: String = rest.chars().filter(|ch| !ch.is_whitespace() && *ch != '_').collect();
+    let word = if digits.len() == INSTRUCTION_WORD_BITS as usize && digits.chars().all(|ch| ch == '0' || ch == '1') {
+        u32::from_str_radix(&digits, 2).ok()
+    } else {
+        let hex = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")).unwrap_or(&digits);
+        u32::from_str_radix(hex, 16).ok()
+    };
+
+    return match word {
+        Some(word) if word < 1 << INSTRUCTION_WORD_BITS => Ok(Instruction::from_word(address, word)),
+        Some(_) => Err(INVALID_WORD.message(format!("The Word {} has more than {} Bits", rest, INSTRUCTION_WORD_BITS))),
+        None => Err(INVALID_WORD.message(format!("{} is neither a Source Line nor a {} Bit Word in binary or hexadecimal", rest, INSTRUCTION_WORD_BITS)))
+    };
+}
+
+/// Explains every hardware field of the instruction, from the most significant one down.
+pub fn explain_instruction(instruction: &Instruction) -> Vec<FieldExplanation> {
+    let a = instruction.content_a.to_u8();
+    let b = instruction.content_b;
+    let b_register = b.to_u8() & 0b111;
+
+    let mut next_address = instruction.next_address;
+    let jump = match (instruction.address_control.ac1, instruction.address_control.ac0, next_address.bit0) {
+        (false, false, _) => None,
+        (false, true, false) => Some("INTA (Level Interrupt)"),
+        (false, true, true) => Some("the Carry Flag of the Flag Register"),
+        (true, false, false) => Some("CO (Carry Out of the ALU)"),
+        (true, false, true) => Some("ZO (Zero Out of the ALU)"),
+        (true, true, false) => Some("NO (Negative Out of the ALU)"),
+        (true, true, true) => Some("INTB (Edge Interrupt)")
+    };
+    let (address_control, next_address_meaning) = match jump {
+        None => (
+            format!("Jump to NA {}", next_address),
+            format!("Next Address {}", next_address)
+        ),
+        Some(condition) => {
+            next_address.bit0 = true;
+            let taken = next_address;
+            next_address.bit0 = false;
+            (
+                format!("Test {} (selected by NA0), jump to {} if it is 1, to {} if it is 0", condition, taken, next_address),
+                format!("Next Address {}{}{}{}x, bit 0 is replaced by the condition", taken.bit4 as u8, taken.bit3 as u8, taken.bit2 as u8, taken.bit1 as u8)
+            )
+        }
+    };
+
+    let bus = match (instruction.bus_control.bus_wr, instruction.bus_control.bus_en) {
+        (_, false) => "Bus off".to_string(),
+        (false, true) => format!("Read the Bus at the Address in R{}", a),
+        (true, true) => format!("Write F to the Bus at the Address in R{}", a)
+    };
+    let register_b = match (instruction.register_address_control.acb, instruction.register_control.rws && instruction.register_control.rwe) {
+        (true, true) => format!("Constant {:02X} ({} sign extended), the written Register is R{}", b.to_constant_value(), b, b_register),
+        (true, false) => format!("Constant {:02X} ({} sign extended)", b.to_constant_value(), b),
+        (false, _) => format!("Register Address B: R{}", b_register)
+    };
+    let write = match (instruction.register_control.rws, instruction.register_control.rwe) {
+        (_, false) => "No Register is written".to_string(),
+        (false, true) => format!("Write F to R{} (Address A)", a),
+        (true, true) => format!("Write F to R{} (Address B)", b_register)
+    };
+    let input_a = match instruction.register_address_control.aca {
+        true => "A from the Data Bus".to_string(),
+        false => format!("A from R{}", a)
+    };
+    let input_b = match instruction.register_address_control.acb {
+        true => format!("B from the Constant {:02X}", b.to_constant_value()),
+        false => format!("B from R{}", b_register)
+    };
+    let flags = match instruction.alu_control.cf {
+        true => "Copy the ALU Flags into the Flag Register",
+        false => "Keep the Flag Register"
+    };
+
+    return vec![
+        field("MAC1..MAC0", "24..23", instruction.address_control.to_string(), address_control),
+        field("NA4..NA0", "22..18", instruction.next_address.to_string(), next_address_meaning),
+        field("BUSWR/BUSEN", "17..16", instruction.bus_control.to_string(), bus),
+        field("MRGAA2..MRGAA0", "15..13", instruction.content_a.to_string(), format!("Register Address A: R{}", a)),
+        field("MRGAB3..MRGAB0", "12..9", b.to_string(), register_b),
+        field("MRGWS/MRGWE", "8..7", instruction.register_control.to_string(), write),
+        field("MALUIA/MALUIB", "6..5", instruction.register_address_control.to_string(), format!("{}, {}", input_a, input_b)),
+        field("MALUS3..MALUS0", "4..1", instruction.alu_function.to_string(), ALU_FUNCTIONS[instruction.alu_function.to_u8() as usize].to_string()),
+        field("MCHFLG", "0", instruction.alu_control.to_string(), flags.to_string())
+    ];
+}
+
+fn field(name: &'static str, bits: &'static str, value: String, meaning: String) -> FieldExplanation {
+    return FieldExplanation { name, bits, value, meaning };
+}
+
+/// The field table followed by the instruction as source line and as register transfer, where it can be written as one.
+pub fn format_explanation(instruction: &Instruction, dialect: Dialect) -> String {
+    let fields = explain_instruction(instruction);
+    let name_width = fields.iter().map(|field| field.name.len()).max().unwrap_or(0);
+    let bits_width = fields.iter().map(|field| field.bits.len()).max().unwrap_or(0);
+    let value_width = fields.iter().map(|field| field.value.len()).max().unwrap_or(0);
+
+    let mut explanation = format!("{}\n\n", instruction);
+    for field in &fields {
+        explanation.push_str(&format!("{:name_width$}  {:bits_width$}  {:value_width$}  {}\n",
+            field.name, field.bits, field.value, field.meaning,
+            name_width = name_width, bits_width = bits_width, value_width = value_width));
+    }
+
+    explanation.push('\n');
+    explanation.push_str(&format_source_lines(instruction, dialect));
+    return explanation;
+}
+
+/// The instruction as source line and as register transfer, where it can be written as one.
+pub fn format_source_lines(instruction: &Instruction, dialect: Dialect) -> String {
+    let mut lines = match disassemble_instruction(instruction, dialect) {
+        Ok(source) => format!("Source:   {}\n", source),
+        Err(error) => format!("Source:   {}\n", error)
+    };
+    if let Ok(transfer) = describe_instruction(instruction) {
+        lines.push_str(&format!("Transfer: {}\n", transfer));
+    }
+    return lines;
+}
+
+#[cfg(test)]
+mod explanation_tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_are_the_same_instruction() {
+        let word = parse_explain_input("00 00010 01 000 0000 01 11 0001 0", Dialect::Standard).unwrap();
+        let hex = parse_explain_input(&format!("0x{:07X}", word.to_word()), Dialect::Standard).unwrap();
+        let line = parse_explain_input("PASSA R0 0000; WRITE A; BUS READ; FLAGS KEEP; JUMP 00010", Dialect::Standard).unwrap();
+
+        assert_eq!(word, hex);
+        assert_eq!(word, line);
+        assert_eq!(3, parse_explain_input("00011: NOP; JUMP 00100", Dialect::Standard).unwrap().address.to_u8());
+        assert!(parse_explain_input("0x2000000", Dialect::Standard).is_err());
+        assert!(parse_explain_input("hello", Dialect::Standard).is_err());
+    }
+
+    #[test]
+    fn test_conditional_jump_names_both_targets() {
+        let instruction = parse_explain_input("R0 = R0 + FF; FLAGS COPY; if Z goto 00101 else 00100", Dialect::Standard).unwrap();
+        let fields = explain_instruction(&instruction);
+
+        assert_eq!(9, fields.len());
+        assert_eq!("Test ZO (Zero Out of the ALU) (selected by NA0), jump to 00101 if it is 1, to 00100 if it is 0", fields[0].meaning);
+        assert_eq!("Next Address 0010x, bit 0 is replaced by the condition", fields[1].meaning);
+        assert_eq!("Constant FF (1111 sign extended)", fields[4].meaning);
+        assert!(format_explanation(&instruction, Dialect::Standard).ends_with("Transfer: R0 = R0 + FF; FLAGS COPY; if Z goto 00101 else 00100\n"));
+    }
+}
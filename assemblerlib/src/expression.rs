@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use crate::diagnostics::{INVALID_EXPRESSION, INVALID_SYMBOL_NAME, UNKNOWN_SYMBOL};
+
+/// Values of the symbols an assembly-time expression may refer to.
+pub type SymbolTable = HashMap<String, i64>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    Identifier(String),
+    Operator(&'static str),
+    OpenParenthesis,
+    CloseParenthesis
+}
+
+const OPERATORS: [&str; 20] = [
+    "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+    "+", "-", "*", "/", "%", "<", ">", "&", "|", "^", "!", "~"
+];
+
+/// Binary operators ordered from the loosest to the tightest binding.
+const PRECEDENCE_LEVELS: [&[&str]; 10] = [
+    &["||"],
+    &["&&"],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+/// Parses a number literal in decimal, hexadecimal (`0x`) or binary (`0b`) notation.
+pub fn parse_number(value: &str) -> Result<i64, String> {
+    let lowercase_value = value.to_ascii_lowercase();
+    let parsed = if let Some(hex) = lowercase_value.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16)
+    } else if let Some(binary) = lowercase_value.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2)
+    } else {
+        lowercase_value.parse::<i64>()
+    };
+
+    return parsed.map_err(|_| format!("Invalid Number {}", value));
+}
+
+pub fn is_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    return match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_'),
+        _ => false
+    };
+}
+
+/// Evaluates an assembly-time expression, comparisons and logical operators evaluate to 0 or 1.
+pub fn evaluate(expression: &str, symbols: &SymbolTable) -> Result<i64, String> {
+    let tokens = tokenize(expression)?;
+    let mut parser = ExpressionParser { tokens, position: 0, symbols };
+
+    let value = parser.parse_binary(0)?;
+
+    if parser.position != parser.tokens.len() {
+        return Err(INVALID_EXPRESSION.message(format!("Unexpected Symbol in Expression {}", expression.trim())));
+    }
+
+    return Ok(value);
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+
+        if ch.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if ch.is_ascii_alphanumeric() || ch == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(match ch.is_ascii_digit() {
+                true => Token::Number(parse_number(&word).map_err(|err| INVALID_EXPRESSION.message(err))?),
+                false => Token::Identifier(word)
+            });
+            continue;
+        }
+
+        match ch {
+            '(' => tokens.push(Token::OpenParenthesis),
+            ')' => tokens.push(Token::CloseParenthesis),
+            _ => {
+                let rest: String = chars[i..].iter().take(2).collect();
+                let operator = OPERATORS.iter()
+                    .find(|operator| rest.starts_with(**operator))
+                    .ok_or_else(|| INVALID_EXPRESSION.message(format!("Unexpected Symbol {} in Expression", ch)))?;
+                tokens.push(Token::Operator(operator));
+                i += operator.len();
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    return Ok(tokens);
+}
+
+struct ExpressionParser<'t> {
+    tokens: Vec<Token>,
+    position: usize,
+    symbols: &'t SymbolTable
+}
+
+impl<'t> ExpressionParser<'t> {
+    fn peek_operator(&self) -> Option<&'static str> {
+        return match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) => Some(operator),
+            _ => None
+        };
+    }
+
+    fn parse_binary(&mut self, level: usize) -> Result<i64, String> {
+        if level == PRECEDENCE_LEVELS.len() {
+            return self.parse_unary();
+        }
+
+        let mut value = self.parse_binary(level + 1)?;
+
+        while let Some(operator) = self.peek_operator().filter(|operator| PRECEDENCE_LEVELS[level].contains(operator)) {
+            self.position += 1;
+            let right = self.parse_binary(level + 1)?;
+            value = apply_binary_operator(operator, value, right)?;
+        }
+
+        return Ok(value);
+    }
+
+    fn parse_unary(&mut self) -> Result<i64, String> {
+        return match self.peek_operator() {
+            Some("-") => { self.position += 1; Ok(self.parse_unary()?.wrapping_neg()) },
+            Some("!") => { self.position += 1; Ok((self.parse_unary()? == 0) as i64) },
+            Some("~") => { self.position += 1; Ok(!self.parse_unary()?) },
+            _ => self.parse_primary()
+        };
+    }
+
+    fn parse_primary(&mut self) -> Result<i64, String> {
+        let token = self.tokens.get(self.position).cloned()
+            .ok_or_else(|| INVALID_EXPRESSION.message("Expression ends unexpectedly"))?;
+        self.position += 1;
+
+        return match token {
+            Token::Number(value) => Ok(value),
+            Token::Identifier(name) if name == "defined" => self.parse_defined(),
+            Token::Identifier(name) => self.symbols.get(&name)
+                .copied()
+                .ok_or_else(|| UNKNOWN_SYMBOL.message(format!("Unknown Symbol {}", name))),
+            Token::OpenParenthesis => {
+                let value = self.parse_binary(0)?;
+                self.expect(Token::CloseParenthesis)?;
+                Ok(value)
+            },
+            _ => Err(INVALID_EXPRESSION.message("Expected Number or Symbol in Expression"))
+        };
+    }
+
+    fn parse_defined(&mut self) -> Result<i64, String> {
+        self.expect(Token::OpenParenthesis)?;
+        let name = match self.tokens.get(self.position) {
+            Some(Token::Identifier(name)) => name.clone(),
+            _ => return Err(INVALID_SYMBOL_NAME.message("Expected Symbol Name after defined"))
+        };
+        self.position += 1;
+        self.expect(Token::CloseParenthesis)?;
+        return Ok(self.symbols.contains_key(&name) as i64);
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        if self.tokens.get(self.position) != Some(&token) {
+            return Err(INVALID_EXPRESSION.message("Unbalanced Parentheses in Expression"));
+        }
+        self.position += 1;
+        return Ok(());
+    }
+}
+
+fn apply_binary_operator(operator: &str, left: i64, right: i64) -> Result<i64, String> {
+    return Ok(match operator {
+        "||" => (left != 0 || right != 0) as i64,
+        "&&" => (left != 0 && right != 0) as i64,
+        "|" => left | right,
+        "^" => left ^ right,
+        "&" => left & right,
+        "==" => (left == right) as i64,
+        "!=" => (left != right) as i64,
+        "<" => (left < right) as i64,
+        "<=" => (left <= right) as i64,
+        ">" => (left > right) as i64,
+        ">=" => (left >= right) as i64,
+        "<<" => left.wrapping_shl(right as u32),
+        ">>" => left.wrapping_shr(right as u32),
+        "+" => left.wrapping_add(right),
+        "-" => left.wrapping_sub(right),
+        "*" => left.wrapping_mul(right),
+        "/" | "%" if right == 0 => return Err(INVALID_EXPRESSION.message("Division by Zero in Expression")),
+        "/" => left.wrapping_div(right),
+        "%" => left.wrapping_rem(right),
+        _ => return Err(INVALID_EXPRESSION.message(format!("Unknown Operator {}", operator)))
+    });
+}
+
+#[cfg(test)]
+mod expression_tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        let symbols = SymbolTable::new();
+        assert_eq!(Ok(7), evaluate("1 + 2 * 3", &symbols));
+        assert_eq!(Ok(9), evaluate("(1 + 2) * 3", &symbols));
+        assert_eq!(Ok(1), evaluate("1 + 1 == 2 && 0x10 > 0b1111", &symbols));
+        assert_eq!(Ok(-4), evaluate("-(2 << 1)", &symbols));
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut symbols = SymbolTable::new();
+        symbols.insert("DEBUG".to_string(), 2);
+        assert_eq!(Ok(1), evaluate("DEBUG >= 2", &symbols));
+        assert_eq!(Ok(1), evaluate("defined(DEBUG) && !defined(RELEASE)", &symbols));
+        assert!(evaluate("RELEASE", &symbols).is_err());
+    }
+
+    #[test]
+    fn test_malformed_expressions() {
+        let symbols = SymbolTable::new();
+        assert!(evaluate("(1 + 2", &symbols).is_err());
+        assert!(evaluate("1 +", &symbols).is_err());
+        assert!(evaluate("1 2", &symbols).is_err());
+        assert!(evaluate("4 / 0", &symbols).is_err());
+    }
+}
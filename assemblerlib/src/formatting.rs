@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::path::Path;
+use crate::Assembler;
+use crate::diagnostics::FORMAT_CHANGES_MEANING;
+use crate::structured::block_depth_change;
+use crate::translated::TranslatedProgram;
+
+/// Blocks of structured code are indented by this many spaces per level.
+const INDENT_WIDTH: usize = 4;
+
+/// A non-empty source line, split into the parts that are aligned in columns.
+struct LayoutLine {
+    /// The indentation and the `ADDRESS: ` prefix, in front of the first field.
+    prefix: String,
+    /// The `;` separated commands, or the whole text of a directive or block line.
+    fields: Vec<String>,
+    comment: Option<String>
+}
+
+impl LayoutLine {
+    /// The code of the line with every field but the last padded to its column.
+    fn code(&self, widths: &HashMap<(usize, usize), usize>) -> String {
+        let mut code = self.prefix.clone();
+        for (i, field) in self.fields.iter().enumerate() {
+            if i + 1 == self.fields.len() {
+                code.push_str(field);
+                continue;
+            }
+            let width = widths.get(&(self.fields.len(), i)).copied().unwrap_or(0) - match i {
+                0 => self.prefix.len(),
+                _ => 0
+            };
+            code.push_str(&format!("{:<width$} ", format!("{};", field), width = width + 1));
+        }
+        return code;
+    }
+
+    /// The width of a field, the first field includes the prefix so that columns line up at any indentation.
+    fn field_width(&self, index: usize) -> usize {
+        return match index {
+            0 => self.prefix.len() + self.fields[0].len(),
+            _ => self.fields[index].len()
+        };
+    }
+}
+
+/// Rewrites a `.2ia` source into the canonical layout, without checking that it still translates the same.
+/// Within a paragraph, the fields of lines with the same number of commands and the comments behind code are aligned in columns.
+/// Structured code is indented by block, whitespace is collapsed, number literals are written as `0x1F` and `0b101`,
+/// and runs of empty lines become one.
+pub fn format_source(source: &str) -> String {
+    let mut paragraphs: Vec<Vec<LayoutLine>> = vec![Vec::new()];
+    let mut depth: i32 = 0;
+
+    for line in source.lines() {
+        if line.trim().is_empty() {
+            paragraphs.push(Vec::new());
+            continue;
+        }
+
+        let indentation = match line.trim_start().starts_with('}') {
+            true => depth - 1,
+            false => depth
+        };
+        depth += block_depth_change(line);
+        if let Some(paragraph) = paragraphs.last_mut() {
+            paragraph.push(layout_line(line, indentation.max(0) as usize * INDENT_WIDTH));
+        }
+    }
+
+    return paragraphs.iter()
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format_paragraph(paragraph))
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
+/// Formats a source like `format_source` and fails instead of returning a layout that translates to other instructions or data.
+/// `name` and `base_directory` are used for error messages and includes, like for `translate_source`.
+pub fn format_program(assembler: &Assembler, name: &str, source: &str, base_directory: &Path) -> Result<String, String> {
+    let formatted = format_source(source);
+    let original = assembler.translate_source(name, source, base_directory)?;
+    let same = assembler.translate_source(name, &formatted, base_directory)
+        .map(|reformatted| same_translation(&original, &reformatted))
+        .unwrap_or(false);
+
+    if !same {
+        return Err(format!("Error in {}, Description: {}", name,
+            FORMAT_CHANGES_MEANING.message("The formatted Source translates to other Instructions")));
+    }
+    return Ok(formatted);
+}
+
+fn same_translation(original: &TranslatedProgram, formatted: &TranslatedProgram) -> bool {
+    let lines = |program: &TranslatedProgram| program.lines.iter()
+        .map(|line| line.to_string().trim_end().to_string())
+        .collect::<Vec<String>>();
+    return lines(original) == lines(formatted) && original.memory == formatted.memory;
+}
+
+fn format_paragraph(paragraph: &[LayoutLine]) -> String {
+    let mut widths: HashMap<(usize, usize), usize> = HashMap::new();
+    for line in paragraph.iter().filter(|line| line.fields.len() > 1) {
+        for index in 0..line.fields.len() - 1 {
+            let width = widths.entry((line.fields.len(), index)).or_insert(0);
+            *width = (*width).max(line.field_width(index));
+        }
+    }
+    let codes: Vec<String> = paragraph.iter().map(|line| line.code(&widths)).collect();
+
+    let comment_column = paragraph.iter().zip(codes.iter())
+        .filter(|(line, code)| line.comment.is_some() && !code.trim().is_empty())
+        .map(|(_, code)| code.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut formatted = String::new();
+    for (line, code) in paragraph.iter().zip(codes.iter()) {
+        match &line.comment {
+            Some(comment) if code.trim().is_empty() => formatted.push_str(&format!("{}#{}", code, comment)),
+            Some(comment) => formatted.push_str(&format!("{:<width$} #{}", code, comment, width = comment_column)),
+            None => formatted.push_str(code)
+        }
+        formatted.push('\n');
+    }
+    return formatted;
+}
+
+fn layout_line(line: &str, indentation: usize) -> LayoutLine {
+    let (code, comment) = match line.split_once('#') {
+        Some((code, comment)) => (code.trim(), Some(comment.trim_end().to_string())),
+        None => (line.trim(), None)
+    };
+    let indentation = " ".repeat(indentation);
+
+    if code.is_empty() {
+        return LayoutLine { prefix: indentation, fields: Vec::new(), comment };
+    }
+    // Directives keep their text, only file names in quotes are not touched.
+    if code.starts_with('.') || code.starts_with("use ") {
+        let text = match code.contains('"') {
+            true => code.to_string(),
+            false => normalize_words(code)
+        };
+        return LayoutLine { prefix: indentation, fields: vec![text], comment };
+    }
+
+    let (prefix, commands) = match code.split_once(':') {
+        Some((address, commands)) => (format!("{}{}: ", indentation, address.trim()), commands),
+        None => (indentation, code)
+    };
+    let fields = commands.split(';').map(normalize_field).collect();
+    return LayoutLine { prefix, fields, comment };
+}
+
+/// Collapses the whitespace of a command and puts one space behind every comma of pseudo instruction operands.
+fn normalize_field(field: &str) -> String {
+    if field.contains(',') && !field.contains('=') {
+        return field.split(',').map(normalize_words).collect::<Vec<String>>().join(", ");
+    }
+    return normalize_words(field);
+}
+
+fn normalize_words(text: &str) -> String {
+    return text.split_whitespace().map(normalize_number).collect::<Vec<String>>().join(" ");
+}
+
+/// Writes the prefix of a hexadecimal or binary literal in lower case and its digits in upper case.
+/// Punctuation like the `:` of `.data NAME 0x10:` stays behind the literal.
+fn normalize_number(word: &str) -> String {
+    let literal = word.trim_end_matches(|ch: char| !ch.is_ascii_alphanumeric());
+    let lowercase_literal = literal.to_ascii_lowercase();
+    for prefix in ["0x", "0b"] {
+        if let Some(digits) = lowercase_literal.strip_prefix(prefix) {
+            if !digits.is_empty() && digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+                return format!("{}{}{}", prefix, digits.to_ascii_uppercase(), &word[literal.len()..]);
+            }
+        }
+    }
+    return word.to_string();
+}
+
+#[cfg(test)]
+mod formatting_tests {
+    use super::*;
+
+    #[test]
+    fn test_format_aligns_columns() {
+        let source = "\n\n# Add two numbers\n\
+            00000: PASSB R0 1100;WRITE A; BUS   OFF; FLAGS KEEP; JUMP 00001 # first\n\
+            00001:  ADD R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00010\t# second\n\
+            00010: MOV R2,R0; JUMP 00011\n\
+            \n\n\n\
+            .data TABLE 0x0a:   1, 2\n\
+            loop {\n\
+            R0 = R0 + TABLE; FLAGS COPY # count\n\
+            if zero {\n\
+            break\n\
+            }\n\
+            }\n\n";
+        let expected = "# Add two numbers\n\
+            00000: PASSB R0 1100; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001 # first\n\
+            00001: ADD R0 R1;     WRITE A; BUS OFF; FLAGS KEEP; JUMP 00010 # second\n\
+            00010: MOV R2, R0; JUMP 00011\n\
+            \n\
+            .data TABLE 0x0A: 1, 2\n\
+            loop {\n    R0 = R0 + TABLE; FLAGS COPY # count\n    if zero {\n        break\n    }\n}\n";
+
+        assert_eq!(expected, format_source(source));
+        assert_eq!(expected, format_source(expected));
+    }
+
+    #[test]
+    fn test_format_keeps_translation() {
+        let example = include_str!("../../Examples/Example 2ia Code.2ia");
+        let formatted = format_program(&Assembler::new(), "example.2ia", example, Path::new(".")).unwrap();
+        assert!(formatted.contains("00100: LSL R1;        WRITE A;   BUS OFF;   FLAGS KEEP; JUMP 00101 # Same as 2*R1\n"));
+        assert_eq!(formatted, format_source(&formatted));
+
+        assert!(format_program(&Assembler::new(), "example.2ia", "00000: NOP; JUMP 0001", Path::new(".")).is_err());
+    }
+}
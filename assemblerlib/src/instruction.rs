@@ -1,9 +1,34 @@
 use std::fmt;
-use std::fmt::{Formatter, Error};
+use std::fmt::Formatter;
 use std::convert::TryFrom;
+use crate::diagnostics::{INVALID_CONSTANT, INVALID_REGISTER};
+use crate::suggestion::{closest, did_you_mean};
 
 #[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
 mod conversion_tests {
+    use super::*;
+
+    #[test]
+    fn test_word_round_trip() {
+        let address = InstructionAddress::from_u8(0b01001);
+        for word in [0u32, 1, 0x1FF_FFFF, 0x0AA_AAAA, 0x155_5555, 0b00_00010_01_000_0000_01_11_0001_0].iter() {
+            assert_eq!(*word, Instruction::from_word(address, *word).to_word());
+        }
+    }
+
+    #[test]
+    fn test_word_matches_display() {
+        let instruction = Instruction::from_word(InstructionAddress::from_u8(1), 0b00_00010_01_000_0000_01_11_0001_0);
+        assert_eq!("00001: 00 00010 01 000 0000 01 11 0001 0", format!("{}", instruction));
+    }
+
+    #[test]
+    fn test_constant_is_sign_extended() {
+        assert_eq!(0xFC, ContentB::from_u8(0b1100).to_constant_value());
+        assert_eq!(0x07, ContentB::from_u8(0b0111).to_constant_value());
+    }
+
     #[test]
     fn test_conversion_true() {
         assert_eq!("1".to_string(), format!("{}",true as i32))
@@ -14,6 +39,10 @@ mod conversion_tests {
     }
 }
 
+fn bit(value: u32, position: u32) -> bool {
+    return (value >> position) & 1 == 1;
+}
+
 fn try_bool_from_char(ch: char) -> Result<bool,String> {
     return match ch {
         '0' => Ok(false),
@@ -27,6 +56,7 @@ pub struct ParseError {
     pub description: String
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct InstructionAddress {
     pub bit4: bool,
     pub bit3: bool,
@@ -35,6 +65,27 @@ pub struct InstructionAddress {
     pub bit0: bool
 }
 
+impl InstructionAddress {
+    pub fn from_u8(value: u8) -> InstructionAddress {
+        let value = value as u32;
+        return InstructionAddress {
+            bit4: bit(value, 4),
+            bit3: bit(value, 3),
+            bit2: bit(value, 2),
+            bit1: bit(value, 1),
+            bit0: bit(value, 0)
+        };
+    }
+
+    pub fn to_u8(self) -> u8 {
+        return (self.bit4 as u8) << 4
+            | (self.bit3 as u8) << 3
+            | (self.bit2 as u8) << 2
+            | (self.bit1 as u8) << 1
+            | self.bit0 as u8;
+    }
+}
+
 impl fmt::Display for InstructionAddress {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f,"{}{}{}{}{}",
@@ -89,6 +140,7 @@ impl TryFrom<String> for InstructionAddress {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct AddressControl {
     pub ac1: bool,
     pub ac0: bool
@@ -103,6 +155,7 @@ impl fmt::Display for AddressControl {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct BusControl {
     pub bus_wr: bool,
     pub bus_en: bool
@@ -117,7 +170,7 @@ impl fmt::Display for BusControl {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct RegisterAddress {
     pub ad2: bool,
     pub ad1: bool,
@@ -125,7 +178,20 @@ pub struct RegisterAddress {
 }
 
 impl RegisterAddress {
-    pub fn to_content_b(&self) -> ContentB{
+    pub fn from_u8(value: u8) -> RegisterAddress {
+        let value = value as u32;
+        return RegisterAddress {
+            ad2: bit(value, 2),
+            ad1: bit(value, 1),
+            ad0: bit(value, 0)
+        };
+    }
+
+    pub fn to_u8(self) -> u8 {
+        return (self.ad2 as u8) << 2 | (self.ad1 as u8) << 1 | self.ad0 as u8;
+    }
+
+    pub fn to_content_b(self) -> ContentB{
         return ContentB{
             b3: false,
             b2: self.ad2,
@@ -135,19 +201,21 @@ impl RegisterAddress {
     }
 }
 
+const REGISTER_NAMES: [&str; 8] = ["R0", "R1", "R2", "R3", "R4", "R5", "R6", "R7"];
+
 impl TryFrom<&str> for RegisterAddress {
     type Error = String;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let value_chars: Vec<char> = value.chars().collect();
         match value_chars.len() {
-            0 => return Err("No Register Number".to_string()),
+            0 => return Err(INVALID_REGISTER.message("No Register Number")),
             2 => {},
-            _ => return Err("Invalid Register Number".to_string())
+            _ => return Err(INVALID_REGISTER.message(format!("{} is no Register Number{}", value, did_you_mean(closest(value, REGISTER_NAMES)))))
         }
 
         if value_chars[0] != 'R' {
-            return Err("Invalid Register Number".to_string())
+            return Err(INVALID_REGISTER.message(format!("{} is no Register Number{}", value, did_you_mean(closest(value, REGISTER_NAMES)))))
         }
 
         return match value_chars[1] {
@@ -191,7 +259,7 @@ impl TryFrom<&str> for RegisterAddress {
                 ad1: true,
                 ad0: true
             }),
-            _ => Err("Invalid Register Number".to_string())
+            _ => Err(INVALID_REGISTER.message(format!("{} is no Register Number", value)))
         }
     }
 }
@@ -206,7 +274,7 @@ impl fmt::Display for RegisterAddress {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ContentB {
     pub b3: bool,
     pub b2: bool,
@@ -214,33 +282,44 @@ pub struct ContentB {
     pub b0: bool
 }
 
+impl ContentB {
+    pub fn from_u8(value: u8) -> ContentB {
+        let value = value as u32;
+        return ContentB {
+            b3: bit(value, 3),
+            b2: bit(value, 2),
+            b1: bit(value, 1),
+            b0: bit(value, 0)
+        };
+    }
+
+    pub fn to_u8(self) -> u8 {
+        return (self.b3 as u8) << 3 | (self.b2 as u8) << 2 | (self.b1 as u8) << 1 | self.b0 as u8;
+    }
+
+    /// The 8 bit value the ALU sees when this is used as a constant, constants are sign extended.
+    pub fn to_constant_value(self) -> u8 {
+        return match self.b3 {
+            true => self.to_u8() | 0xF0,
+            false => self.to_u8()
+        };
+    }
+}
+
 impl TryFrom<&str> for ContentB {
     type Error = String;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let char_array: Vec<char> = value.chars().collect();
         match char_array.len() {
-            0..=3 => return Err("Could not Parse Constant".to_string()),
             4 => {},
-            _ => return Err("Could not Parse Constant".to_string())
+            _ => return Err(INVALID_CONSTANT.message(format!("Could not Parse Constant {}, expected 4 Bits", value)))
         }
 
-        let b0 = match try_bool_from_char(char_array[3]){
-            Ok(bit) => bit,
-            Err(err) => return Err(err)
-        };
-        let b1 = match try_bool_from_char(char_array[2]){
-            Ok(bit) => bit,
-            Err(err) => return Err(err)
-        };
-        let b2 = match try_bool_from_char(char_array[1]){
-            Ok(bit) => bit,
-            Err(err) => return Err(err)
-        };
-        let b3 = match try_bool_from_char(char_array[0]){
-            Ok(bit) => bit,
-            Err(err) => return Err(err)
-        };
+        let b0 = try_bool_from_char(char_array[3]).map_err(|err| INVALID_CONSTANT.message(err))?;
+        let b1 = try_bool_from_char(char_array[2]).map_err(|err| INVALID_CONSTANT.message(err))?;
+        let b2 = try_bool_from_char(char_array[1]).map_err(|err| INVALID_CONSTANT.message(err))?;
+        let b3 = try_bool_from_char(char_array[0]).map_err(|err| INVALID_CONSTANT.message(err))?;
         return Ok(ContentB {
             b3,
             b2,
@@ -262,6 +341,7 @@ impl fmt::Display for ContentB {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct RegisterControl {
     pub rws: bool,
     pub rwe: bool
@@ -276,6 +356,7 @@ impl fmt::Display for RegisterControl {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct RegisterAddressControl {
     pub aca: bool,
     pub acb: bool
@@ -290,6 +371,7 @@ impl fmt::Display for RegisterAddressControl {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ALUFunction {
     pub alu3: bool,
     pub alu2: bool,
@@ -297,6 +379,22 @@ pub struct ALUFunction {
     pub alu0: bool
 }
 
+impl ALUFunction {
+    pub fn from_u8(value: u8) -> ALUFunction {
+        let value = value as u32;
+        return ALUFunction {
+            alu3: bit(value, 3),
+            alu2: bit(value, 2),
+            alu1: bit(value, 1),
+            alu0: bit(value, 0)
+        };
+    }
+
+    pub fn to_u8(self) -> u8 {
+        return (self.alu3 as u8) << 3 | (self.alu2 as u8) << 2 | (self.alu1 as u8) << 1 | self.alu0 as u8;
+    }
+}
+
 impl fmt::Display for ALUFunction {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f,"{}{}{}{}",
@@ -308,6 +406,7 @@ impl fmt::Display for ALUFunction {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ALUControl {
     pub cf: bool
 }
@@ -318,6 +417,7 @@ impl fmt::Display for ALUControl {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Instruction {
     pub address: InstructionAddress,
     pub address_control: AddressControl,
@@ -331,6 +431,57 @@ pub struct Instruction {
     pub alu_control: ALUControl
 }
 
+/// Number of bits in an instruction word of the 2i control store.
+pub const INSTRUCTION_WORD_BITS: u32 = 25;
+
+impl Instruction {
+    /// Packs the instruction into a word with the bit numbering of `2iLanguage.txt`, MCHFLG is bit 0.
+    pub fn to_word(&self) -> u32 {
+        return (self.address_control.ac1 as u32) << 24
+            | (self.address_control.ac0 as u32) << 23
+            | (self.next_address.to_u8() as u32) << 18
+            | (self.bus_control.bus_wr as u32) << 17
+            | (self.bus_control.bus_en as u32) << 16
+            | (self.content_a.to_u8() as u32) << 13
+            | (self.content_b.to_u8() as u32) << 9
+            | (self.register_control.rws as u32) << 8
+            | (self.register_control.rwe as u32) << 7
+            | (self.register_address_control.aca as u32) << 6
+            | (self.register_address_control.acb as u32) << 5
+            | (self.alu_function.to_u8() as u32) << 1
+            | self.alu_control.cf as u32;
+    }
+
+    /// Unpacks a word with the bit numbering of `2iLanguage.txt`, bits above bit 24 are ignored.
+    pub fn from_word(address: InstructionAddress, word: u32) -> Instruction {
+        return Instruction {
+            address,
+            address_control: AddressControl { ac1: bit(word, 24), ac0: bit(word, 23) },
+            next_address: InstructionAddress::from_u8((word >> 18) as u8 & 0x1F),
+            bus_control: BusControl { bus_wr: bit(word, 17), bus_en: bit(word, 16) },
+            content_a: RegisterAddress::from_u8((word >> 13) as u8 & 0x7),
+            content_b: ContentB::from_u8((word >> 9) as u8 & 0xF),
+            register_control: RegisterControl { rws: bit(word, 8), rwe: bit(word, 7) },
+            register_address_control: RegisterAddressControl { aca: bit(word, 6), acb: bit(word, 5) },
+            alu_function: ALUFunction::from_u8((word >> 1) as u8 & 0xF),
+            alu_control: ALUControl { cf: bit(word, 0) }
+        };
+    }
+
+    /// The addresses the instruction can continue at, conditional jumps go to NA with bit 0 cleared or set.
+    pub fn jump_targets(&self) -> Vec<InstructionAddress> {
+        if !self.address_control.ac1 && !self.address_control.ac0 {
+            return vec![self.next_address];
+        }
+
+        let mut not_taken = self.next_address;
+        not_taken.bit0 = false;
+        let mut taken = self.next_address;
+        taken.bit0 = true;
+        return vec![not_taken, taken];
+    }
+}
+
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {} {} {} {} {} {} {} {} {}",
@@ -1,13 +1,63 @@
+#![allow(clippy::needless_return)]
+
+use crate::diagnostics::INVALID_DATA_SYMBOL;
 use crate::translated::{TranslatedProgram, TranslatedLine};
-use crate::parsing::{CodeLine, parse_line};
+use crate::parsing::parse_line;
+use crate::source::{SourceLine, load_file, load_string, ANONYMOUS_SOURCE};
+use crate::dialect::{Dialect, parse_dialect_directive};
+use crate::rtl::{describe_instruction, resolve_operand_symbols};
+use crate::memory::{DataDefinition, MemoryImage, parse_data_directive};
+use crate::metadata::ProgramMetadata;
+use crate::structured::{StructuredProgram, is_structured_line};
+use crate::instruction::InstructionAddress;
+use crate::source::SourceLocation;
+use std::collections::HashMap;
+use std::path::Path;
 
-mod instruction;
+pub mod alu;
+pub mod analysis;
+pub mod checking;
+mod conditional;
+pub mod coverage;
+pub mod debugger;
+pub mod devices;
+pub mod diagnostics;
+pub mod dialect;
+pub mod disassembly;
+pub mod documentation;
+pub mod emulator;
+pub mod equivalence;
+pub mod explanation;
+mod expression;
+pub mod formatting;
+pub mod instruction;
+pub mod library;
+pub mod memory;
+pub mod metadata;
 mod parsing;
+mod pseudo;
+pub mod repl;
+mod rtl;
+mod structured;
+pub mod source;
+pub mod stimulus;
+pub mod suggestion;
+pub mod testing;
+pub mod trace;
 pub mod translated;
 
+pub use crate::conditional::parse_definition;
+pub use crate::expression::SymbolTable;
+
 pub struct AssemblerSettings {
     pub copy_comments: bool,
-    pub copy_instructions: bool
+    pub copy_instructions: bool,
+    /// Describe every instruction as a register transfer in its comment, in front of the source comment.
+    pub rtl_comments: bool,
+    /// Symbols that are defined before the first line is read, like `-D NAME=VALUE`.
+    pub defines: SymbolTable,
+    /// The dialect of every file that does not select one with `.dialect`.
+    pub dialect: Dialect
 }
 
 pub struct Assembler {
@@ -19,7 +69,10 @@ impl Assembler {
         return Assembler {
             settings: AssemblerSettings {
                 copy_comments: false,
-                copy_instructions: false
+                copy_instructions: false,
+                rtl_comments: false,
+                defines: SymbolTable::new(),
+                dialect: Dialect::Standard
             }
         };
     }
@@ -45,27 +98,262 @@ impl Assembler {
         return self;
     }
 
+    pub fn rtl_comments(&mut self, rtl_comments: bool) -> &mut Self {
+        self.settings.rtl_comments = rtl_comments;
+        return self;
+    }
+
+    pub fn define(&mut self, name: &str, value: i64) -> &mut Self {
+        self.settings.defines.insert(name.to_string(), value);
+        return self;
+    }
+
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.settings.dialect = dialect;
+        return self;
+    }
+
+    /// Translates a program given as a string, includes are resolved relative to the working directory.
     pub fn translate_program(&self, program_string: &str) -> Result<TranslatedProgram, String> {
-        let input_lines = program_string.lines();
-        let mut code_lines = Vec::<CodeLine>::new();
-        for (i,line) in input_lines.enumerate() {
-            let code_line = match parse_line(line) {
-                Ok(code_line) => {code_line},
+        return self.translate_source(ANONYMOUS_SOURCE, program_string, Path::new("."));
+    }
+
+    /// Translates a program given as a string under a file name, includes are resolved relative to the base directory.
+    pub fn translate_source(&self, name: &str, content: &str, base_directory: &Path) -> Result<TranslatedProgram, String> {
+        let source_lines = load_string(name, content, base_directory, &self.settings.defines)?;
+        return self.translate_source_lines(name, source_lines);
+    }
+
+    /// Reads a program and all of its includes from disk and translates it.
+    pub fn translate_file(&self, path: &Path) -> Result<TranslatedProgram, String> {
+        let source_lines = self.load_file(path)?;
+        return self.translate_source_lines(&path.display().to_string(), source_lines);
+    }
+
+    /// Reads the lines `translate_file` translates, with the defines of the settings.
+    pub fn load_file(&self, path: &Path) -> Result<Vec<SourceLine>, String> {
+        return load_file(path, &self.settings.defines);
+    }
+
+    pub fn translate_line(&self, line: &str) -> Result<TranslatedLine, String> {
+        return translate_line_in_dialect(line, self.settings.dialect)
+            .map(|line| self.add_rtl_comment(line));
+    }
+
+    /// `main_file` is the name of the translated file, its lines may come after those of an include at its top.
+    fn translate_source_lines(&self, main_file: &str, source_lines: Vec<SourceLine>) -> Result<TranslatedProgram, String> {
+        // Lines are kept with the position of their source line, so structured code can be placed in between at the end.
+        let mut translated_lines: Vec<(usize, TranslatedLine, SourceLocation)> = Vec::new();
+        let mut structured_program = StructuredProgram::new();
+        // A `.dialect` directive only applies to the rest of its own file, includes start in the default dialect.
+        let mut file_dialects: HashMap<String, Dialect> = HashMap::new();
+        let mut memory = MemoryImage::new();
+        // Data symbols are usable as constants in transfers, `.data` expressions may also use the defines.
+        let mut data_symbols = SymbolTable::new();
+        let mut symbols = self.settings.defines.clone();
+        let mut metadata = ProgramMetadata::new();
+        // Only the header of the translated file describes the program, not the headers of its includes.
+        for (index, source_line) in source_lines.into_iter().enumerate() {
+            let dialect = *file_dialects.entry(source_line.location.file.clone())
+                .or_insert(self.settings.dialect);
+
+            if let Some(selected_dialect) = parse_dialect_directive(&source_line.text) {
+                let selected_dialect = selected_dialect
+                    .map_err(|error| format!("Error in {}, Description: {}", source_line.location, error))?;
+                file_dialects.insert(source_line.location.file.clone(), selected_dialect);
+                continue;
+            }
+
+            if let Some(definition) = parse_data_directive(&source_line.text, &symbols) {
+                definition
+                    .and_then(|definition| place_data(definition, &mut memory, &mut symbols, &mut data_symbols))
+                    .map_err(|error| format!("Error in {}, Description: {}", source_line.location, error))?;
+                continue;
+            }
+
+            let text = resolve_operand_symbols(&source_line.text, &data_symbols)
+                .map_err(|error| format!("Error in {}, Description: {}", source_line.location, error))?;
+
+            if is_structured_line(&text) {
+                structured_program.process_line(&text, &source_line.location, index, dialect)
+                    .map_err(|error| format!("Error in {}, Description: {}", source_line.location, error))?;
+                continue;
+            }
+
+            let line = match translate_line_in_dialect(&text, dialect) {
+                Ok(line) => self.add_rtl_comment(line),
                 Err(error) => {
-                    return Err(format!("Error in Line {}, Description: {}", i, error));
+                    return Err(format!("Error in {}, Description: {}", source_line.location, error));
                 }
             };
-            code_lines.push(code_line);
+
+            if let TranslatedLine::CommentLine(comment) = &line {
+                if source_line.location.file == main_file {
+                    metadata.read_comment(comment);
+                }
+            }
+
+            match line {
+                TranslatedLine::InstructionLine(_, _) => translated_lines.push((index, line, source_line.location)),
+                _ if self.settings.copy_comments => translated_lines.push((index, line, source_line.location)),
+                _ => {}
+            }
         }
-        let mut program = TranslatedProgram::new();
-        for line in code_lines {
-            program.lines.push(TranslatedLine::InstructionLine(line.instruction, line.comment))
+
+        let used_addresses: Vec<InstructionAddress> = translated_lines.iter()
+            .filter_map(|(_, line, _)| match line {
+                TranslatedLine::InstructionLine(instruction, _) => Some(instruction.address),
+                _ => None
+            })
+            .collect();
+        let mut structured_lines = structured_program.compile(&used_addresses)?;
+        structured_lines.sort_by_key(|generated| generated.instruction.address.to_u8());
+        for generated in structured_lines {
+            let line = self.add_rtl_comment(TranslatedLine::InstructionLine(generated.instruction, generated.comment));
+            translated_lines.push((generated.index, line, generated.location));
         }
+        translated_lines.sort_by_key(|(index, _, _)| *index);
 
+        let mut program = TranslatedProgram::new();
+        for (_, line, location) in translated_lines {
+            program.push(line, location);
+        }
+        program.memory = memory;
+        program.metadata = metadata;
+        program.symbols = data_symbols;
         return Ok(program)
     }
 
-    pub fn translate_line(&self, line: &str) {
+    fn add_rtl_comment(&self, line: TranslatedLine) -> TranslatedLine {
+        if !self.settings.rtl_comments {
+            return line;
+        }
+
+        return match line {
+            TranslatedLine::InstructionLine(instruction, comment) => match describe_instruction(&instruction) {
+                Ok(description) => {
+                    let comment = match comment {
+                        Some(comment) => format!(" {} #{}", description, comment),
+                        None => format!(" {}", description)
+                    };
+                    TranslatedLine::InstructionLine(instruction, Some(comment))
+                },
+                Err(_) => TranslatedLine::InstructionLine(instruction, comment)
+            },
+            line => line
+        };
+    }
+}
 
+/// Places the bytes of a `.data` line and defines its name as a symbol for the address.
+fn place_data(definition: DataDefinition, memory: &mut MemoryImage, symbols: &mut SymbolTable, data_symbols: &mut SymbolTable) -> Result<(), String> {
+    definition.place(memory)?;
+
+    if let Some(name) = definition.name {
+        if symbols.contains_key(&name) {
+            return Err(INVALID_DATA_SYMBOL.message(format!("The Symbol {} is already defined", name)));
+        }
+        data_symbols.insert(name.clone(), definition.address as i64);
+        symbols.insert(name, definition.address as i64);
     }
-}
\ No newline at end of file
+    return Ok(());
+}
+
+fn translate_line_in_dialect(line: &str, dialect: Dialect) -> Result<TranslatedLine, String> {
+    let split_comment: Vec<&str> = line.splitn(2, '#').collect();
+
+    if !split_comment[0].trim().is_empty() {
+        let code_line = parse_line(line, dialect)?;
+        return Ok(TranslatedLine::InstructionLine(code_line.instruction, code_line.comment));
+    }
+
+    return match split_comment.get(1) {
+        Some(comment) => Ok(TranslatedLine::CommentLine(comment.to_string())),
+        None => Ok(TranslatedLine::EmptyLine)
+    };
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        return Assembler::new();
+    }
+}
+
+#[cfg(test)]
+mod assembler_tests {
+    use super::*;
+    use crate::source::TemporaryDirectory;
+    use crate::diagnostics::MISSING_ADDRESS;
+
+    #[test]
+    fn test_dialect_directive_selects_dialect() {
+        let standard = "00000: PASSB R1 0011; WRITE A; BUS OFF; FLAGS KEEP; JUMPCF 00001";
+        let course = ".dialect course\n00000: B R1 0011; WRITE; BUS OFF; FLAGS KEEP; TEST CF JMP 00001";
+
+        let assembler = Assembler::new();
+        assert_eq!(
+            assembler.translate_program(standard).unwrap().to_string(),
+            assembler.translate_program(course).unwrap().to_string()
+        );
+        assert!(assembler.translate_program(".dialect course\n.dialect standard\n00000: B R1 0011; WRITE; BUS OFF; FLAGS KEEP; JMP 00001").is_err());
+        assert!(Assembler::new().dialect(Dialect::Course).translate_program(standard).is_err());
+    }
+
+    #[test]
+    fn test_data_symbols_are_constants() {
+        let program = ".data TABLE 0xF8: 1, 2\n.data TABLE + 2: 3\nR0 = TABLE\nR0 = (R0) + TABLE\nloop {\n}";
+        let translated = Assembler::new().translate_program(program).unwrap();
+
+        assert_eq!(Some(3), translated.memory.get(0xFA));
+        assert_eq!(
+            Assembler::new().translate_program("R0 = F8\nR0 = (R0) + F8\nloop {\n}").unwrap().to_string(),
+            translated.to_string()
+        );
+        assert!(Assembler::new().translate_program(".data TABLE 0x10: 1\nR0 = TABLE").err().unwrap().contains("Constant 0x10"));
+
+        let commands = Assembler::new().translate_program(".data TABLE 0xF8: 1\n00000: LDI R0, TABLE; JUMP 00001\n\
+            00001: PASSB R1 TABLE; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001 # TABLE").unwrap();
+        let constants = Assembler::new().translate_program("00000: LDI R0, 1000; JUMP 00001\n\
+            00001: PASSB R1 1000; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001 # TABLE").unwrap();
+        assert_eq!(constants.to_string(), commands.to_string());
+        assert!(Assembler::new().translate_program(".data TABLE 0x10: 1\n00000: LDI R0, TABLE; JUMP 00001").err().unwrap().contains("Constant 0x10"));
+        assert!(Assembler::new().translate_program(".data 0xFC: 1").err().unwrap().contains("I/O Port FC"));
+    }
+
+    #[test]
+    fn test_metadata_comes_from_the_translated_file() {
+        let directory = TemporaryDirectory::new("metadata");
+        std::fs::write(directory.join("ports.2ia"), "# INPUTS: P in FF\n# OUTPUTS: Q in FF").unwrap();
+
+        let program = Assembler::new().translate_source("main.2ia", ".include \"ports.2ia\"\n# INPUTS: A in FC\nloop {\n}", &directory.path).unwrap();
+        assert_eq!(vec!["A in FC"], program.metadata.inputs);
+        assert!(program.metadata.outputs.is_empty());
+    }
+
+    #[test]
+    fn test_loaded_lines_use_the_defines() {
+        let directory = TemporaryDirectory::new("defines");
+        std::fs::write(directory.join("main.2ia"), ".ifdef SIMULATION\n00000: NOP; JUMP 00000\n.endif").unwrap();
+
+        assert!(Assembler::new().load_file(&directory.join("main.2ia")).unwrap().is_empty());
+        let source_lines = Assembler::new().define("SIMULATION", 1).load_file(&directory.join("main.2ia")).unwrap();
+        assert_eq!(vec!["00000: NOP; JUMP 00000"], source_lines.iter().map(|line| line.text.as_str()).collect::<Vec<&str>>());
+    }
+
+    #[test]
+    fn test_lines_without_address_that_are_no_structured_code() {
+        for line in ["00000 NOP; JUMP 00001", "PASSB R0 0001; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"] {
+            assert!(Assembler::new().translate_program(line).err().unwrap().contains(MISSING_ADDRESS.code), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_rtl_comments_keep_source_comment() {
+        let line = "00100: PASSA R0 R2; WRITE B; BUS OFF; FLAGS KEEP; JUMP 00101 # copy";
+        let translated = Assembler::new().rtl_comments(true).translate_line(line).unwrap();
+        assert!(format!("{}", translated).ends_with("# R2 = R0 # copy"));
+
+        let translated = Assembler::new().translate_line(line).unwrap();
+        assert!(format!("{}", translated).ends_with("0 # copy"));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::diagnostics::UNKNOWN_ROUTINE;
+use crate::suggestion::{closest, did_you_mean};
+
+const USE_DIRECTIVE: &str = "use";
+const LIBRARY_PREFIX: &str = "std::";
+
+/// The version of the bundled routines, changes whenever a routine changes its registers or behaviour.
+pub const LIBRARY_VERSION: &str = "1.0";
+
+/// A routine of the standard library for an operation the 2i ALU can not do in one cycle.
+/// It is structured code that `use std::NAME` copies into the program where it is used,
+/// its header documents the registers and flags it returns, the registers it clobbers and its cycle count.
+pub struct LibraryRoutine {
+    pub name: &'static str,
+    pub source: &'static str
+}
+
+const ROUTINES: [LibraryRoutine; 7] = [
+    LibraryRoutine { name: "and", source: include_str!("../std/and.2ia") },
+    LibraryRoutine { name: "cmp", source: include_str!("../std/cmp.2ia") },
+    LibraryRoutine { name: "mul", source: include_str!("../std/mul.2ia") },
+    LibraryRoutine { name: "or", source: include_str!("../std/or.2ia") },
+    LibraryRoutine { name: "sub", source: include_str!("../std/sub.2ia") },
+    LibraryRoutine { name: "test", source: include_str!("../std/test.2ia") },
+    LibraryRoutine { name: "xor", source: include_str!("../std/xor.2ia") }
+];
+
+/// Every routine of the library, sorted by name.
+pub fn routines() -> &'static [LibraryRoutine] {
+    return &ROUTINES;
+}
+
+pub fn find_routine(name: &str) -> Option<&'static LibraryRoutine> {
+    return ROUTINES.iter().find(|routine| routine.name == name);
+}
+
+/// Whether the lines of a source file are those of a library routine, they have no file on disk.
+pub fn is_library_source(file: &str) -> bool {
+    return file.starts_with(LIBRARY_PREFIX);
+}
+
+/// Returns the routine name of a `use std::NAME` line, or `None` if the line is no use directive.
+pub fn parse_use_directive(line: &str) -> Option<Result<&'static LibraryRoutine, String>> {
+    let line_without_comment = line.split('#').next().unwrap_or("").trim();
+    let mut words = line_without_comment.split_whitespace();
+
+    if words.next() != Some(USE_DIRECTIVE) {
+        return None;
+    }
+
+    let path = match (words.next(), words.next()) {
+        (Some(path), None) => path,
+        _ => return Some(Err(UNKNOWN_ROUTINE.message("Expected one Routine after use, like use std::xor")))
+    };
+
+    if !path.starts_with(LIBRARY_PREFIX) {
+        return Some(Err(UNKNOWN_ROUTINE.message(format!("Unknown Library {}, only std can be used", path))));
+    }
+
+    return Some(find_routine(&path[LIBRARY_PREFIX.len()..])
+        .ok_or_else(|| UNKNOWN_ROUTINE.message(format!("Unknown Library Routine {} in the Standard Library {}{}",
+            path, LIBRARY_VERSION, did_you_mean(closest(&path[LIBRARY_PREFIX.len()..], ROUTINES.iter().map(|routine| routine.name)))))));
+}
+
+#[cfg(test)]
+mod library_tests {
+    use super::*;
+    use crate::Assembler;
+    use crate::emulator::{Emulator, MachineState};
+    use crate::translated::TranslatedProgram;
+
+    const INITIAL_REGISTERS: [u8; 8] = [0, 0, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7];
+
+    struct Expected {
+        registers: Vec<(usize, u8)>,
+        carry_flag: Option<bool>,
+        zero_flag: Option<bool>
+    }
+
+    fn assemble(routine: &LibraryRoutine) -> TranslatedProgram {
+        return Assembler::new()
+            .translate_program(&format!("use std::{}\nloop {{\n}}", routine.name))
+            .unwrap();
+    }
+
+    /// Runs the program until it reaches an instruction that jumps to itself.
+    fn run(program: &TranslatedProgram, x: u8, y: u8) -> MachineState {
+        let mut emulator = Emulator::new(program);
+        emulator.state.registers = INITIAL_REGISTERS;
+        emulator.state.registers[0] = x;
+        emulator.state.registers[1] = y;
+        emulator.run(1000).unwrap();
+        assert!(emulator.is_halted(), "Routine does not terminate");
+        return emulator.state;
+    }
+
+    fn header<'a>(routine: &'a LibraryRoutine, field: &str) -> &'a str {
+        let prefix = format!("# {}:", field);
+        return routine.source.lines()
+            .find(|line| line.starts_with(&prefix))
+            .map(|line| line[prefix.len()..].trim())
+            .unwrap_or_else(|| panic!("std::{} does not document {}", routine.name, field));
+    }
+
+    fn header_registers(routine: &LibraryRoutine, field: &str) -> Vec<usize> {
+        return header(routine, field).split(',')
+            .map(|register| register.trim())
+            .filter(|register| register.starts_with('R'))
+            .map(|register| register[1..].parse().unwrap())
+            .collect();
+    }
+
+    fn header_cycles(routine: &LibraryRoutine) -> (u64, u64) {
+        let cycles = header(routine, "Cycles").split_whitespace().next().unwrap();
+        let mut bounds = cycles.split("..").map(|bound| bound.parse::<u64>().unwrap());
+        let minimum = bounds.next().unwrap();
+        return (minimum, bounds.next().unwrap_or(minimum));
+    }
+
+    /// Runs the routine for all 8 bit values of R0 and R1 and compares it to the reference.
+    /// Registers that are not returned or clobbered have to keep their value, the cycle count has to match the header.
+    fn check_routine(name: &str, reference: fn(u8, u8) -> Expected) {
+        let routine = find_routine(name).unwrap();
+        let program = assemble(routine);
+        let mut changeable = header_registers(routine, "Returns");
+        changeable.extend(header_registers(routine, "Clobbers"));
+        let mut cycles = (u64::MAX, 0);
+
+        for x in 0..=255u8 {
+            for y in 0..=255u8 {
+                let machine = run(&program, x, y);
+                let expected = reference(x, y);
+
+                for (register, value) in &expected.registers {
+                    assert_eq!(*value, machine.registers[*register], "std::{} R{} for {:02X}, {:02X}", name, register, x, y);
+                }
+                if let Some(carry_flag) = expected.carry_flag {
+                    assert_eq!(carry_flag, machine.carry_flag, "std::{} Carry Flag for {:02X}, {:02X}", name, x, y);
+                }
+                if let Some(zero_flag) = expected.zero_flag {
+                    assert_eq!(zero_flag, machine.zero_flag, "std::{} Zero Flag for {:02X}, {:02X}", name, x, y);
+                }
+                for register in (0..8).filter(|register| !changeable.contains(register)) {
+                    let initial = [x, y][..].get(register).copied().unwrap_or(INITIAL_REGISTERS[register]);
+                    assert_eq!(initial, machine.registers[register], "std::{} clobbers R{}", name, register);
+                }
+                cycles = (cycles.0.min(machine.cycles), cycles.1.max(machine.cycles));
+            }
+        }
+
+        assert_eq!(header_cycles(routine), cycles, "std::{} Cycles", name);
+    }
+
+    fn registers(registers: &[(usize, u8)]) -> Expected {
+        return Expected { registers: registers.to_vec(), carry_flag: None, zero_flag: None };
+    }
+
+    #[test]
+    fn test_xor() {
+        check_routine("xor", |x, y| registers(&[(0, x ^ y)]));
+    }
+
+    #[test]
+    fn test_and() {
+        check_routine("and", |x, y| registers(&[(0, x & y)]));
+    }
+
+    #[test]
+    fn test_or() {
+        check_routine("or", |x, y| registers(&[(0, x | y)]));
+    }
+
+    #[test]
+    fn test_sub() {
+        check_routine("sub", |x, y| Expected { carry_flag: Some(x < y), ..registers(&[(0, x.wrapping_sub(y))]) });
+    }
+
+    #[test]
+    fn test_cmp() {
+        check_routine("cmp", |x, y| Expected { carry_flag: Some(x < y), zero_flag: Some(x == y), ..registers(&[]) });
+    }
+
+    #[test]
+    fn test_test() {
+        check_routine("test", |x, y| Expected { zero_flag: Some(x & y == 0), ..registers(&[]) });
+    }
+
+    #[test]
+    fn test_mul() {
+        check_routine("mul", |x, y| {
+            let product = x as u16 * y as u16;
+            return registers(&[(2, product as u8), (3, (product >> 8) as u8)]);
+        });
+    }
+
+    #[test]
+    fn test_use_directive() {
+        assert!(parse_use_directive("R0 = R1 # use std::xor").is_none());
+        assert_eq!("xor", parse_use_directive("  use std::xor # R0 = R0 XOR R1").unwrap().unwrap().name);
+        assert!(parse_use_directive("use std::nand").unwrap().err().unwrap().contains("std::nand in the Standard Library 1.0, did you mean and?"));
+        assert!(parse_use_directive("use core::xor").unwrap().is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::ops::RangeInclusive;
+use crate::diagnostics::{DATA_ON_IO_PORT, DATA_OUT_OF_RANGE, DATA_OVERLAP, INVALID_DATA_DIRECTIVE, INVALID_DATA_SYMBOL};
+use crate::expression::{evaluate, is_identifier, SymbolTable};
+use crate::rtl::is_reserved_operand;
+
+const DATA_DIRECTIVE: &str = ".data";
+const BYTES_PER_ROW: usize = 16;
+
+/// The bus addresses of the memory-mapped input and output ports, data can not be placed there.
+pub const IO_PORTS: RangeInclusive<u8> = 0xFC..=0xFF;
+
+/// The bytes a program places in the external RAM on the bus, addressed through register A.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryImage {
+    bytes: Vec<Option<u8>>
+}
+
+impl MemoryImage {
+    pub fn new() -> MemoryImage {
+        return MemoryImage {
+            bytes: vec![None; 256]
+        };
+    }
+
+    pub fn get(&self, address: u8) -> Option<u8> {
+        return self.bytes[address as usize];
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.bytes.iter().all(|byte| byte.is_none());
+    }
+
+    /// Places a byte, it is an error to place it on an I/O port or on a byte that is already defined.
+    pub fn define(&mut self, address: u8, value: u8) -> Result<(), String> {
+        if IO_PORTS.contains(&address) {
+            return Err(DATA_ON_IO_PORT.message(format!("Data at {:02X} collides with the I/O Port {:02X}", address, address)));
+        }
+        if let Some(previous) = self.bytes[address as usize] {
+            return Err(DATA_OVERLAP.message(format!("Data at {:02X} is already defined as {:02X}", address, previous)));
+        }
+
+        self.bytes[address as usize] = Some(value);
+        return Ok(());
+    }
+}
+
+impl Default for MemoryImage {
+    fn default() -> Self {
+        return MemoryImage::new();
+    }
+}
+
+/// Writes 16 bytes per row prefixed with the address of the first one, undefined bytes are 00 and I/O ports are --.
+impl fmt::Display for MemoryImage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (row, bytes) in self.bytes.chunks(BYTES_PER_ROW).enumerate() {
+            let row_address = row * BYTES_PER_ROW;
+            let values: Vec<String> = bytes.iter().enumerate()
+                .map(|(i, byte)| match IO_PORTS.contains(&((row_address + i) as u8)) {
+                    true => "--".to_string(),
+                    false => format!("{:02X}", byte.unwrap_or(0))
+                })
+                .collect();
+            writeln!(f, "{:02X}: {}", row_address, values.join(" "))?;
+        }
+        return Ok(());
+    }
+}
+
+/// A `.data [NAME] ADDRESS: VALUE, ...` line, the values are placed at ADDRESS and the following bus addresses.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataDefinition {
+    pub name: Option<String>,
+    pub address: u8,
+    pub values: Vec<u8>
+}
+
+impl DataDefinition {
+    pub fn place(&self, memory: &mut MemoryImage) -> Result<(), String> {
+        if self.address as usize + self.values.len() > 256 {
+            return Err(DATA_OUT_OF_RANGE.message(format!("{} Bytes at {:02X} do not fit below Bus Address FF", self.values.len(), self.address)));
+        }
+
+        for (i, value) in self.values.iter().enumerate() {
+            memory.define(self.address + i as u8, *value)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Parses a `.data` line, the address and values are expressions of the given symbols.
+/// Returns `None` if the line is no data directive.
+pub fn parse_data_directive(line: &str, symbols: &SymbolTable) -> Option<Result<DataDefinition, String>> {
+    let line_without_comment = line.split('#').next().unwrap_or("").trim();
+    let argument = line_without_comment.strip_prefix(DATA_DIRECTIVE)?;
+
+    if !argument.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    return Some(parse_data_definition(argument.trim(), symbols));
+}
+
+fn parse_data_definition(argument: &str, symbols: &SymbolTable) -> Result<DataDefinition, String> {
+    let (place, values) = argument.split_once(':')
+        .ok_or_else(|| INVALID_DATA_DIRECTIVE.message("Expected : between the Data Address and the Values"))?;
+
+    // A name is an identifier in front of the address, `BASE + 1` is an address without a name.
+    let (name, address) = match place.trim().split_once(char::is_whitespace) {
+        Some((name, address)) if is_identifier(name) && !address.trim().starts_with(is_operator_symbol) => (Some(name), address),
+        _ => (None, place)
+    };
+
+    if let Some(name) = name {
+        if is_reserved_operand(name) {
+            return Err(INVALID_DATA_SYMBOL.message(format!("{} can not be a Data Symbol, it is a Register, Constant or Operator", name)));
+        }
+    }
+
+    let address = evaluate(address, symbols)?;
+    if !(0..=0xFF).contains(&address) {
+        return Err(DATA_OUT_OF_RANGE.message(format!("The Data Address {} is no Bus Address 00 to FF", address)));
+    }
+
+    let values = values.split(',')
+        .map(|value| {
+            let value = evaluate(value, symbols)?;
+            return match (-128..=255).contains(&value) {
+                true => Ok(value as u8),
+                false => Err(DATA_OUT_OF_RANGE.message(format!("The Data Value {} does not fit into a Byte", value)))
+            };
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+
+    return Ok(DataDefinition {
+        name: name.map(|name| name.to_string()),
+        address: address as u8,
+        values
+    });
+}
+
+fn is_operator_symbol(ch: char) -> bool {
+    return "+-*/%<>=!&|^".contains(ch);
+}
+
+#[cfg(test)]
+mod memory_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_data_directive() {
+        let symbols = SymbolTable::new();
+        assert!(parse_data_directive("R0 = R1 # .data 0x10: 1", &symbols).is_none());
+        assert!(parse_data_directive(".database", &symbols).is_none());
+        assert_eq!(
+            DataDefinition { name: None, address: 0x10, values: vec![1, 2, 3, 0xFF] },
+            parse_data_directive(".data 0x10: 1, 2, 3, 0xFF # table", &symbols).unwrap().unwrap()
+        );
+        assert_eq!(
+            DataDefinition { name: Some("TABLE".to_string()), address: 0xF8, values: vec![0xFE, 0x10] },
+            parse_data_directive(".data TABLE 0xF8: -2, 1 << 4", &symbols).unwrap().unwrap()
+        );
+
+        let mut base = SymbolTable::new();
+        base.insert("BASE".to_string(), 0x10);
+        assert_eq!(0x11, parse_data_directive(".data BASE + 1: 7", &base).unwrap().unwrap().address);
+
+        assert!(parse_data_directive(".data 0x10 1, 2", &symbols).unwrap().is_err());
+        assert!(parse_data_directive(".data 0x10: 256", &symbols).unwrap().is_err());
+        assert!(parse_data_directive(".data R1 0x10: 1", &symbols).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_data_must_not_collide() {
+        let mut memory = MemoryImage::new();
+        DataDefinition { name: None, address: 0xF8, values: vec![1, 2, 3, 4] }.place(&mut memory).unwrap();
+
+        assert!(DataDefinition { name: None, address: 0xF9, values: vec![5] }.place(&mut memory).unwrap_err().contains("already defined"));
+        assert!(DataDefinition { name: None, address: 0xF0, values: vec![0; 13] }.place(&mut MemoryImage::new()).unwrap_err().contains("I/O Port FC"));
+        assert_eq!(Some(4), memory.get(0xFB));
+        assert!(memory.to_string().ends_with("F0: 00 00 00 00 00 00 00 00 01 02 03 04 -- -- -- --\n"));
+    }
+}
@@ -0,0 +1,58 @@
+/// What a program expects and does, from the `# INPUTS:`, `# OUTPUTS:` and `# BEHAVIOUR:` comments at its top.
+/// Every field is a list of the parts between `;`, like `A in FC; B in FD`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramMetadata {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub behaviour: Vec<String>
+}
+
+impl ProgramMetadata {
+    pub fn new() -> ProgramMetadata {
+        return ProgramMetadata::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.inputs.is_empty() && self.outputs.is_empty() && self.behaviour.is_empty();
+    }
+
+    /// Adds the content of a metadata comment (the text after `#`) to its field, a field can span several comments.
+    /// Returns whether the comment was a metadata comment.
+    pub fn read_comment(&mut self, comment: &str) -> bool {
+        let comment = comment.trim();
+        let (field, content) = match comment.split_once(':') {
+            Some((name, content)) => match name.trim().to_ascii_uppercase().as_str() {
+                "INPUTS" => (&mut self.inputs, content),
+                "OUTPUTS" => (&mut self.outputs, content),
+                "BEHAVIOUR" | "BEHAVIOR" => (&mut self.behaviour, content),
+                _ => return false
+            },
+            None => return false
+        };
+
+        field.extend(content.split(';')
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_string()));
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod metadata_tests {
+    use super::*;
+
+    #[test]
+    fn test_read_example_header() {
+        let mut metadata = ProgramMetadata::new();
+        assert!(metadata.read_comment(" INPUTS: A in FC; B in FD"));
+        assert!(metadata.read_comment(" OUTPUTS: C in FE"));
+        assert!(metadata.read_comment(" Behavior: Calculates C = A XOR B; Loops forever;"));
+        assert!(!metadata.read_comment(" R0 = FC"));
+        assert!(!metadata.read_comment(" Note: R0 is the Pointer"));
+
+        assert_eq!(vec!["A in FC", "B in FD"], metadata.inputs);
+        assert_eq!(vec!["C in FE"], metadata.outputs);
+        assert_eq!(vec!["Calculates C = A XOR B", "Loops forever"], metadata.behaviour);
+    }
+}
@@ -1,12 +1,16 @@
 use std::fmt;
 use std::fmt::Formatter;
-use crate::instruction::{Instruction, InstructionAddress, ParseError, RegisterAddress, ContentB, ALUFunction, ALUControl, RegisterControl, BusControl, AddressControl, RegisterAddressControl};
-use std::convert::{TryFrom, Infallible};
+use crate::instruction::{Instruction, InstructionAddress, RegisterAddress, ContentB, ALUFunction, ALUControl, RegisterControl, BusControl, AddressControl, RegisterAddressControl};
+use std::convert::TryFrom;
 use crate::parsing::WriteCommand::{WriteA, WriteB, WriteOff};
 use crate::parsing::BusCommand::{BusRead, BusWrite, BusOff};
 use crate::parsing::FlagCommand::{UpdateFlags, KeepFlags};
-use std::slice::SplitN;
 use crate::parsing::RegisterOrConstant::{Register, Constant};
+use crate::pseudo::parse_pseudo_instruction;
+use crate::dialect::{Dialect, ALUMnemonic, find_mnemonic};
+use crate::diagnostics::{INVALID_ADDRESS, INVALID_JUMP, MISSING_ADDRESS, UNKNOWN_ALU_COMMAND, UNKNOWN_BUS_COMMAND, UNKNOWN_FLAG_COMMAND, UNKNOWN_WRITE_COMMAND, WRONG_COMMAND_COUNT, WRONG_OPERANDS};
+use crate::rtl::{is_rtl_transfer, parse_rtl_transfer, is_rtl_jump, parse_rtl_jump};
+use crate::suggestion::{closest, did_you_mean, joined_alu_mnemonic, suggest_alu_mnemonic};
 
 pub struct CodeLine {
     pub instruction: Instruction,
@@ -22,112 +26,145 @@ impl fmt::Display for CodeLine {
     }
 }
 
-pub fn parse_line(line: &str) -> Result<CodeLine,String> {
+pub fn parse_line(line: &str, dialect: Dialect) -> Result<CodeLine,String> {
     let split_comment: Vec<&str> = line.splitn(2,"#").collect();
 
-    let comment: Option<String>;
-    match split_comment.len() {
-        0 => return Err("Nothing after Split".to_string()),
-        1 => comment = None,
-        2 => comment = Some(split_comment[1].to_string()),
-        _ => return Err("Too much after Split".to_string())
-    }
+    let comment: Option<String> = split_comment.get(1).map(|comment| comment.to_string());
 
     let line_without_comment = split_comment[0].to_string();
 
     let split_instruction_line: Vec<&str> = line_without_comment.splitn(2,":").collect();
 
-    match split_instruction_line.len() {
-        0 => return Err("Nothing after Split".to_string()),
-        1 => return Err("No Instruction Address".to_string()),
-        2 => {},
-        _ => return Err("Too much after Split".to_string())
+    if split_instruction_line.len() < 2 {
+        return Err(MISSING_ADDRESS.message("No Instruction Address in front of a colon"));
     }
 
     let instruction_address_string = split_instruction_line[0];
 
     let instruction_address = match InstructionAddress::try_from(instruction_address_string.to_string()) {
         Ok(address) => address,
-        Err(err) => return Err(format!("Error Parsing Instruction Address: {}", err.description))
+        Err(err) => return Err(INVALID_ADDRESS.message(format!("Error Parsing Instruction Address: {}", err.description)))
     };
 
     let instruction_string = split_instruction_line[1];
 
-    let split_instructions: Vec<&str> = instruction_string.split(";").collect();
+    let (operation_string, jump_command_string) = match instruction_string.rfind(';') {
+        Some(index) => (&instruction_string[..index], &instruction_string[index + 1..]),
+        None => return Err(WRONG_COMMAND_COUNT.message("Too few Commands, the Jump Command is missing"))
+    };
 
-    match split_instructions.len() {
-        0..=4 => return Err("Too few Commands".to_string()),
-        5 => {},
-        _ => return Err("Too many Commands".to_string())
-    }
+    let operation = parse_operation_commands(operation_string, dialect)?;
+
+    let jump_command = match parse_jump_command(jump_command_string, dialect) {
+        Ok(cmd) => cmd,
+        Err(err) => return Err(format!("Error Parsing Jump Command: {}", err))
+    };
+
+    let instruction = operation.to_instruction(instruction_address, &jump_command);
 
-    let alu_command_string = split_instructions[0];
-    let write_command_string = split_instructions[1];
-    let bus_command_string = split_instructions[2];
-    let flag_command_string = split_instructions[3];
-    let jump_command_string = split_instructions[4];
+    return Ok(CodeLine { instruction , comment });
+}
 
-    let alu_command = match parse_alu_command(alu_command_string) {
+/// Parses everything in front of the jump command: the ALU, write, bus and flag commands,
+/// a pseudo instruction, or a register transfer with optional flag command.
+pub(crate) fn parse_operation_commands(operation_string: &str, dialect: Dialect) -> Result<Operation, String> {
+    let split_instructions: Vec<&str> = operation_string.split(';').collect();
+
+    return match split_instructions.len() {
+        1 if is_rtl_transfer(split_instructions[0]) => match parse_rtl_transfer(split_instructions[0], KeepFlags) {
+            Ok(operation) => Ok(operation),
+            Err(err) => Err(format!("Error Parsing Register Transfer: {}", err))
+        },
+        2 if is_rtl_transfer(split_instructions[0]) => {
+            let flag_command = match parse_flag_command(split_instructions[1], dialect) {
+                Ok(cmd) => cmd,
+                Err(err) => return Err(format!("Error Parsing Flag Command: {}", err))
+            };
+            match parse_rtl_transfer(split_instructions[0], flag_command) {
+                Ok(operation) => Ok(operation),
+                Err(err) => Err(format!("Error Parsing Register Transfer: {}", err))
+            }
+        },
+        1 => match parse_pseudo_instruction(split_instructions[0]) {
+            Ok(operation) => Ok(operation),
+            Err(err) => Err(format!("Error Parsing Pseudo Instruction: {}", err))
+        },
+        2 | 3 => Err(WRONG_COMMAND_COUNT.message(format!("Too few Commands, got {} of 4 in front of the Jump", split_instructions.len()))),
+        4 => parse_operation(&split_instructions, dialect),
+        _ => Err(WRONG_COMMAND_COUNT.message(format!("Too many Commands, got {} of 4 in front of the Jump", split_instructions.len())))
+    };
+}
+
+fn parse_operation(command_strings: &[&str], dialect: Dialect) -> Result<Operation, String> {
+    let alu_command = match parse_alu_command(command_strings[0], dialect) {
         Ok(cmd) => cmd,
         Err(err) => return Err(format!("Error Parsing ALU Command: {}",err))
     };
 
-
-    let write_command = match parse_write_command(write_command_string) {
+    let write_command = match parse_write_command(command_strings[1], dialect) {
         Ok(cmd) => cmd,
         Err(err) => return Err(format!("Error Parsing Write Command: {}", err))
     };
 
-    let bus_command = match parse_bus_command(bus_command_string) {
+    let bus_command = match parse_bus_command(command_strings[2], dialect) {
         Ok(cmd) => cmd,
         Err(err) => return Err(format!("Error Parsing Bus Command: {}", err))
     };
 
-    let flag_command = match parse_flag_command(flag_command_string) {
+    let flag_command = match parse_flag_command(command_strings[3], dialect) {
         Ok(cmd) => cmd,
         Err(err) => return Err(format!("Error Parsing Flag Command: {}", err))
     };
 
-    let jump_command = match parse_jump_command(jump_command_string) {
-        Ok(cmd) => cmd,
-        Err(err) => return Err(format!("Error Parsing Jump Command: {}", err))
-    };
+    return Ok(Operation { alu_command, write_command, bus_command, flag_command });
+}
 
-    let alu_control = flag_command.to_alu_control();
-    let alu_function = alu_command.to_alu_function();
-    let register_control = write_command.to_register_control();
-    let bus_control = bus_command.to_bus_control();
-    let address_control = jump_command.jump_type.to_address_control();
-    let register_contents = alu_command.to_content();
-    let mut register_address_control = alu_command.to_register_address_control();
-    register_address_control.aca = bus_control.bus_en && !bus_control.bus_wr;
-
-    let instruction = Instruction {
-        address: instruction_address,
-        address_control,
-        next_address: jump_command.next_address,
-        bus_control,
-        content_a: register_contents.content_a,
-        content_b: register_contents.content_b,
-        register_control,
-        register_address_control,
-        alu_function,
-        alu_control
-    };
+/// Everything an instruction does apart from choosing the next address.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Operation {
+    pub alu_command: ALUCommand,
+    pub write_command: WriteCommand,
+    pub bus_command: BusCommand,
+    pub flag_command: FlagCommand
+}
 
-    return Ok(CodeLine { instruction , comment });
+impl Operation {
+    pub fn to_instruction(self, address: InstructionAddress, jump_command: &JumpCommand) -> Instruction {
+        let alu_control = self.flag_command.to_alu_control();
+        let alu_function = self.alu_command.to_alu_function();
+        let register_control = self.write_command.to_register_control();
+        let bus_control = self.bus_command.to_bus_control();
+        let address_control = jump_command.jump_type.to_address_control();
+        let register_contents = self.alu_command.to_content();
+        let mut register_address_control = self.alu_command.to_register_address_control();
+        register_address_control.aca = bus_control.bus_en && !bus_control.bus_wr;
+
+        return Instruction {
+            address,
+            address_control,
+            next_address: jump_command.next_address,
+            bus_control,
+            content_a: register_contents.content_a,
+            content_b: register_contents.content_b,
+            register_control,
+            register_address_control,
+            alu_function,
+            alu_control
+        };
+    }
 }
 
-enum RegisterOrConstant {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum RegisterOrConstant {
     Register(RegisterAddress),
     Constant(ContentB)
 }
 
 impl RegisterOrConstant {
-    fn to_content_b(&self) -> ContentB{
+    fn to_content_b(self) -> ContentB{
         return match self {
             RegisterOrConstant::Register(r) => r.to_content_b(),
-            RegisterOrConstant::Constant(c) => *c,
+            RegisterOrConstant::Constant(c) => c,
         }
     }
 }
@@ -138,7 +175,7 @@ impl TryFrom<&str> for RegisterOrConstant {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let value_chars: Vec<char> = value.chars().collect();
         return match value_chars.len() {
-            0 => return Err("No Content".to_string()),
+            0 => return Err(WRONG_OPERANDS.message("No Register Content")),
             2 => match RegisterAddress::try_from(value) {
                 Ok(address) => Ok(Register(address)),
                 Err(err) => Err(err),
@@ -147,12 +184,13 @@ impl TryFrom<&str> for RegisterOrConstant {
                 Ok(content) => Ok(Constant(content)),
                 Err(err) => Err(err),
             },
-            _ => return Err("Invalid Content".to_string())
+            _ => return Err(WRONG_OPERANDS.message(format!("{} is neither a Register nor a Constant", value)))
         }
     }
 }
 
-enum ALUCommand {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ALUCommand {
     Zero(RegisterAddress, RegisterOrConstant),
     PassA(RegisterAddress, RegisterOrConstant),
     PassB(RegisterAddress, RegisterOrConstant),
@@ -182,7 +220,7 @@ struct ContentAB {
 }
 
 impl ALUCommand {
-    fn to_alu_function(&self) -> ALUFunction {
+    pub fn to_alu_function(self) -> ALUFunction {
         return match self {
             ALUCommand::Zero(_, _) => {ALUFunction{
                 alu3: false,
@@ -313,7 +351,7 @@ impl ALUCommand {
         }
     }
 
-    fn to_content(&self) -> ContentAB {
+    fn to_content(self) -> ContentAB {
         return match self {
             ALUCommand::Zero(a, b) |
             ALUCommand::PassA(a, b) |
@@ -331,7 +369,7 @@ impl ALUCommand {
             ALUCommand::RotateRight(a, b) |
             ALUCommand::ArithShiftRight(a,b) |
             ALUCommand::RotateRightCarry(a, b) => ContentAB {
-                content_a: *a,
+                content_a: a,
                 content_b: b.to_content_b()
             },
             ALUCommand::LogicShiftLeftHoldC(r) |
@@ -339,12 +377,39 @@ impl ALUCommand {
             ALUCommand::ShiftLeftAppend1(r) |
             ALUCommand::Complement(r) |
             ALUCommand::RotateLeftCarry(r) => ContentAB{
-                content_a: *r,
+                content_a: r,
                 content_b: r.to_content_b() },
         }
     }
 
-    fn to_register_address_control(&self) -> RegisterAddressControl {
+    /// The register A operand and, for commands that take two, the B operand.
+    pub fn operands(self) -> (RegisterAddress, Option<RegisterOrConstant>) {
+        return match self {
+            ALUCommand::Zero(a, b) |
+            ALUCommand::PassA(a, b) |
+            ALUCommand::PassB(a, b) |
+            ALUCommand::PassBSetC(a, b) |
+            ALUCommand::PassBHoldC(a, b) |
+            ALUCommand::PassBInvertC(a, b) |
+            ALUCommand::Nor(a, b) |
+            ALUCommand::AddHoldC(a, b) |
+            ALUCommand::Add(a, b) |
+            ALUCommand::AddSub(a, b) |
+            ALUCommand::AddC(a, b) |
+            ALUCommand::AddSubC(a, b) |
+            ALUCommand::LogicShiftRight(a, b) |
+            ALUCommand::RotateRight(a, b) |
+            ALUCommand::ArithShiftRight(a, b) |
+            ALUCommand::RotateRightCarry(a, b) => (a, Some(b)),
+            ALUCommand::LogicShiftLeftHoldC(r) |
+            ALUCommand::LogicShiftLeft(r) |
+            ALUCommand::ShiftLeftAppend1(r) |
+            ALUCommand::Complement(r) |
+            ALUCommand::RotateLeftCarry(r) => (r, None)
+        }
+    }
+
+    fn to_register_address_control(self) -> RegisterAddressControl {
         return match self {
             ALUCommand::Zero(_, b) |
             ALUCommand::PassA(_, b) |
@@ -371,11 +436,11 @@ impl ALUCommand {
                     acb: true
                 },
             },
-            ALUCommand::LogicShiftLeftHoldC(r) |
-            ALUCommand::LogicShiftLeft(r) |
-            ALUCommand::ShiftLeftAppend1(r) |
-            ALUCommand::Complement(r) |
-            ALUCommand::RotateLeftCarry(r) => RegisterAddressControl {
+            ALUCommand::LogicShiftLeftHoldC(_) |
+            ALUCommand::LogicShiftLeft(_) |
+            ALUCommand::ShiftLeftAppend1(_) |
+            ALUCommand::Complement(_) |
+            ALUCommand::RotateLeftCarry(_) => RegisterAddressControl {
                 aca: false,
                 acb: false
             }
@@ -383,74 +448,50 @@ impl ALUCommand {
     }
 }
 
-fn parse_alu_command(command_string: &str) -> Result<ALUCommand, String>{
-    let command_string = command_string.trim();
-    let split_command_string: Vec<&str> = command_string.split(" ").collect();
-
-    return match split_command_string.len() {
-        0 => return Err("Nothing after Split".to_string()),
-        1 => return Err("No Register Contents".to_string()),
-        2 => parse_single_alu_command(split_command_string[0], split_command_string[1]),
-        3 => parse_double_alu_command(split_command_string[0], split_command_string[1], split_command_string[2]),
-        _ => return Err("Too much after Split".to_string()),
+fn parse_alu_command(command_string: &str, dialect: Dialect) -> Result<ALUCommand, String>{
+    let words: Vec<&str> = command_string.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(UNKNOWN_ALU_COMMAND.message("The ALU Command is empty"));
     }
-}
 
-fn parse_single_alu_command(command: &str, content: &str) -> Result<ALUCommand, String> {
-    let content = match RegisterAddress::try_from(content) {
-        Ok(content) => content,
-        Err(err) => return Err(err),
+    // Course mnemonics like `B SETC` contain spaces, so the longest mnemonic that leaves operands wins.
+    let mnemonic = dialect.alu_mnemonics().iter()
+        .map(|(name, mnemonic)| (name.split(' ').count(), name, mnemonic))
+        .filter(|(length, name, _)| words.len() > *length && name.split(' ').eq(words[..*length].iter().copied()))
+        .max_by_key(|(length, _, _)| *length)
+        .map(|(length, _, mnemonic)| (length, mnemonic));
+
+    let (length, mnemonic) = match mnemonic {
+        Some(found) => found,
+        None if words.len() == 1 && dialect.alu_mnemonics().iter().any(|(name, _)| *name == words[0]) =>
+            return Err(WRONG_OPERANDS.message(format!("No Register Contents after {}", words[0]))),
+        None => return Err(UNKNOWN_ALU_COMMAND.message(format!("{}{}", command_string.trim(), did_you_mean(suggest_alu_mnemonic(&words, dialect)))))
     };
 
-    return match command {
-        "LSLH" => Ok(ALUCommand::LogicShiftLeftHoldC(content)),
-        "LSL" => Ok(ALUCommand::LogicShiftLeft(content)),
-        "SL1" => Ok(ALUCommand::ShiftLeftAppend1(content)),
-        "COM" => Ok(ALUCommand::Complement(content)),
-        "RLC" => Ok(ALUCommand::RotateLeftCarry(content)),
-        _ => Err("Got only one Register Content, but no Command fits".to_string())
+    return match (mnemonic, &words[length..]) {
+        (ALUMnemonic::Single(command), [content]) => Ok(command(RegisterAddress::try_from(*content)?)),
+        (ALUMnemonic::Double(command), [content_a, content_b]) =>
+            Ok(command(RegisterAddress::try_from(*content_a)?, RegisterOrConstant::try_from(*content_b)?)),
+        (ALUMnemonic::Single(_), [_, _]) => Err(WRONG_OPERANDS.message("Got two Register Contents, but no Command fits")),
+        (ALUMnemonic::Double(_), [_]) => Err(WRONG_OPERANDS.message("Got only one Register Content, but no Command fits")),
+        _ => Err(WRONG_OPERANDS.message(format!("Got {} Register Contents, but no Command fits{}",
+            words.len() - length, did_you_mean(joined_alu_mnemonic(&words, dialect)))))
     }
 }
 
-fn parse_double_alu_command(command: &str, content_a: &str, content_b: &str) -> Result<ALUCommand, String> {
-    let content_a = match RegisterAddress::try_from(content_a) {
-        Ok(content) => content,
-        Err(err) => return Err(err),
-    };
-    let content_b = match RegisterOrConstant::try_from(content_b) {
-        Ok(content) => content,
-        Err(err) => return Err(err),
-    };
-
-    return match command {
-        "ZERO" => Ok(ALUCommand::Zero(content_a,content_b)),
-        "PASSA" => Ok(ALUCommand::PassA(content_a,content_b)),
-        "PASSB" => Ok(ALUCommand::PassB(content_a,content_b)),
-        "BSETC" => Ok(ALUCommand::PassBSetC(content_a,content_b)),
-        "BHOLDC" => Ok(ALUCommand::PassBHoldC(content_a,content_b)),
-        "BINVC" => Ok(ALUCommand::PassBInvertC(content_a,content_b)),
-        "NOR" => Ok(ALUCommand::Nor(content_a,content_b)),
-        "ADDH" => Ok(ALUCommand::AddHoldC(content_a,content_b)),
-        "ADD" => Ok(ALUCommand::Add(content_a,content_b)),
-        "ADDC" => Ok(ALUCommand::AddC(content_a,content_b)),
-        "ADDS" => Ok(ALUCommand::AddSub(content_a,content_b)),
-        "ADDSC" => Ok(ALUCommand::AddSubC(content_a,content_b)),
-        "ASR" => Ok(ALUCommand::ArithShiftRight(content_a,content_b)),
-        "LSR" => Ok(ALUCommand::LogicShiftRight(content_a,content_b)),
-        "RR" => Ok(ALUCommand::RotateRight(content_a,content_b)),
-        "RRC" => Ok(ALUCommand::RotateRightCarry(content_a,content_b)),
-        _ => Err("Got two Register Contents, but no Command fits".to_string())
-    }
+fn suggest_mnemonic<T>(command_string: &str, mnemonics: &'static [(&'static str, T)]) -> String {
+    return did_you_mean(closest(command_string, mnemonics.iter().map(|(name, _)| *name)));
 }
 
-enum WriteCommand {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum WriteCommand {
     WriteA,
     WriteB,
     WriteOff
 }
 
 impl WriteCommand {
-    fn to_register_control(&self) -> RegisterControl {
+    fn to_register_control(self) -> RegisterControl {
         return match self {
             WriteA => RegisterControl{ rws: false, rwe: true },
             WriteB => RegisterControl{ rws: true, rwe: true },
@@ -459,23 +500,22 @@ impl WriteCommand {
     }
 }
 
-fn parse_write_command(command_string: &str) -> Result<WriteCommand, String> {
-    return match command_string.trim() {
-        "WRITE A" => Ok(WriteA),
-        "WRITE B" => Ok(WriteB),
-        "WRITE OFF" => Ok(WriteOff),
-        _ => Err("Unknown Write Command".to_string())
-    }
+fn parse_write_command(command_string: &str, dialect: Dialect) -> Result<WriteCommand, String> {
+    let words: Vec<&str> = command_string.split_whitespace().collect();
+    return find_mnemonic(dialect.write_mnemonics(), &words)
+        .ok_or_else(|| UNKNOWN_WRITE_COMMAND.message(format!("{}{}", command_string.trim(), suggest_mnemonic(command_string, dialect.write_mnemonics()))));
 }
 
-enum BusCommand {
+#[allow(clippy::enum_variant_names)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum BusCommand {
     BusRead,
     BusWrite,
     BusOff
 }
 
 impl BusCommand {
-    fn to_bus_control(&self) -> BusControl {
+    fn to_bus_control(self) -> BusControl {
         return match self {
             BusRead => BusControl{ bus_wr: false, bus_en: true },
             BusWrite => BusControl{ bus_wr: true, bus_en: true },
@@ -484,22 +524,20 @@ impl BusCommand {
     }
 }
 
-fn parse_bus_command(command_string: &str) -> Result<BusCommand, String> {
-    return match command_string.trim() {
-        "BUS READ" => Ok(BusRead),
-        "BUS WRITE" => Ok(BusWrite),
-        "BUS OFF" => Ok(BusOff),
-        _ => Err("Unknown Bus Command".to_string())
-    }
+fn parse_bus_command(command_string: &str, dialect: Dialect) -> Result<BusCommand, String> {
+    let words: Vec<&str> = command_string.split_whitespace().collect();
+    return find_mnemonic(dialect.bus_mnemonics(), &words)
+        .ok_or_else(|| UNKNOWN_BUS_COMMAND.message(format!("{}{}", command_string.trim(), suggest_mnemonic(command_string, dialect.bus_mnemonics()))));
 }
 
-enum FlagCommand {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum FlagCommand {
     UpdateFlags,
     KeepFlags,
 }
 
 impl FlagCommand {
-    fn to_alu_control(&self) -> ALUControl {
+    fn to_alu_control(self) -> ALUControl {
         return match self {
             UpdateFlags => ALUControl{ cf: true },
             KeepFlags => ALUControl{ cf: false },
@@ -507,15 +545,14 @@ impl FlagCommand {
     }
 }
 
-fn parse_flag_command(command_string: &str) -> Result<FlagCommand, String> {
-    return match command_string.trim() {
-        "FLAGS COPY" => Ok(UpdateFlags),
-        "FLAGS KEEP" => Ok(KeepFlags),
-        _ => Err("Unknown Flag Command".to_string())
-    }
+fn parse_flag_command(command_string: &str, dialect: Dialect) -> Result<FlagCommand, String> {
+    let words: Vec<&str> = command_string.split_whitespace().collect();
+    return find_mnemonic(dialect.flag_mnemonics(), &words)
+        .ok_or_else(|| UNKNOWN_FLAG_COMMAND.message(format!("{}{}", command_string.trim(), suggest_mnemonic(command_string, dialect.flag_mnemonics()))));
 }
 
-enum JumpType {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum JumpType {
     Jump,
     TestIntAJump,
     TestIntBJump,
@@ -525,13 +562,35 @@ enum JumpType {
     TestNOJump
 }
 
-struct JumpCommand {
-    jump_type: JumpType,
-    next_address: InstructionAddress
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JumpCommand {
+    pub jump_type: JumpType,
+    pub next_address: InstructionAddress
+}
+
+impl JumpCommand {
+    /// Conditional jumps select their condition with bit 0 of the next address, so it is overwritten here.
+    pub fn new(jump_type: JumpType, mut next_address: InstructionAddress) -> JumpCommand {
+        match jump_type {
+            JumpType::TestIntAJump |
+            JumpType::TestCOJump |
+            JumpType::TestNOJump => {
+                next_address.bit0 = false;
+            },
+            JumpType::TestIntBJump |
+            JumpType::TestCFJump |
+            JumpType::TestZOJump => {
+                next_address.bit0 = true;
+            },
+            _ => {}
+        }
+
+        return JumpCommand { jump_type, next_address };
+    }
 }
 
 impl JumpType {
-    fn to_address_control(&self) -> AddressControl {
+    fn to_address_control(self) -> AddressControl {
         return match self {
             JumpType::Jump => AddressControl{ ac1: false, ac0: false },
             JumpType::TestIntAJump => AddressControl{ ac1: false, ac0: true },
@@ -544,48 +603,25 @@ impl JumpType {
     }
 }
 
-fn parse_jump_command(command_string: &str) -> Result<JumpCommand, String> {
-    let command_string = command_string.trim();
-    let split_command_string: Vec<&str> = command_string.split( " ").collect();
-    match split_command_string.len() {
-        0 => return Err("Nothing after Split".to_string()),
-        1 => return Err("No Jump Address".to_string()),
-        2 => {},
-        _ => return Err("Too much after Split".to_string())
+fn parse_jump_command(command_string: &str, dialect: Dialect) -> Result<JumpCommand, String> {
+    if is_rtl_jump(command_string) {
+        return parse_rtl_jump(command_string);
     }
 
-    let jump_command = split_command_string[0];
-    let jump_address = split_command_string[1];
-
-    let jump_type = match jump_command {
-        "JUMP" => JumpType::Jump,
-        "JUMPINTA" => JumpType::TestIntAJump,
-        "JUMPINTB" => JumpType::TestIntBJump,
-        "JUMPCF" => JumpType::TestCFJump,
-        "JUMPCO" => JumpType::TestCOJump,
-        "JUMPZO" => JumpType::TestZOJump,
-        "JUMPNO" => JumpType::TestNOJump,
-        _ => return Err("Unknown Jump Type".to_string())
+    let words: Vec<&str> = command_string.split_whitespace().collect();
+    let (jump_address, jump_command) = match words.split_last() {
+        None => return Err(INVALID_JUMP.message("The Jump Command is empty")),
+        Some((_, [])) => return Err(INVALID_JUMP.message(format!("Expected a Jump Type and a Next Address, got {}", words[0]))),
+        Some((jump_address, jump_command)) => (jump_address, jump_command)
     };
 
-    let mut next_address = match InstructionAddress::try_from(jump_address.to_string()) {
+    let jump_type = find_mnemonic(dialect.jump_mnemonics(), jump_command)
+        .ok_or_else(|| INVALID_JUMP.message(format!("Unknown Jump Type {}{}", jump_command.join(" "), suggest_mnemonic(&jump_command.join(" "), dialect.jump_mnemonics()))))?;
+
+    let next_address = match InstructionAddress::try_from(jump_address.to_string()) {
         Ok(addr) => addr,
-        Err(err) => return Err(format!("Could not parse Next Address: {}",err.description)),
+        Err(err) => return Err(INVALID_ADDRESS.message(format!("Could not parse Next Address: {}", err.description))),
     };
 
-    match jump_type {
-        JumpType::TestIntAJump |
-        JumpType::TestCOJump |
-        JumpType::TestNOJump => {
-            next_address.bit0 = false;
-        },
-        JumpType::TestIntBJump |
-        JumpType::TestCFJump |
-        JumpType::TestZOJump => {
-            next_address.bit0 = true;
-        },
-        _ => {}
-    }
-
-    return Ok(JumpCommand{ jump_type, next_address })
+    return Ok(JumpCommand::new(jump_type, next_address))
 }
\ No newline at end of file
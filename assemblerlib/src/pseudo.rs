@@ -0,0 +1,218 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Formatter;
+use crate::diagnostics::{UNKNOWN_PSEUDO_INSTRUCTION, WRONG_OPERANDS};
+use crate::instruction::{RegisterAddress, ContentB, InstructionAddress};
+use crate::parsing::{Operation, ALUCommand, WriteCommand, BusCommand, FlagCommand, JumpCommand, JumpType};
+use crate::parsing::RegisterOrConstant::{Register, Constant};
+use crate::suggestion::{closest, did_you_mean};
+
+/// Single cycle idioms that can be written instead of the ALU, write, bus and flag commands.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PseudoInstruction {
+    /// `MOV Rd, Rs` is `PASSA Rs Rd; WRITE B; BUS OFF; FLAGS KEEP`
+    Move(RegisterAddress, RegisterAddress),
+    /// `LDI Rd, const` is `PASSB Rd const; WRITE A; BUS OFF; FLAGS KEEP`
+    LoadImmediate(RegisterAddress, ContentB),
+    /// `CLR Rd` is `PASSB Rd 0000; WRITE A; BUS OFF; FLAGS KEEP`
+    Clear(RegisterAddress),
+    /// `IN Rd, [Ra]` is `PASSA Ra Rd; WRITE B; BUS READ; FLAGS KEEP`, or `PASSA Ra 0000; WRITE A; ...` if Rd is Ra
+    Input(RegisterAddress, RegisterAddress),
+    /// `OUT [Ra], Rs` is `PASSB Ra Rs; WRITE OFF; BUS WRITE; FLAGS KEEP`
+    Output(RegisterAddress, RegisterAddress),
+    /// `CLC` is `PASSB R0 0000; WRITE OFF; BUS OFF; FLAGS COPY`
+    ClearCarry,
+    /// `SETC` is `BSETC R0 0000; WRITE OFF; BUS OFF; FLAGS COPY`
+    SetCarry,
+    /// `NOP` is `ADDH R0 R0; WRITE OFF; BUS OFF; FLAGS KEEP`
+    NoOperation
+}
+
+/// The mnemonics of all pseudo instructions, for suggestions when a command is misspelled.
+pub(crate) const PSEUDO_MNEMONICS: [&str; 8] = ["MOV", "LDI", "CLR", "IN", "OUT", "CLC", "SETC", "NOP"];
+
+const R0: RegisterAddress = RegisterAddress { ad2: false, ad1: false, ad0: false };
+const ZERO: ContentB = ContentB { b3: false, b2: false, b1: false, b0: false };
+
+impl PseudoInstruction {
+    pub fn to_operation(self) -> Operation {
+        let (alu_command, write_command, bus_command, flag_command) = match self {
+            PseudoInstruction::Move(destination, source) =>
+                (ALUCommand::PassA(source, Register(destination)), WriteCommand::WriteB, BusCommand::BusOff, FlagCommand::KeepFlags),
+            PseudoInstruction::LoadImmediate(destination, constant) =>
+                (ALUCommand::PassB(destination, Constant(constant)), WriteCommand::WriteA, BusCommand::BusOff, FlagCommand::KeepFlags),
+            PseudoInstruction::Clear(destination) =>
+                (ALUCommand::PassB(destination, Constant(ZERO)), WriteCommand::WriteA, BusCommand::BusOff, FlagCommand::KeepFlags),
+            PseudoInstruction::Input(destination, address) if destination == address =>
+                (ALUCommand::PassA(address, Constant(ZERO)), WriteCommand::WriteA, BusCommand::BusRead, FlagCommand::KeepFlags),
+            PseudoInstruction::Input(destination, address) =>
+                (ALUCommand::PassA(address, Register(destination)), WriteCommand::WriteB, BusCommand::BusRead, FlagCommand::KeepFlags),
+            PseudoInstruction::Output(address, source) =>
+                (ALUCommand::PassB(address, Register(source)), WriteCommand::WriteOff, BusCommand::BusWrite, FlagCommand::KeepFlags),
+            PseudoInstruction::ClearCarry =>
+                (ALUCommand::PassB(R0, Constant(ZERO)), WriteCommand::WriteOff, BusCommand::BusOff, FlagCommand::UpdateFlags),
+            PseudoInstruction::SetCarry =>
+                (ALUCommand::PassBSetC(R0, Constant(ZERO)), WriteCommand::WriteOff, BusCommand::BusOff, FlagCommand::UpdateFlags),
+            PseudoInstruction::NoOperation =>
+                (ALUCommand::AddHoldC(R0, Register(R0)), WriteCommand::WriteOff, BusCommand::BusOff, FlagCommand::KeepFlags),
+        };
+
+        return Operation { alu_command, write_command, bus_command, flag_command };
+    }
+
+    /// Finds the pseudo instruction that assembles to exactly this operation, if there is one.
+    pub fn from_operation(operation: &Operation) -> Option<PseudoInstruction> {
+        let candidates = match operation.alu_command {
+            ALUCommand::PassA(a, Register(b)) => vec![
+                PseudoInstruction::Move(b, a),
+                PseudoInstruction::Input(b, a)
+            ],
+            ALUCommand::PassA(a, Constant(_)) => vec![PseudoInstruction::Input(a, a)],
+            ALUCommand::PassB(a, Constant(constant)) => vec![
+                PseudoInstruction::ClearCarry,
+                PseudoInstruction::Clear(a),
+                PseudoInstruction::LoadImmediate(a, constant)
+            ],
+            ALUCommand::PassB(a, Register(b)) => vec![PseudoInstruction::Output(a, b)],
+            ALUCommand::PassBSetC(_, _) => vec![PseudoInstruction::SetCarry],
+            // The decoder reads ADDH R0 R0 as LSLH R0, both are the same word.
+            ALUCommand::AddHoldC(_, _) | ALUCommand::LogicShiftLeftHoldC(_) => vec![PseudoInstruction::NoOperation],
+            _ => vec![]
+        };
+
+        let address = InstructionAddress::from_u8(0);
+        let jump_command = JumpCommand::new(JumpType::Jump, address);
+        return candidates.into_iter()
+            .find(|candidate| candidate.to_operation().to_instruction(address, &jump_command) == operation.to_instruction(address, &jump_command));
+    }
+}
+
+impl fmt::Display for PseudoInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        return match self {
+            PseudoInstruction::Move(destination, source) => write!(f, "MOV {}, {}", register_name(*destination), register_name(*source)),
+            PseudoInstruction::LoadImmediate(destination, constant) => write!(f, "LDI {}, {}", register_name(*destination), constant),
+            PseudoInstruction::Clear(destination) => write!(f, "CLR {}", register_name(*destination)),
+            PseudoInstruction::Input(destination, address) => write!(f, "IN {}, [{}]", register_name(*destination), register_name(*address)),
+            PseudoInstruction::Output(address, source) => write!(f, "OUT [{}], {}", register_name(*address), register_name(*source)),
+            PseudoInstruction::ClearCarry => write!(f, "CLC"),
+            PseudoInstruction::SetCarry => write!(f, "SETC"),
+            PseudoInstruction::NoOperation => write!(f, "NOP"),
+        };
+    }
+}
+
+pub(crate) fn register_name(register: RegisterAddress) -> String {
+    return format!("R{}", register.to_u8());
+}
+
+pub(crate) fn parse_pseudo_instruction(command_string: &str) -> Result<Operation, String> {
+    let command_string = command_string.trim();
+    let (mnemonic, operand_string) = match command_string.find(char::is_whitespace) {
+        Some(index) => (&command_string[..index], command_string[index..].trim()),
+        None => (command_string, "")
+    };
+
+    let operands: Vec<&str> = match operand_string.is_empty() {
+        true => Vec::new(),
+        false => operand_string.split(',').map(|operand| operand.trim()).collect()
+    };
+
+    let pseudo_instruction = match (mnemonic, operands.as_slice()) {
+        ("MOV", [destination, source]) =>
+            PseudoInstruction::Move(RegisterAddress::try_from(*destination)?, RegisterAddress::try_from(*source)?),
+        ("LDI", [destination, constant]) =>
+            PseudoInstruction::LoadImmediate(RegisterAddress::try_from(*destination)?, ContentB::try_from(*constant)?),
+        ("CLR", [destination]) =>
+            PseudoInstruction::Clear(RegisterAddress::try_from(*destination)?),
+        ("IN", [destination, address]) =>
+            PseudoInstruction::Input(RegisterAddress::try_from(*destination)?, parse_memory_operand(address)?),
+        ("OUT", [address, source]) =>
+            PseudoInstruction::Output(parse_memory_operand(address)?, RegisterAddress::try_from(*source)?),
+        ("CLC", []) => PseudoInstruction::ClearCarry,
+        ("SETC", []) => PseudoInstruction::SetCarry,
+        ("NOP", []) => PseudoInstruction::NoOperation,
+        ("MOV", _) | ("LDI", _) | ("CLR", _) | ("IN", _) | ("OUT", _) | ("CLC", _) | ("SETC", _) | ("NOP", _) =>
+            return Err(WRONG_OPERANDS.message(format!("Wrong Number of Operands for {}", mnemonic))),
+        _ => return Err(UNKNOWN_PSEUDO_INSTRUCTION.message(format!("{}{}", command_string, did_you_mean(closest(mnemonic, PSEUDO_MNEMONICS)))))
+    };
+
+    return Ok(pseudo_instruction.to_operation());
+}
+
+/// Parses a `[Rx]` operand, the bus address is the content of register Rx.
+fn parse_memory_operand(operand: &str) -> Result<RegisterAddress, String> {
+    if !operand.starts_with('[') || !operand.ends_with(']') {
+        return Err(WRONG_OPERANDS.message(format!("Bus Address {} must be a Register in Brackets", operand)));
+    }
+    return RegisterAddress::try_from(operand[1..operand.len() - 1].trim());
+}
+
+#[cfg(test)]
+mod pseudo_tests {
+    use super::*;
+    use crate::parsing::parse_line;
+    use crate::dialect::Dialect;
+    use crate::disassembly::decode_instruction;
+    use crate::instruction::Instruction;
+
+    fn assemble(line: &str) -> String {
+        return format!("{}", parse_line(line, Dialect::Standard).unwrap().instruction);
+    }
+
+    #[test]
+    fn test_pseudo_instructions_match_idioms() {
+        let idioms = [
+            ("MOV R2, R0", "PASSA R0 R2; WRITE B; BUS OFF; FLAGS KEEP"),
+            ("LDI R0, 1100", "PASSB R0 1100; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("CLR R3", "PASSB R3 0000; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("IN R0, [R0]", "PASSA R0 0000; WRITE A; BUS READ; FLAGS KEEP"),
+            ("IN R2, [R1]", "PASSA R1 R2; WRITE B; BUS READ; FLAGS KEEP"),
+            ("OUT [R1], R0", "PASSB R1 R0; WRITE OFF; BUS WRITE; FLAGS KEEP"),
+            ("CLC", "PASSB R0 0000; WRITE OFF; BUS OFF; FLAGS COPY"),
+            ("SETC", "BSETC R0 0000; WRITE OFF; BUS OFF; FLAGS COPY"),
+            ("NOP", "ADDH R0 R0; WRITE OFF; BUS OFF; FLAGS KEEP"),
+        ];
+
+        for (pseudo, idiom) in idioms.iter() {
+            assert_eq!(
+                assemble(&format!("00011: {}; JUMP 00100", idiom)),
+                assemble(&format!("00011: {}; JUMP 00100", pseudo)),
+                "{}", pseudo
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_pseudo_instruction_survives_a_round_trip() {
+        let r1 = RegisterAddress::try_from("R1").unwrap();
+        let r5 = RegisterAddress::try_from("R5").unwrap();
+        let pseudo_instructions = [
+            PseudoInstruction::Move(r1, r5),
+            PseudoInstruction::LoadImmediate(r5, ContentB::try_from("0110").unwrap()),
+            PseudoInstruction::Clear(r1),
+            PseudoInstruction::Input(r1, r1),
+            PseudoInstruction::Input(r1, r5),
+            PseudoInstruction::Output(r5, r1),
+            PseudoInstruction::ClearCarry,
+            PseudoInstruction::SetCarry,
+            PseudoInstruction::NoOperation,
+        ];
+
+        let address = InstructionAddress::from_u8(0b00011);
+        let jump_command = JumpCommand::new(JumpType::Jump, InstructionAddress::from_u8(0b00100));
+        for pseudo_instruction in pseudo_instructions.iter() {
+            let word = pseudo_instruction.to_operation().to_instruction(address, &jump_command).to_word();
+            let (operation, _) = decode_instruction(&Instruction::from_word(address, word)).unwrap();
+            assert_eq!(Some(*pseudo_instruction), PseudoInstruction::from_operation(&operation), "{}", pseudo_instruction);
+        }
+    }
+
+    #[test]
+    fn test_invalid_operands() {
+        assert!(parse_pseudo_instruction("MOV R1").is_err());
+        assert!(parse_pseudo_instruction("IN R1, R2").is_err());
+        assert!(parse_pseudo_instruction("NOP R1").is_err());
+        assert!(parse_pseudo_instruction("JMP R1").is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::Assembler;
+use crate::instruction::Instruction;
+use crate::source::ANONYMOUS_SOURCE;
+use crate::structured::block_depth_change;
+use crate::translated::TranslatedLine;
+
+/// What entering a line into a session did.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplOutcome {
+    /// The line opens a block that is not closed yet, it is translated together with the line that closes it.
+    Pending,
+    /// The lines were accepted, with the instructions translated from them.
+    Accepted(Vec<Instruction>)
+}
+
+/// The lines typed into the REPL so far. Every new line is translated together with all accepted lines,
+/// so `.define` constants, `.data` symbols, `.dialect` and `use std::` stay in effect between lines.
+pub struct ReplSession {
+    assembler: Assembler,
+    lines: Vec<String>,
+    pending: Vec<String>
+}
+
+impl ReplSession {
+    pub fn new(assembler: Assembler) -> ReplSession {
+        return ReplSession {
+            assembler,
+            lines: Vec::new(),
+            pending: Vec::new()
+        };
+    }
+
+    /// The accepted lines, without the lines of an open block.
+    pub fn lines(&self) -> &[String] {
+        return &self.lines;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        return !self.pending.is_empty();
+    }
+
+    /// The accepted lines as `.2ia` source.
+    pub fn source(&self) -> String {
+        return self.lines.iter().map(|line| format!("{}\n", line)).collect();
+    }
+
+    /// Translates the line with the accepted lines, a line that does not translate is discarded with the open block it is in.
+    pub fn enter(&mut self, line: &str) -> Result<ReplOutcome, String> {
+        self.pending.push(line.trim_end().to_string());
+        let depth = self.pending.iter().map(|line| block_depth_change(line)).sum::<i32>();
+        if depth > 0 {
+            return Ok(ReplOutcome::Pending);
+        }
+
+        let first_line = self.lines.len() + 1;
+        let mut candidate = self.lines.clone();
+        candidate.append(&mut self.pending);
+        let last_line = candidate.len();
+
+        let program = self.assembler.translate_program(&candidate.join("\n"))?;
+        let instructions = program.lines.iter().zip(program.locations.iter())
+            .filter(|(_, location)| location.file == ANONYMOUS_SOURCE && (first_line..=last_line).contains(&location.line))
+            .filter_map(|(line, _)| match line {
+                TranslatedLine::InstructionLine(instruction, _) => Some(*instruction),
+                _ => None
+            })
+            .collect();
+
+        self.lines = candidate;
+        return Ok(ReplOutcome::Accepted(instructions));
+    }
+
+    /// Replaces the session with the lines of a program, if the whole program translates.
+    /// Returns the number of instructions it translates to.
+    pub fn load(&mut self, content: &str) -> Result<usize, String> {
+        let program = self.assembler.translate_program(content)?;
+        self.lines = content.lines().map(|line| line.trim_end().to_string()).collect();
+        self.pending.clear();
+        return Ok(program.lines.iter()
+            .filter(|line| matches!(line, TranslatedLine::InstructionLine(_, _)))
+            .count());
+    }
+}
+
+#[cfg(test)]
+mod repl_tests {
+    use super::*;
+
+    fn accepted(outcome: Result<ReplOutcome, String>) -> Vec<Instruction> {
+        return match outcome {
+            Ok(ReplOutcome::Accepted(instructions)) => instructions,
+            other => panic!("the line was not accepted: {:?}", other)
+        };
+    }
+
+    #[test]
+    fn test_symbols_stay_defined_between_lines() {
+        let mut session = ReplSession::new(Assembler::new());
+        assert!(accepted(session.enter(".dialect standard")).is_empty());
+        assert!(accepted(session.enter(".data TABLE 0x02: 1, 2")).is_empty());
+
+        let instructions = accepted(session.enter("00000: R0 = R0 + TABLE; goto 00001"));
+        assert_eq!(1, instructions.len());
+        assert_eq!(0x2, instructions[0].content_b.to_u8());
+        assert_eq!(1, accepted(session.enter("00001: R0 = TABLE; goto 00001")).len());
+
+        assert!(session.enter("00010: R0 = MISSING; goto 00010").is_err());
+        assert_eq!(4, session.lines().len());
+    }
+
+    #[test]
+    fn test_blocks_wait_for_their_end() {
+        let mut session = ReplSession::new(Assembler::new());
+        assert_eq!(Ok(ReplOutcome::Pending), session.enter("loop {"));
+        assert!(session.is_pending());
+        assert_eq!(Ok(ReplOutcome::Pending), session.enter("    R0 = R0 + 01"));
+        assert!(!accepted(session.enter("}")).is_empty());
+        assert!(!session.is_pending());
+        assert_eq!("loop {\n    R0 = R0 + 01\n}\n", session.source());
+
+        assert_eq!(1, session.load("00000: R1 = R0; goto 00000\n").unwrap());
+        assert_eq!(1, session.lines().len());
+    }
+}
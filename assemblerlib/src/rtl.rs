@@ -0,0 +1,497 @@
+use std::convert::TryFrom;
+use crate::diagnostics::{IMPOSSIBLE_TRANSFER, INVALID_ADDRESS, INVALID_CONSTANT, INVALID_JUMP, INVALID_TRANSFER};
+use crate::expression::{parse_number, SymbolTable};
+use crate::disassembly::decode_instruction;
+use crate::instruction::{Instruction, InstructionAddress, RegisterAddress, ContentB};
+use crate::parsing::{Operation, ALUCommand, WriteCommand, BusCommand, FlagCommand, JumpCommand, JumpType, RegisterOrConstant};
+use crate::parsing::RegisterOrConstant::{Register, Constant};
+use crate::suggestion::{closest, did_you_mean};
+
+type DoubleCommand = fn(RegisterAddress, RegisterOrConstant) -> ALUCommand;
+
+const R0: RegisterAddress = RegisterAddress { ad2: false, ad1: false, ad0: false };
+const ZERO: ContentB = ContentB { b3: false, b2: false, b1: false, b0: false };
+
+/// A value the ALU can work with: a register, the bus at the address in a register, or a constant.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Operand {
+    Register(RegisterAddress),
+    Memory(RegisterAddress),
+    Constant(ContentB)
+}
+
+/// Where the result of a transfer goes, `F` only computes it (for the flags).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Destination {
+    Register(RegisterAddress),
+    Memory(RegisterAddress),
+    AluOnly
+}
+
+/// The right hand side of a transfer, by which ALU inputs it depends on.
+#[derive(Copy, Clone)]
+enum Expression {
+    /// The operand is passed through, on input A or B.
+    Pass(Operand),
+    /// The result only depends on input A.
+    OnlyA(DoubleCommand, Operand),
+    /// The result depends on both inputs, the operands may be swapped.
+    Both(DoubleCommand, Operand, Operand)
+}
+
+impl Expression {
+    /// Every way to put the operands onto the A and B inputs, `None` means the input is not used.
+    fn candidates(self) -> Vec<(DoubleCommand, Option<Operand>, Option<Operand>)> {
+        return match self {
+            Expression::Pass(operand) => vec![
+                (ALUCommand::PassA, Some(operand), None),
+                (ALUCommand::PassB, None, Some(operand))
+            ],
+            Expression::OnlyA(command, operand) => vec![(command, Some(operand), None)],
+            Expression::Both(command, x, y) => vec![
+                (command, Some(x), Some(y)),
+                (command, Some(y), Some(x))
+            ]
+        };
+    }
+}
+
+/// A transfer contains `=`, which none of the other command syntaxes do.
+pub(crate) fn is_rtl_transfer(command_string: &str) -> bool {
+    return command_string.contains('=');
+}
+
+/// Lowers a transfer like `R2 = R2 + R3 + C`, `R0 = (R0)` or `(R1) = R0` to the single instruction that does it.
+pub(crate) fn parse_rtl_transfer(command_string: &str, flag_command: FlagCommand) -> Result<Operation, String> {
+    let command_string = command_string.trim();
+    let mut split_transfer = command_string.splitn(2, '=');
+    let destination = parse_destination(split_transfer.next().unwrap_or("").trim())?;
+    let expression = parse_expression(split_transfer.next().unwrap_or("").trim())?;
+
+    let mut first_error = None;
+    for (command, a, b) in expression.candidates() {
+        match lower_transfer(command, a, b, destination, flag_command) {
+            Ok(operation) => return Ok(operation),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    return Err(IMPOSSIBLE_TRANSFER.message(format!("{} can not be done in one Cycle, {}", command_string, first_error.unwrap_or_default())));
+}
+
+fn lower_transfer(command: DoubleCommand, a: Option<Operand>, b: Option<Operand>, destination: Destination, flag_command: FlagCommand) -> Result<Operation, String> {
+    let (a, bus_read) = match a {
+        None => (None, false),
+        Some(Operand::Register(register)) => (Some(register), false),
+        Some(Operand::Memory(register)) => (Some(register), true),
+        Some(Operand::Constant(_)) => return Err("only the B Input can be a Constant".to_string())
+    };
+    let b = match b {
+        None => None,
+        Some(Operand::Register(register)) => Some(Register(register)),
+        Some(Operand::Constant(constant)) => Some(Constant(constant)),
+        Some(Operand::Memory(_)) => return Err("only the A Input can be read from the Bus".to_string())
+    };
+
+    let (a, b, write_command, bus_command) = match destination {
+        Destination::Register(register) if a.unwrap_or(register) == register =>
+            (register, b.unwrap_or(Constant(ZERO)), WriteCommand::WriteA, bus_read_command(bus_read)),
+        Destination::Register(register) if b.unwrap_or(Register(register)) == Register(register) =>
+            (a.unwrap_or(R0), Register(register), WriteCommand::WriteB, bus_read_command(bus_read)),
+        Destination::Register(register) =>
+            return Err(format!("the Destination R{} must be the A or B Register", register.to_u8())),
+        Destination::Memory(_) if bus_read =>
+            return Err("the Bus can not be read and written at once".to_string()),
+        Destination::Memory(address) if a.unwrap_or(address) == address =>
+            (address, b.unwrap_or(Constant(ZERO)), WriteCommand::WriteOff, BusCommand::BusWrite),
+        Destination::Memory(address) =>
+            return Err(format!("the Bus Address R{} must be the A Register", address.to_u8())),
+        Destination::AluOnly =>
+            (a.unwrap_or(R0), b.unwrap_or(Constant(ZERO)), WriteCommand::WriteOff, bus_read_command(bus_read))
+    };
+
+    return Ok(Operation { alu_command: command(a, b), write_command, bus_command, flag_command });
+}
+
+fn bus_read_command(bus_read: bool) -> BusCommand {
+    return match bus_read {
+        true => BusCommand::BusRead,
+        false => BusCommand::BusOff
+    };
+}
+
+fn parse_destination(destination: &str) -> Result<Destination, String> {
+    if destination == "F" {
+        return Ok(Destination::AluOnly);
+    }
+
+    return match parse_operand(destination)? {
+        Operand::Register(register) => Ok(Destination::Register(register)),
+        Operand::Memory(address) => Ok(Destination::Memory(address)),
+        Operand::Constant(_) => Err(INVALID_TRANSFER.message(format!("Can not assign to {}", destination)))
+    };
+}
+
+fn parse_expression(expression: &str) -> Result<Expression, String> {
+    let terms: Vec<&str> = expression.split('+').map(|term| term.trim()).collect();
+    if terms.len() > 1 {
+        return parse_sum(&terms);
+    }
+
+    let words: Vec<&str> = expression.split_whitespace().collect();
+    return match words.as_slice() {
+        ["ASR", x] => Ok(Expression::OnlyA(ALUCommand::ArithShiftRight, parse_operand(x)?)),
+        ["RR", x] => Ok(Expression::OnlyA(ALUCommand::RotateRight, parse_operand(x)?)),
+        ["RRC", x] => Ok(Expression::OnlyA(ALUCommand::RotateRightCarry, parse_operand(x)?)),
+        [x, "NOR", y] => Ok(Expression::Both(ALUCommand::Nor, parse_operand(x)?, parse_operand(y)?)),
+        [x, ">>", "1"] => Ok(Expression::OnlyA(ALUCommand::LogicShiftRight, parse_operand(x)?)),
+        [x, "<<", "1"] => Ok(Expression::Both(ALUCommand::Add, parse_operand(x)?, parse_operand(x)?)),
+        [x] => match x.strip_prefix('~') {
+            Some(x) => Ok(Expression::Both(ALUCommand::Nor, parse_operand(x)?, parse_operand(x)?)),
+            None => Ok(Expression::Pass(parse_operand(x)?))
+        },
+        [] => Err(INVALID_TRANSFER.message("No Expression")),
+        _ => Err(INVALID_TRANSFER.message(format!("Unknown Expression {}", expression)))
+    };
+}
+
+/// `X + Y`, with an optional carry in of `C` (`ADC`), `!C` (`ADCS`) or `1` (`ADDS`).
+fn parse_sum(terms: &[&str]) -> Result<Expression, String> {
+    return match terms {
+        [x, "C"] => Ok(Expression::Both(ALUCommand::AddC, parse_operand(x)?, Operand::Constant(ZERO))),
+        [x, "!C"] => Ok(Expression::Both(ALUCommand::AddSubC, parse_operand(x)?, Operand::Constant(ZERO))),
+        [x, y] => Ok(Expression::Both(ALUCommand::Add, parse_operand(x)?, parse_operand(y)?)),
+        [x, y, "C"] => Ok(Expression::Both(ALUCommand::AddC, parse_operand(x)?, parse_operand(y)?)),
+        [x, y, "!C"] => Ok(Expression::Both(ALUCommand::AddSubC, parse_operand(x)?, parse_operand(y)?)),
+        [x, y, "1"] => Ok(Expression::Both(ALUCommand::AddSub, parse_operand(x)?, parse_operand(y)?)),
+        _ => Err(INVALID_TRANSFER.message("A Sum has two Operands and an optional Carry In of C, !C or 1"))
+    };
+}
+
+fn parse_operand(operand: &str) -> Result<Operand, String> {
+    if let Some(address) = operand.strip_prefix('(').and_then(|operand| operand.strip_suffix(')')) {
+        return Ok(Operand::Memory(RegisterAddress::try_from(address.trim())?));
+    }
+    if operand.starts_with('R') {
+        return Ok(Operand::Register(RegisterAddress::try_from(operand)?));
+    }
+    return Ok(Operand::Constant(parse_constant(operand)?));
+}
+
+/// Four binary digits are the constant bits like in the other syntaxes (`1100`), two digit constants are
+/// hexadecimal like in the 2i documentation (`FC`), everything else is an expression number (`-4`, `0xFC`).
+fn parse_constant(constant: &str) -> Result<ContentB, String> {
+    if constant.len() == 4 && constant.chars().all(|ch| ch == '0' || ch == '1') {
+        return ContentB::try_from(constant);
+    }
+
+    let value = match constant.len() == 2 && constant.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        true => i64::from_str_radix(constant, 16).map_err(|_| INVALID_CONSTANT.message(format!("Invalid Number {}", constant)))?,
+        false => match constant.strip_prefix('-') {
+            Some(magnitude) => -parse_number(magnitude).map_err(|err| INVALID_CONSTANT.message(err))?,
+            None => parse_number(constant).map_err(|err| INVALID_CONSTANT.message(err))?
+        }
+    };
+
+    let content = ContentB::from_u8((value & 0x0F) as u8);
+    if !(-128..=255).contains(&value) || i64::from(content.to_constant_value()) != value & 0xFF {
+        return Err(INVALID_CONSTANT.message(format!("The Constant {} can not be sign extended from 4 Bits, only 00 to 07 and F8 to FF can", constant)));
+    }
+    return Ok(content);
+}
+
+/// Whether a name is an operand or operator of a transfer expression, so it can not name a data symbol.
+pub(crate) fn is_reserved_operand(name: &str) -> bool {
+    return RegisterAddress::try_from(name).is_ok()
+        || (name.len() == 2 && name.chars().all(|ch| ch.is_ascii_hexdigit()))
+        || matches!(name, "NOR" | "ASR" | "RR" | "RRC" | "C");
+}
+
+/// Replaces the symbols among the operands of a transfer, or the last operand of a command or pseudo instruction,
+/// by their value, so data symbols can be used as constants.
+pub(crate) fn resolve_operand_symbols(line: &str, symbols: &SymbolTable) -> Result<String, String> {
+    let (code, comment) = match line.find('#') {
+        Some(index) => line.split_at(index),
+        None => (line, "")
+    };
+    let (destination, expression) = match code.split_once('=') {
+        Some(transfer) => transfer,
+        None => return Ok(format!("{}{}", resolve_constant_operand(code, symbols)?, comment))
+    };
+    let (expression, rest) = match expression.find(';') {
+        Some(index) => expression.split_at(index),
+        None => (expression, "")
+    };
+
+    let mut resolved = String::new();
+    let mut word = String::new();
+    for ch in expression.chars().chain(std::iter::once(' ')) {
+        if ch.is_ascii_alphanumeric() || ch == '_' {
+            word.push(ch);
+            continue;
+        }
+        match symbols.get(&word) {
+            Some(value) => resolved.push_str(&format!("0x{:02X}", value & 0xFF)),
+            None => resolved.push_str(&word)
+        }
+        word.clear();
+        resolved.push(ch);
+    }
+    resolved.pop();
+
+    return Ok(format!("{}={}{}{}", destination, resolved, rest, comment));
+}
+
+/// Constants are the last operand of the first command, like in `PASSB R0 TABLE` or `LDI R0, TABLE`. A symbol there is
+/// replaced by its 4 bits, it has to fit sign extended like the constants of transfers.
+fn resolve_constant_operand(code: &str, symbols: &SymbolTable) -> Result<String, String> {
+    let command = code[..code.find(';').unwrap_or(code.len())].trim_end();
+    let start = match command.rfind(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')) {
+        Some(index) if !command[..index].trim().is_empty() => index + 1,
+        _ => return Ok(code.to_string())
+    };
+    let value = match symbols.get(&command[start..]) {
+        Some(value) => value & 0xFF,
+        None => return Ok(code.to_string())
+    };
+
+    if value > 0x07 && value < 0xF8 {
+        return Err(INVALID_CONSTANT.message(format!("The Constant 0x{:02X} of {} can not be sign extended from 4 Bits, only 00 to 07 and F8 to FF can",
+            value, &command[start..])));
+    }
+    return Ok(format!("{}{:04b}{}", &code[..start], value & 0x0F, &code[command.len()..]));
+}
+
+/// A jump in transfer notation starts with `goto` or `if`.
+pub(crate) fn is_rtl_jump(command_string: &str) -> bool {
+    return matches!(command_string.split_whitespace().next(), Some("goto") | Some("if"));
+}
+
+/// Parses `goto NA` and `if [not] CONDITION goto NA1 else NA0`, where NA1 and NA0 may only differ in bit 0.
+pub(crate) fn parse_rtl_jump(command_string: &str) -> Result<JumpCommand, String> {
+    let words: Vec<&str> = command_string.split_whitespace().collect();
+    let (negated, condition, taken, not_taken) = match words.as_slice() {
+        ["goto", address] => return Ok(JumpCommand::new(JumpType::Jump, parse_address(address)?)),
+        ["if", "not", condition, "goto", taken, "else", not_taken] => (true, *condition, *taken, *not_taken),
+        ["if", condition, "goto", taken, "else", not_taken] => match condition.strip_prefix('!') {
+            Some(condition) => (true, condition, *taken, *not_taken),
+            None => (false, *condition, *taken, *not_taken)
+        },
+        _ => return Err(INVALID_JUMP.message("Expected goto NA or if CONDITION goto NA else NA"))
+    };
+
+    let jump_type = match condition {
+        "C" | "CF" => JumpType::TestCFJump,
+        "CO" => JumpType::TestCOJump,
+        "Z" | "ZO" => JumpType::TestZOJump,
+        "N" | "NO" => JumpType::TestNOJump,
+        "INTA" => JumpType::TestIntAJump,
+        "INTB" => JumpType::TestIntBJump,
+        _ => return Err(INVALID_JUMP.message(format!("Unknown Condition {}{}", condition,
+            did_you_mean(closest(condition, ["C", "CF", "CO", "Z", "ZO", "N", "NO", "INTA", "INTB"])))))
+    };
+
+    let (true_address, false_address) = match negated {
+        true => (parse_address(not_taken)?, parse_address(taken)?),
+        false => (parse_address(taken)?, parse_address(not_taken)?)
+    };
+
+    if !true_address.bit0 || false_address.bit0 || true_address.to_u8() != false_address.to_u8() | 1 {
+        return Err(INVALID_JUMP.message(format!(
+            "A Conditional Jump goes to xxxx1 if {} is true and to xxxx0 if not, {} and {} do not fit",
+            condition, true_address, false_address
+        )));
+    }
+
+    return Ok(JumpCommand::new(jump_type, true_address));
+}
+
+fn parse_address(address: &str) -> Result<InstructionAddress, String> {
+    return InstructionAddress::try_from(address.to_string())
+        .map_err(|err| INVALID_ADDRESS.message(format!("Could not parse Next Address: {}", err.description)));
+}
+
+/// Describes what an instruction does as a register transfer, like `R0 = R0 NOR R1` or
+/// `R2 = (R1) + R2; FLAGS COPY; if Z goto 00101 else 00100`.
+/// Jumps are only described if they are conditional or do not go to the following address.
+pub(crate) fn describe_instruction(instruction: &Instruction) -> Result<String, String> {
+    let (operation, jump_command) = decode_instruction(instruction)?;
+    let (a, b) = operation.alu_command.operands();
+    let b = b.unwrap_or(Register(a));
+
+    let a_name = match operation.bus_command {
+        BusCommand::BusRead => format!("(R{})", a.to_u8()),
+        _ => format!("R{}", a.to_u8())
+    };
+    let b_name = match b {
+        Register(register) => format!("R{}", register.to_u8()),
+        Constant(constant) => format!("{:02X}", constant.to_constant_value())
+    };
+
+    let expression = match operation.alu_command.to_alu_function().to_u8() {
+        0b0000 => format!("{} + {} (hold C)", a_name, b_name),
+        0b0001 => a_name,
+        0b0010 => format!("{} NOR {}", a_name, b_name),
+        0b0011 => "0".to_string(),
+        0b0100 => format!("{} + {}", a_name, b_name),
+        0b0101 => format!("{} + {} + 1", a_name, b_name),
+        0b0110 => format!("{} + {} + C", a_name, b_name),
+        0b0111 => format!("{} + {} + !C", a_name, b_name),
+        0b1000 => format!("{} >> 1", a_name),
+        0b1001 => format!("RR {}", a_name),
+        0b1010 => format!("RRC {}", a_name),
+        0b1011 => format!("ASR {}", a_name),
+        0b1100 => b_name,
+        0b1101 => format!("{} (set C)", b_name),
+        0b1110 => format!("{} (hold C)", b_name),
+        _ => format!("{} (invert C)", b_name)
+    };
+
+    let mut destinations = Vec::new();
+    match (operation.write_command, b) {
+        (WriteCommand::WriteA, _) => destinations.push(format!("R{}", a.to_u8())),
+        (WriteCommand::WriteB, Register(register)) => destinations.push(format!("R{}", register.to_u8())),
+        (WriteCommand::WriteB, Constant(constant)) => destinations.push(format!("R{}", constant.to_u8() & 0b111)),
+        (WriteCommand::WriteOff, _) => {}
+    }
+    if operation.bus_command == BusCommand::BusWrite {
+        destinations.push(format!("(R{})", a.to_u8()));
+    }
+    if destinations.is_empty() {
+        destinations.push("F".to_string());
+    }
+
+    let mut description = format!("{} = {}", destinations.join(", "), expression);
+    if operation.flag_command == FlagCommand::UpdateFlags {
+        description.push_str("; FLAGS COPY");
+    }
+    if let Some(jump) = describe_jump(instruction.address, &jump_command) {
+        description.push_str(&format!("; {}", jump));
+    }
+    return Ok(description);
+}
+
+fn describe_jump(address: InstructionAddress, jump_command: &JumpCommand) -> Option<String> {
+    let condition = match jump_command.jump_type {
+        JumpType::Jump if jump_command.next_address.to_u8() == (address.to_u8() + 1) & 0b11111 => return None,
+        JumpType::Jump => return Some(format!("goto {}", jump_command.next_address)),
+        JumpType::TestCFJump => "C",
+        JumpType::TestCOJump => "CO",
+        JumpType::TestZOJump => "Z",
+        JumpType::TestNOJump => "N",
+        JumpType::TestIntAJump => "INTA",
+        JumpType::TestIntBJump => "INTB"
+    };
+
+    let true_address = InstructionAddress::from_u8(jump_command.next_address.to_u8() | 1);
+    let false_address = InstructionAddress::from_u8(jump_command.next_address.to_u8() & !1);
+    return Some(format!("if {} goto {} else {}", condition, true_address, false_address));
+}
+
+#[cfg(test)]
+mod rtl_tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::parsing::parse_line;
+    use crate::translated::{TranslatedProgram, TranslatedLine};
+
+    fn assemble(line: &str) -> u32 {
+        return parse_line(line, Dialect::Standard).unwrap().instruction.to_word();
+    }
+
+    #[test]
+    fn test_transfers_match_commands() {
+        let transfers = [
+            ("R2 = R2 + R3 + C", "ADDC R2 R3; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("R3 = R2 + R3 + 1; FLAGS COPY", "ADDS R2 R3; WRITE B; BUS OFF; FLAGS COPY"),
+            ("R1 = R1 + 0011", "ADD R1 0011; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("R4 = 0x3 + R4", "ADD R4 0011; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("R0 = (R0)", "PASSA R0 0000; WRITE A; BUS READ; FLAGS KEEP"),
+            ("R2 = (R1) + R2", "ADD R1 R2; WRITE B; BUS READ; FLAGS KEEP"),
+            ("(R1) = R0", "PASSB R1 R0; WRITE OFF; BUS WRITE; FLAGS KEEP"),
+            ("R1 = -4", "PASSB R1 1100; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("R5 = ~R5", "COM R5; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("R6 = R1 >> 1", "LSR R1 R6; WRITE B; BUS OFF; FLAGS KEEP"),
+            ("R1 = RRC R1", "RRC R1 0000; WRITE A; BUS OFF; FLAGS KEEP"),
+            ("F = R1 NOR R2; FLAGS COPY", "NOR R1 R2; WRITE OFF; BUS OFF; FLAGS COPY"),
+        ];
+
+        for (transfer, command) in transfers.iter() {
+            assert_eq!(
+                assemble(&format!("00011: {}; JUMP 00100", command)),
+                assemble(&format!("00011: {}; goto 00100", transfer)),
+                "{}", transfer
+            );
+        }
+    }
+
+    #[test]
+    fn test_example_comments_assemble_to_their_lines() {
+        let example = include_str!("../../Examples/Example 2i Code.2i");
+        let program = TranslatedProgram::parse("example", example).unwrap();
+
+        for line in program.lines.iter() {
+            if let TranslatedLine::InstructionLine(instruction, Some(comment)) = line {
+                let transfer = comment.split(';').next().unwrap();
+                let line = format!("{}: {}; goto {}", instruction.address, transfer, instruction.next_address);
+                assert_eq!(instruction.to_word(), assemble(&line), "{}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_comments_are_derived() {
+        let example = include_str!("../../Examples/Example 2i Code.2i");
+        let program = TranslatedProgram::parse("example", example).unwrap();
+
+        for line in program.lines.iter() {
+            if let TranslatedLine::InstructionLine(instruction, Some(comment)) = line {
+                let described = describe_instruction(instruction).unwrap();
+                assert_eq!(comment.split(';').next().unwrap().trim(), described.split(';').next().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_descriptions_assemble_to_their_instruction() {
+        let lines = [
+            "00010: ADDSC R1 R2; WRITE B; BUS READ; FLAGS COPY; JUMPZO 00101",
+            "00011: BSETC R0 0000; WRITE OFF; BUS OFF; FLAGS COPY; JUMPNO 01000",
+            "00100: RRC R3 1111; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00101",
+        ];
+        let descriptions = [
+            "R2 = (R1) + R2 + !C; FLAGS COPY; if Z goto 00101 else 00100",
+            "F = 00 (set C); FLAGS COPY; if N goto 01001 else 01000",
+            "R3 = RRC R3",
+        ];
+
+        for (line, expected) in lines.iter().zip(descriptions.iter()) {
+            let instruction = parse_line(line, Dialect::Standard).unwrap().instruction;
+            assert_eq!(*expected, describe_instruction(&instruction).unwrap());
+        }
+        assert_eq!(assemble(lines[0]), assemble(&format!("00010: {}", descriptions[0])));
+    }
+
+    #[test]
+    fn test_conditional_jumps() {
+        assert_eq!(
+            assemble("00010: R1 = R1 + R2; FLAGS COPY; JUMPZO 00101"),
+            assemble("00010: R1 = R1 + R2; FLAGS COPY; if Z goto 00101 else 00100")
+        );
+        assert_eq!(
+            assemble("00010: NOP; JUMPCF 00111"),
+            assemble("00010: NOP; if not C goto 00110 else 00111")
+        );
+        assert!(parse_line("00010: NOP; if Z goto 00101 else 00010", Dialect::Standard).is_err());
+        assert!(parse_line("00010: NOP; if Z goto 00100 else 00101", Dialect::Standard).is_err());
+    }
+
+    #[test]
+    fn test_impossible_transfers_are_rejected() {
+        for transfer in ["R2 = R0 + R1", "R0 = FC + 03", "R0 = 10", "(R1) = (R2)", "(R1) = R0 + R2", "R0 = (R1) + (R1)", "R1 = R2 + R3 + R4"].iter() {
+            assert!(parse_line(&format!("00000: {}; goto 00001", transfer), Dialect::Standard).is_err(), "{}", transfer);
+        }
+    }
+}
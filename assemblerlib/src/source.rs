@@ -0,0 +1,252 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::diagnostics::{INCLUDE_CYCLE, INVALID_INCLUDE, MISSING_FILE, UNBALANCED_CONDITIONAL};
+use crate::conditional::{ConditionalAssembly, LineDisposition};
+use crate::expression::SymbolTable;
+use crate::library::parse_use_directive;
+
+const INCLUDE_DIRECTIVE: &str = ".include";
+
+/// The name used for programs that were not read from a file.
+pub const ANONYMOUS_SOURCE: &str = "<input>";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize
+}
+
+impl SourceLocation {
+    pub fn new(file: &str, line: usize) -> SourceLocation {
+        return SourceLocation {
+            file: file.to_string(),
+            line
+        };
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SourceLine {
+    pub location: SourceLocation,
+    pub text: String
+}
+
+/// Reads a source file and every file it includes, in include order.
+/// Lines in inactive conditional regions and preprocessor directives are left out.
+pub fn load_file(path: &Path, symbols: &SymbolTable) -> Result<Vec<SourceLine>, String> {
+    let mut loader = SourceLoader::new(symbols);
+    loader.load_file(path, None)?;
+    return Ok(loader.lines);
+}
+
+/// Splits a program given as a string into lines, resolving includes relative to `base_directory`.
+pub fn load_string(name: &str, content: &str, base_directory: &Path, symbols: &SymbolTable) -> Result<Vec<SourceLine>, String> {
+    let mut loader = SourceLoader::new(symbols);
+    loader.load_content(name, content, base_directory)?;
+    return Ok(loader.lines);
+}
+
+/// Returns the quoted path of an `.include "file"` line, or `None` if the line is no include directive.
+pub fn parse_include_directive(line: &str) -> Option<Result<String, String>> {
+    let line_without_comment = line.split('#').next().unwrap_or("").trim();
+
+    if !line_without_comment.starts_with(INCLUDE_DIRECTIVE) {
+        return None;
+    }
+
+    let argument = line_without_comment[INCLUDE_DIRECTIVE.len()..].trim();
+
+    if argument.len() < 2 || !argument.starts_with('"') || !argument.ends_with('"') {
+        return Some(Err(INVALID_INCLUDE.message("Include Path must be enclosed in quotes")));
+    }
+
+    return Some(Ok(argument[1..argument.len() - 1].to_string()));
+}
+
+struct SourceLoader {
+    lines: Vec<SourceLine>,
+    include_stack: Vec<PathBuf>,
+    conditional_assembly: ConditionalAssembly
+}
+
+impl SourceLoader {
+    fn new(symbols: &SymbolTable) -> SourceLoader {
+        return SourceLoader {
+            lines: Vec::new(),
+            include_stack: Vec::new(),
+            conditional_assembly: ConditionalAssembly::new(symbols.clone())
+        };
+    }
+
+    fn load_file(&mut self, path: &Path, included_from: Option<&SourceLocation>) -> Result<(), String> {
+        let canonical_path = fs::canonicalize(path)
+            .map_err(|_| with_location(included_from, MISSING_FILE.message(format!("Could not open File {}", path.display()))))?;
+
+        if self.include_stack.contains(&canonical_path) {
+            let mut cycle: Vec<String> = self.include_stack.iter()
+                .skip_while(|included| **included != canonical_path)
+                .map(|included| included.display().to_string())
+                .collect();
+            cycle.push(canonical_path.display().to_string());
+            return Err(with_location(included_from, INCLUDE_CYCLE.message(format!("Include Cycle: {}", cycle.join(" -> ")))));
+        }
+
+        let content = fs::read_to_string(&canonical_path)
+            .map_err(|_| with_location(included_from, MISSING_FILE.message(format!("Could not read File {}", path.display()))))?;
+
+        let base_directory = canonical_path.parent()
+            .map(|directory| directory.to_path_buf())
+            .unwrap_or_default();
+
+        self.include_stack.push(canonical_path);
+        let result = self.load_content(&path.display().to_string(), &content, &base_directory);
+        self.include_stack.pop();
+
+        return result;
+    }
+
+    fn load_content(&mut self, name: &str, content: &str, base_directory: &Path) -> Result<(), String> {
+        let conditional_depth = self.conditional_assembly.depth();
+        let mut line_count = 0;
+
+        for (i, line) in content.lines().enumerate() {
+            let location = SourceLocation::new(name, i + 1);
+            line_count = i + 1;
+
+            match self.conditional_assembly.process_line(line) {
+                Ok(LineDisposition::Keep) => {},
+                Ok(_) => continue,
+                Err(err) => return Err(with_location(Some(&location), err))
+            }
+
+            match parse_use_directive(line) {
+                None => {},
+                Some(Ok(routine)) => {
+                    self.load_content(&format!("std::{}", routine.name), routine.source, base_directory)?;
+                    continue;
+                },
+                Some(Err(err)) => return Err(with_location(Some(&location), err))
+            }
+
+            match parse_include_directive(line) {
+                None => self.lines.push(SourceLine { location, text: line.to_string() }),
+                Some(Ok(include_path)) => {
+                    let include_path = base_directory.join(include_path);
+                    self.load_file(&include_path, Some(&location))?;
+                },
+                Some(Err(err)) => return Err(with_location(Some(&location), err))
+            }
+        }
+
+        if self.conditional_assembly.depth() != conditional_depth {
+            let location = SourceLocation::new(name, line_count);
+            return Err(with_location(Some(&location), UNBALANCED_CONDITIONAL.message("Missing .endif at End of File")));
+        }
+
+        return Ok(());
+    }
+}
+
+fn with_location(location: Option<&SourceLocation>, description: String) -> String {
+    return match location {
+        Some(location) => format!("Error in {}, Description: {}", location, description),
+        None => description
+    };
+}
+
+/// A directory for the files of a test, it is removed with everything in it when the test ends.
+#[cfg(test)]
+pub(crate) struct TemporaryDirectory {
+    pub path: PathBuf
+}
+
+#[cfg(test)]
+impl TemporaryDirectory {
+    pub fn new(name: &str) -> TemporaryDirectory {
+        let path = std::env::temp_dir().join(format!("assemblerlib_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        return TemporaryDirectory { path };
+    }
+
+    pub fn join(&self, path: &str) -> PathBuf {
+        return self.path.join(path);
+    }
+}
+
+#[cfg(test)]
+impl Drop for TemporaryDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod source_tests {
+    use super::*;
+
+    #[test]
+    fn test_include_is_resolved_relative_to_including_file() {
+        let directory = TemporaryDirectory::new("include");
+        fs::create_dir_all(directory.join("lib")).unwrap();
+        fs::write(directory.join("main.2ia"), "# main\n.include \"lib/io.2ia\"\n# end").unwrap();
+        fs::write(directory.join("lib").join("io.2ia"), "# io\n.include \"ports.2ia\"").unwrap();
+        fs::write(directory.join("lib").join("ports.2ia"), "# ports").unwrap();
+
+        let lines = load_file(&directory.join("main.2ia"), &SymbolTable::new()).unwrap();
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+
+        assert_eq!(vec!["# main", "# io", "# ports", "# end"], texts);
+        assert!(lines[2].location.file.ends_with("ports.2ia"));
+        assert_eq!(1, lines[2].location.line);
+        assert_eq!(3, lines[3].location.line);
+    }
+
+    #[test]
+    fn test_include_cycle_is_detected() {
+        let directory = TemporaryDirectory::new("cycle");
+        fs::write(directory.join("a.2ia"), ".include \"b.2ia\"").unwrap();
+        fs::write(directory.join("b.2ia"), "\n.include \"a.2ia\"").unwrap();
+
+        let error = load_file(&directory.join("a.2ia"), &SymbolTable::new()).unwrap_err();
+
+        assert!(error.contains("Include Cycle"));
+        assert!(error.contains("b.2ia:2"));
+    }
+
+    #[test]
+    fn test_conditional_include_is_not_loaded() {
+        let directory = TemporaryDirectory::new("conditional");
+        fs::write(directory.join("main.2ia"), ".ifdef SIMULATION\n.include \"missing.2ia\"\n.endif\n# main").unwrap();
+
+        let lines = load_file(&directory.join("main.2ia"), &SymbolTable::new()).unwrap();
+
+        assert_eq!(1, lines.len());
+        assert_eq!(4, lines[0].location.line);
+    }
+
+    #[test]
+    fn test_conditional_must_be_closed_in_same_file() {
+        let directory = TemporaryDirectory::new("unclosed");
+        fs::write(directory.join("main.2ia"), ".include \"open.2ia\"\n.endif").unwrap();
+        fs::write(directory.join("open.2ia"), ".if 1").unwrap();
+
+        let error = load_file(&directory.join("main.2ia"), &SymbolTable::new()).unwrap_err();
+
+        assert!(error.contains("Missing .endif"));
+    }
+
+    #[test]
+    fn test_include_directive_requires_quotes() {
+        assert!(parse_include_directive("00000: ZERO R0 R0; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00000").is_none());
+        assert_eq!(Some(Ok("io.2ia".to_string())), parse_include_directive("  .include \"io.2ia\" # ports"));
+        assert!(parse_include_directive(".include io.2ia").unwrap().is_err());
+    }
+}
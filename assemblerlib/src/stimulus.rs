@@ -0,0 +1,235 @@
+use crate::debugger::parse_byte;
+use crate::diagnostics::INVALID_STIMULUS;
+use crate::emulator::Emulator;
+use crate::expression::SymbolTable;
+use crate::memory::IO_PORTS;
+use crate::source::SourceLocation;
+
+/// When an input port value starts to be read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    /// Once this many cycles are executed.
+    Cycle(u64),
+    /// From this read of the port on, the first read is 1.
+    Read(u64)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StimulusEvent {
+    Input(u8, u8),
+    InterruptA(bool),
+    /// A rising edge of the edge interrupt.
+    InterruptB
+}
+
+/// A byte a program wrote to an output port.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PortWrite {
+    /// The cycle the write happened in, the first cycle is 1.
+    pub cycle: u64,
+    pub port: u8,
+    pub value: u8
+}
+
+/// Input port values and interrupts over time and the output port writes a program is expected to do, read from lines like
+/// `at 10: FC=05 INTA=1`, `read 2: FD=07`, `at 20: INTB` and `out: FE=0C`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stimulus {
+    pub events: Vec<(Trigger, StimulusEvent)>,
+    /// The expected writes to output ports, in order.
+    pub expected_writes: Vec<(u8, u8)>
+}
+
+impl Stimulus {
+    /// `name` is used for error messages.
+    pub fn parse(name: &str, content: &str) -> Result<Stimulus, String> {
+        let mut stimulus = Stimulus::default();
+        for (index, line) in content.lines().enumerate() {
+            let location = SourceLocation::new(name, index + 1);
+            stimulus.parse_line(line)
+                .map_err(|error| format!("Error in {}, Description: {}", location, INVALID_STIMULUS.message(error)))?;
+        }
+        return Ok(stimulus);
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        let (trigger, items) = line.split_once(':')
+            .ok_or_else(|| format!("Expected a Line like at 10: FC=05, read 2: FD=07 or out: FE=0C, got {}", line))?;
+        let trigger: Vec<&str> = trigger.split_whitespace().collect();
+        let items: Vec<&str> = items.split_whitespace().collect();
+        if items.is_empty() {
+            return Err(format!("The Line {} sets nothing", line));
+        }
+
+        if trigger == ["out"] {
+            for item in items {
+                let (port, value) = parse_port_value(item)?;
+                self.expected_writes.push((port, value));
+            }
+            return Ok(());
+        }
+
+        let trigger = match trigger.as_slice() {
+            ["at", cycle] => Trigger::Cycle(cycle.parse().map_err(|_| format!("Invalid Cycle {}", cycle))?),
+            ["read", count] => match count.parse() {
+                Ok(count) if count > 0 => Trigger::Read(count),
+                _ => return Err(format!("Invalid Read Count {}, the first Read is 1", count))
+            },
+            _ => return Err(format!("Unknown Trigger {}, expected at CYCLE, read COUNT or out", trigger.join(" ")))
+        };
+        for item in items {
+            let event = match item.to_ascii_uppercase().as_str() {
+                "INTA=1" => StimulusEvent::InterruptA(true),
+                "INTA=0" => StimulusEvent::InterruptA(false),
+                "INTB" => StimulusEvent::InterruptB,
+                _ => {
+                    let (port, value) = parse_port_value(item)?;
+                    StimulusEvent::Input(port, value)
+                }
+            };
+            if matches!((trigger, event), (Trigger::Read(_), StimulusEvent::InterruptA(_) | StimulusEvent::InterruptB)) {
+                return Err(format!("Interrupts are triggered at a Cycle, not at a Read: {}", item));
+            }
+            self.events.push((trigger, event));
+        }
+        return Ok(());
+    }
+
+    /// Runs the program for up to `cycles` cycles, or until it halts, with the inputs and interrupts of the stimulus
+    /// and returns the writes to output ports. Where several lines set a port, the last line that applies wins.
+    pub fn run(&self, emulator: &mut Emulator, cycles: u64) -> Result<Vec<PortWrite>, String> {
+        return match self.run_until_error(emulator, cycles) {
+            (writes, None) => Ok(writes),
+            (_, Some(error)) => Err(error)
+        };
+    }
+
+    /// Like `run`, but keeps the writes before an error that stopped the program.
+    pub fn run_until_error(&self, emulator: &mut Emulator, cycles: u64) -> (Vec<PortWrite>, Option<String>) {
+        let mut reads = [0u64; 4];
+        let mut writes = Vec::new();
+
+        for _ in 0..cycles {
+            if emulator.is_halted() {
+                break;
+            }
+
+            let executed = emulator.state.cycles;
+            for (trigger, event) in &self.events {
+                match (*trigger, *event) {
+                    (Trigger::Cycle(cycle), StimulusEvent::InterruptA(level)) if cycle == executed => emulator.state.interrupt_a = level,
+                    (Trigger::Cycle(cycle), StimulusEvent::InterruptB) if cycle == executed => {
+                        emulator.set_interrupt_b(true);
+                        emulator.set_interrupt_b(false);
+                    },
+                    (Trigger::Cycle(cycle), StimulusEvent::Input(port, value)) if cycle <= executed => emulator.state.memory[port as usize] = value,
+                    (Trigger::Read(count), StimulusEvent::Input(port, value)) if count <= reads[port_index(port)] + 1 => emulator.state.memory[port as usize] = value,
+                    _ => {}
+                }
+            }
+
+            let cycle = match emulator.step() {
+                Ok(cycle) => cycle,
+                Err(error) => return (writes, Some(error))
+            };
+            if let Some((address, _)) = cycle.bus_read.filter(|(address, _)| IO_PORTS.contains(address)) {
+                reads[port_index(address)] += 1;
+            }
+            if let Some((port, value)) = cycle.bus_write.filter(|(address, _)| IO_PORTS.contains(address)) {
+                writes.push(PortWrite { cycle: emulator.state.cycles, port, value });
+            }
+        }
+        return (writes, None);
+    }
+
+    /// The differences between the expected and the actual output port writes, empty if they are the same.
+    pub fn check(&self, writes: &[PortWrite]) -> Vec<String> {
+        let mut differences = Vec::new();
+        for index in 0..self.expected_writes.len().max(writes.len()) {
+            let difference = match (self.expected_writes.get(index), writes.get(index)) {
+                (Some((port, value)), Some(write)) if write.port == *port && write.value == *value => continue,
+                (Some((port, value)), Some(write)) => format!("expected {:02X}={:02X}, got {:02X}={:02X} in Cycle {}", port, value, write.port, write.value, write.cycle),
+                (Some((port, value)), None) => format!("expected {:02X}={:02X}, got no Write", port, value),
+                (None, Some(write)) => format!("expected no Write, got {:02X}={:02X} in Cycle {}", write.port, write.value, write.cycle),
+                (None, None) => continue
+            };
+            differences.push(format!("Write {}: {}", index + 1, difference));
+        }
+        return differences;
+    }
+}
+
+fn port_index(port: u8) -> usize {
+    return (port - IO_PORTS.start()) as usize;
+}
+
+fn parse_port_value(item: &str) -> Result<(u8, u8), String> {
+    let (port, value) = item.split_once('=')
+        .ok_or_else(|| format!("Expected PORT=VALUE, got {}", item))?;
+    let port = parse_byte(port, &SymbolTable::new())?;
+    if !IO_PORTS.contains(&port) {
+        return Err(format!("{:02X} is no I/O Port, the Ports are FC to FF", port));
+    }
+    return Ok((port, parse_byte(value, &SymbolTable::new())?));
+}
+
+#[cfg(test)]
+mod stimulus_tests {
+    use super::*;
+    use crate::Assembler;
+
+    /// Waits for INTB, then copies FC to FE and counts the interrupts in FF.
+    const PROGRAM: &str = "R1 = 00\n\
+        loop {\n\
+            NOP\n\
+            if intb {\n\
+                R0 = FC\n\
+                R2 = (R0)\n\
+                R0 = FE\n\
+                (R0) = R2\n\
+                R1 = R1 + 01\n\
+                R0 = FF\n\
+                (R0) = R1\n\
+            }\n\
+        }";
+
+    fn run(stimulus: &str) -> (Stimulus, Vec<PortWrite>) {
+        let program = Assembler::new().translate_program(PROGRAM).unwrap();
+        let stimulus = Stimulus::parse("copy.stim", stimulus).unwrap();
+        let writes = stimulus.run(&mut Emulator::new(&program), 100).unwrap();
+        return (stimulus, writes);
+    }
+
+    #[test]
+    fn test_inputs_and_interrupts() {
+        let (stimulus, writes) = run("# Two Interrupts\n\
+            at 0: FC=05\n\
+            at 10: INTB\n\
+            read 2: FC=07\n\
+            at 40: INTB\n\
+            out: FE=05 FF=01\n\
+            out: FE=07 FF=02");
+
+        assert_eq!(4, writes.len());
+        assert!(stimulus.check(&writes).is_empty());
+
+        let (stimulus, writes) = run("at 5: INTB\nout: FE=00 FF=02");
+        assert_eq!(vec!["Write 2: expected FF=02, got FF=01 in Cycle 13".to_string()], stimulus.check(&writes));
+        let (stimulus, writes) = run("at 5: FC=01\nout: FE=01");
+        assert_eq!(vec!["Write 1: expected FE=01, got no Write".to_string()], stimulus.check(&writes));
+    }
+
+    #[test]
+    fn test_invalid_stimulus() {
+        assert!(Stimulus::parse("wrong.stim", INVALID_STIMULUS.wrong).unwrap_err().contains(INVALID_STIMULUS.code));
+        assert!(Stimulus::parse("right.stim", INVALID_STIMULUS.right).is_ok());
+        assert!(Stimulus::parse("wrong.stim", "read 0: FC=01").is_err());
+        assert!(Stimulus::parse("wrong.stim", "read 1: INTB").is_err());
+        assert!(Stimulus::parse("wrong.stim", "later: FC=01").is_err());
+    }
+}
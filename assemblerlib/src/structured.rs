@@ -0,0 +1,606 @@
+use crate::diagnostics::{ADDRESS_CONFLICT, BREAK_OUTSIDE_LOOP, CONDITION_WITHOUT_INSTRUCTION, CONTROL_STORE_FULL, UNBALANCED_BLOCK, UNKNOWN_CONDITION, UNRESOLVED_JUMP};
+use crate::suggestion::{closest, did_you_mean};
+use crate::dialect::Dialect;
+use crate::instruction::{Instruction, InstructionAddress};
+use crate::parsing::{Operation, JumpCommand, JumpType, parse_operation_commands};
+use crate::pseudo::{PseudoInstruction, PSEUDO_MNEMONICS};
+use crate::rtl::is_rtl_transfer;
+use crate::source::SourceLocation;
+
+/// The words that start a line of structured code that is no instruction.
+pub(crate) const STRUCTURED_KEYWORDS: [&str; 5] = ["loop", "if", "while", "break", "}"];
+
+/// The number of words in the control store of the 2i.
+pub const CONTROL_STORE_WORDS: usize = 32;
+
+/// A branch condition, tested on the instruction line right in front of the block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Condition {
+    jump_type: JumpType,
+    negated: bool
+}
+
+const CONDITION_NAMES: [&str; 6] = ["zero", "negative", "carry", "carry flag", "inta", "intb"];
+
+impl Condition {
+    fn parse(words: &[&str]) -> Result<Condition, String> {
+        let (negated, words) = match words {
+            ["not", rest @ ..] => (true, rest),
+            _ => (false, words)
+        };
+
+        let jump_type = match words {
+            ["zero"] => JumpType::TestZOJump,
+            ["negative"] => JumpType::TestNOJump,
+            ["carry"] => JumpType::TestCOJump,
+            ["carry", "flag"] => JumpType::TestCFJump,
+            ["inta"] => JumpType::TestIntAJump,
+            ["intb"] => JumpType::TestIntBJump,
+            _ => return Err(UNKNOWN_CONDITION.message(format!("Unknown Condition {}, expected zero, negative, carry, carry flag, inta or intb{}",
+                words.join(" "), did_you_mean(closest(&words.join(" "), CONDITION_NAMES)))))
+        };
+
+        return Ok(Condition { jump_type, negated });
+    }
+
+    fn branch(self, taken: Target, not_taken: Target) -> NodeJump {
+        return match self.negated {
+            false => NodeJump::Branch(self.jump_type, taken, not_taken),
+            true => NodeJump::Branch(self.jump_type, not_taken, taken)
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+struct InstructionStatement {
+    operation: Operation,
+    comment: Option<String>,
+    location: SourceLocation,
+    /// The position of the source line, generated instructions are listed there.
+    index: usize
+}
+
+#[derive(Clone, Debug)]
+enum Statement {
+    Instruction(InstructionStatement),
+    If(Condition, Vec<Statement>, Vec<Statement>, SourceLocation),
+    While(Condition, Vec<Statement>, SourceLocation),
+    Loop(Vec<Statement>, SourceLocation, usize),
+    Break(SourceLocation)
+}
+
+enum BlockKind {
+    Program,
+    Then(Condition),
+    Else(Condition, Vec<Statement>),
+    While(Condition),
+    Loop
+}
+
+struct OpenBlock {
+    kind: BlockKind,
+    statements: Vec<Statement>,
+    location: SourceLocation,
+    index: usize
+}
+
+/// Structured code is made of these keywords, pseudo instructions and commands or transfers without a jump command,
+/// their addresses and jumps are generated. Every other line needs an instruction address.
+pub(crate) fn is_structured_line(line: &str) -> bool {
+    let code = line.split('#').next().unwrap_or("").trim();
+    let first_word = match code.split_whitespace().next() {
+        Some(first_word) => first_word,
+        None => return false
+    };
+    if STRUCTURED_KEYWORDS.contains(&first_word) {
+        return true;
+    }
+
+    let commands: Vec<&str> = code.split(';').map(|command| command.trim()).collect();
+    return match commands.as_slice() {
+        [transfer] if is_rtl_transfer(transfer) => true,
+        [transfer, flag_command] if is_rtl_transfer(transfer) => flag_command.starts_with("FLAGS"),
+        [_] => PSEUDO_MNEMONICS.contains(&first_word),
+        [_, _, _, flag_command] => flag_command.starts_with("FLAGS"),
+        _ => false
+    };
+}
+
+/// How many blocks of structured code the line opens, negative if it closes more than it opens.
+pub(crate) fn block_depth_change(line: &str) -> i32 {
+    let code = line.split('#').next().unwrap_or("");
+    return code.split_whitespace()
+        .map(|token| match token {
+            "{" => 1,
+            "}" => -1,
+            _ => 0
+        })
+        .sum();
+}
+
+/// An instruction generated from structured code.
+pub(crate) struct GeneratedInstruction {
+    /// The position of the source line the instruction was generated for.
+    pub index: usize,
+    pub instruction: Instruction,
+    pub comment: Option<String>,
+    pub location: SourceLocation
+}
+
+/// Collects the structured lines of a program and compiles them to instructions.
+pub(crate) struct StructuredProgram {
+    blocks: Vec<OpenBlock>
+}
+
+impl StructuredProgram {
+    pub fn new() -> StructuredProgram {
+        return StructuredProgram {
+            blocks: vec![OpenBlock {
+                kind: BlockKind::Program,
+                statements: Vec::new(),
+                location: SourceLocation::new("", 0),
+                index: 0
+            }]
+        };
+    }
+
+    pub fn process_line(&mut self, line: &str, location: &SourceLocation, index: usize, dialect: Dialect) -> Result<(), String> {
+        let mut split_comment = line.splitn(2, '#');
+        let code = split_comment.next().unwrap_or("").trim();
+        let comment = split_comment.next().map(|comment| comment.to_string());
+        let words: Vec<&str> = code.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["}"] => self.close_block()?,
+            ["}", "else", "{"] => {
+                let block = self.close_open_block()?;
+                match block.kind {
+                    BlockKind::Then(condition) => self.open_block(BlockKind::Else(condition, block.statements), &block.location, block.index),
+                    _ => return Err(UNBALANCED_BLOCK.message("else without if"))
+                }
+            },
+            ["loop", "{"] => self.open_block(BlockKind::Loop, location, index),
+            ["if", condition @ .., "{"] => self.open_block(BlockKind::Then(Condition::parse(condition)?), location, index),
+            ["while", condition @ .., "{"] => self.open_block(BlockKind::While(Condition::parse(condition)?), location, index),
+            ["break"] => self.push(Statement::Break(location.clone())),
+            _ => {
+                let operation = parse_operation_commands(code, dialect)?;
+                self.push(Statement::Instruction(InstructionStatement {
+                    operation,
+                    comment,
+                    location: location.clone(),
+                    index
+                }));
+            }
+        }
+        return Ok(());
+    }
+
+    fn open_block(&mut self, kind: BlockKind, location: &SourceLocation, index: usize) {
+        self.blocks.push(OpenBlock {
+            kind,
+            statements: Vec::new(),
+            location: location.clone(),
+            index
+        });
+    }
+
+    fn close_open_block(&mut self) -> Result<OpenBlock, String> {
+        if self.blocks.len() < 2 {
+            return Err(UNBALANCED_BLOCK.message("} without open Block"));
+        }
+        return Ok(self.blocks.pop().unwrap());
+    }
+
+    fn close_block(&mut self) -> Result<(), String> {
+        let block = self.close_open_block()?;
+        let statement = match block.kind {
+            BlockKind::Then(condition) => Statement::If(condition, block.statements, Vec::new(), block.location),
+            BlockKind::Else(condition, then_statements) => Statement::If(condition, then_statements, block.statements, block.location),
+            BlockKind::While(condition) => Statement::While(condition, block.statements, block.location),
+            BlockKind::Loop => Statement::Loop(block.statements, block.location, block.index),
+            BlockKind::Program => unreachable!()
+        };
+        self.push(statement);
+        return Ok(());
+    }
+
+    fn push(&mut self, statement: Statement) {
+        if let Some(block) = self.blocks.last_mut() {
+            block.statements.push(statement);
+        }
+    }
+
+    /// Generates the instructions, the first one at address 00000, the others at addresses `used` does not contain.
+    pub fn compile(mut self, used: &[InstructionAddress]) -> Result<Vec<GeneratedInstruction>, String> {
+        if let Some(block) = self.blocks.get(1) {
+            return Err(format!("Error in {}, Description: {}", block.location, UNBALANCED_BLOCK.message("Block is never closed with }")));
+        }
+
+        let statements = self.blocks.pop().map(|block| block.statements).unwrap_or_default();
+        let mut compiler = Compiler { nodes: Vec::new(), slots: Vec::new() };
+        let start = compiler.new_slot();
+        let entry = compiler.compile_block(&statements, start, None)?;
+        if entry == start {
+            return Ok(Vec::new());
+        }
+        compiler.slots[target_index(start)] = Some(entry);
+
+        let entry = compiler.resolve(entry)?;
+        let jumps = compiler.resolve_jumps()?;
+        return compiler.place(entry, jumps, used);
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Node(usize),
+    /// Stands for a node that is only known once the enclosing block is compiled, like the start of a loop.
+    Slot(usize)
+}
+
+fn target_index(target: Target) -> usize {
+    return match target {
+        Target::Slot(index) | Target::Node(index) => index
+    };
+}
+
+#[derive(Copy, Clone, Debug)]
+enum NodeJump {
+    Goto(Target),
+    Branch(JumpType, Target, Target)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ResolvedJump {
+    Goto(usize),
+    /// A conditional jump to the first node if the condition is true and to the second one if not.
+    Branch(JumpType, usize, usize)
+}
+
+struct Node {
+    operation: Operation,
+    jump: NodeJump,
+    comment: Option<String>,
+    location: SourceLocation,
+    index: usize
+}
+
+struct Compiler {
+    nodes: Vec<Node>,
+    slots: Vec<Option<Target>>
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PairRole {
+    /// The node is at address 00000, its partner at 00001 is still free.
+    Entry,
+    /// The node is the true target of a branch, at xxxx1.
+    Odd,
+    /// The node is the false target of a branch, at xxxx0.
+    Even
+}
+
+impl Compiler {
+    fn new_slot(&mut self) -> Target {
+        self.slots.push(None);
+        return Target::Slot(self.slots.len() - 1);
+    }
+
+    fn add_node(&mut self, operation: Operation, jump: NodeJump, comment: Option<String>, location: &SourceLocation, index: usize) -> Target {
+        self.nodes.push(Node {
+            operation,
+            jump,
+            comment,
+            location: location.clone(),
+            index
+        });
+        return Target::Node(self.nodes.len() - 1);
+    }
+
+    fn add_statement_node(&mut self, statement: &InstructionStatement, jump: NodeJump) -> Target {
+        return self.add_node(statement.operation, jump, statement.comment.clone(), &statement.location, statement.index);
+    }
+
+    /// Compiles the statements back to front, every statement continues with the one after it and the last with `next`.
+    fn compile_block(&mut self, statements: &[Statement], next: Target, break_target: Option<Target>) -> Result<Target, String> {
+        let mut next = next;
+        // The statements after a `break` can never run, they get no address.
+        let mut index = statements.iter()
+            .position(|statement| matches!(statement, Statement::Break(_)))
+            .map_or(statements.len(), |position| position + 1);
+
+        while index > 0 {
+            index -= 1;
+            next = match &statements[index] {
+                Statement::Instruction(statement) => self.add_statement_node(statement, NodeJump::Goto(next)),
+                Statement::Break(location) => break_target
+                    .ok_or_else(|| format!("Error in {}, Description: {}", location, BREAK_OUTSIDE_LOOP.message("break outside of a Loop")))?,
+                Statement::Loop(body, location, source_index) => {
+                    let start = self.new_slot();
+                    let mut entry = self.compile_block(body, start, Some(next))?;
+                    if entry == start {
+                        // An empty `loop {}` stops the program in a `NOP` that jumps to itself.
+                        let halt = Target::Node(self.nodes.len());
+                        entry = self.add_node(PseudoInstruction::NoOperation.to_operation(), NodeJump::Goto(halt), None, location, *source_index);
+                    }
+                    self.slots[target_index(start)] = Some(entry);
+                    entry
+                },
+                Statement::If(condition, then_statements, else_statements, location) => {
+                    let tested = tested_statement(statements, index, location)?;
+                    let then_entry = self.compile_block(then_statements, next, break_target)?;
+                    let else_entry = self.compile_block(else_statements, next, break_target)?;
+                    index -= 1;
+                    self.add_statement_node(tested, condition.branch(then_entry, else_entry))
+                },
+                Statement::While(condition, body, location) => {
+                    let tested = tested_statement(statements, index, location)?;
+                    let test = self.add_statement_node(tested, NodeJump::Goto(next));
+                    let body_entry = self.compile_block(body, test, Some(next))?;
+                    self.nodes[target_index(test)].jump = condition.branch(body_entry, next);
+                    index -= 1;
+                    test
+                }
+            };
+        }
+
+        return Ok(next);
+    }
+
+    fn resolve(&self, target: Target) -> Result<usize, String> {
+        let mut target = target;
+        for _ in 0..=self.slots.len() {
+            target = match target {
+                Target::Node(node) => return Ok(node),
+                Target::Slot(slot) => self.slots[slot].ok_or_else(|| UNRESOLVED_JUMP.message("Unresolved Jump Target"))?
+            };
+        }
+        return Err(UNRESOLVED_JUMP.message("Jump Targets form a Cycle without Instructions"));
+    }
+
+    fn resolve_jumps(&self) -> Result<Vec<ResolvedJump>, String> {
+        return self.nodes.iter()
+            .map(|node| match node.jump {
+                NodeJump::Goto(target) => Ok(ResolvedJump::Goto(self.resolve(target)?)),
+                NodeJump::Branch(jump_type, taken, not_taken) => {
+                    let (taken, not_taken) = (self.resolve(taken)?, self.resolve(not_taken)?);
+                    Ok(match taken == not_taken {
+                        true => ResolvedJump::Goto(taken),
+                        false => ResolvedJump::Branch(jump_type, taken, not_taken)
+                    })
+                }
+            })
+            .collect();
+    }
+
+    /// Places branch targets in address pairs xxxx1/xxxx0. A node that would need to be in two different pairs
+    /// is reached through an inserted `NOP` instead.
+    fn place(mut self, entry: usize, mut jumps: Vec<ResolvedJump>, used: &[InstructionAddress]) -> Result<Vec<GeneratedInstruction>, String> {
+        let mut roles: Vec<Option<PairRole>> = vec![None; self.nodes.len()];
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        roles[entry] = Some(PairRole::Entry);
+
+        for node in 0..jumps.len() {
+            let (jump_type, mut taken, mut not_taken) = match jumps[node] {
+                ResolvedJump::Branch(jump_type, taken, not_taken) => (jump_type, taken, not_taken),
+                ResolvedJump::Goto(_) => continue
+            };
+
+            if pairs.contains(&(taken, not_taken)) {
+                continue;
+            }
+            if roles[taken].is_some() {
+                taken = self.add_trampoline(&mut jumps, &mut roles, node, taken);
+            }
+            if roles[not_taken].is_some() && roles[not_taken] != Some(PairRole::Entry) {
+                not_taken = self.add_trampoline(&mut jumps, &mut roles, node, not_taken);
+            }
+
+            roles[taken] = Some(PairRole::Odd);
+            roles[not_taken] = Some(PairRole::Even);
+            pairs.push((taken, not_taken));
+            jumps[node] = ResolvedJump::Branch(jump_type, taken, not_taken);
+        }
+
+        let mut free: Vec<bool> = (0..CONTROL_STORE_WORDS)
+            .map(|address| !used.iter().any(|used| used.to_u8() as usize == address))
+            .collect();
+        if !free[0] {
+            return Err(format!("Error in Structured Code, Description: {}",
+                ADDRESS_CONFLICT.message("Address 00000 is used by a Line with Address, but the structured Code starts there")));
+        }
+
+        let mut addresses: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        addresses[entry] = Some(0);
+        free[0] = false;
+        for (taken, not_taken) in pairs {
+            let address = match not_taken == entry {
+                true if free[1] => 0,
+                true => return Err(format!("Error in Structured Code, Description: {}",
+                    ADDRESS_CONFLICT.message("Address 00001 is used by a Line with Address, but a Branch from the structured Code needs it"))),
+                false => (0..CONTROL_STORE_WORDS).step_by(2)
+                    .find(|address| free[*address] && free[address + 1])
+                    .ok_or_else(|| self.capacity_error(used))?
+            };
+            addresses[not_taken] = Some(address);
+            addresses[taken] = Some(address + 1);
+            free[address] = false;
+            free[address + 1] = false;
+        }
+
+        // The remaining instructions follow each other in source order where possible.
+        let mut unplaced: Vec<usize> = (0..self.nodes.len()).filter(|node| addresses[*node].is_none()).collect();
+        unplaced.sort_by_key(|node| (self.nodes[*node].index, *node));
+        for node in unplaced {
+            let free_address = free.iter().position(|free| *free).ok_or_else(|| self.capacity_error(used))?;
+            addresses[node] = Some(free_address);
+            free[free_address] = false;
+        }
+
+        let address_of = |node: usize| InstructionAddress::from_u8(addresses[node].unwrap_or(0) as u8);
+        return Ok(self.nodes.iter().zip(jumps.iter())
+            .enumerate()
+            .map(|(node_index, (node, jump))| {
+                let jump_command = match *jump {
+                    ResolvedJump::Goto(target) => JumpCommand::new(JumpType::Jump, address_of(target)),
+                    ResolvedJump::Branch(jump_type, taken, _) => JumpCommand::new(jump_type, address_of(taken))
+                };
+                let instruction = node.operation.to_instruction(address_of(node_index), &jump_command);
+                GeneratedInstruction {
+                    index: node.index,
+                    instruction,
+                    comment: node.comment.clone(),
+                    location: node.location.clone()
+                }
+            })
+            .collect());
+    }
+
+    /// Adds a `NOP` that jumps to `target` and lets the branch of `node` go there instead.
+    fn add_trampoline(&mut self, jumps: &mut Vec<ResolvedJump>, roles: &mut Vec<Option<PairRole>>, node: usize, target: usize) -> usize {
+        let location = self.nodes[node].location.clone();
+        let index = self.nodes[node].index;
+        self.add_node(PseudoInstruction::NoOperation.to_operation(), NodeJump::Goto(Target::Node(target)), None, &location, index);
+        jumps.push(ResolvedJump::Goto(target));
+        roles.push(None);
+        return self.nodes.len() - 1;
+    }
+
+    fn capacity_error(&self, used: &[InstructionAddress]) -> String {
+        return format!("Error in Structured Code, Description: {}", CONTROL_STORE_FULL.message(format!(
+            "The structured Code needs {} Instructions, which do not fit into the {} free of the {} Addresses",
+            self.nodes.len(), CONTROL_STORE_WORDS - used.len(), CONTROL_STORE_WORDS
+        )));
+    }
+}
+
+/// The instruction line in front of an `if` or `while`, whose ALU result and flags the condition tests.
+fn tested_statement<'s>(statements: &'s [Statement], index: usize, location: &SourceLocation) -> Result<&'s InstructionStatement, String> {
+    return match index.checked_sub(1).map(|previous| &statements[previous]) {
+        Some(Statement::Instruction(statement)) => Ok(statement),
+        _ => Err(format!("Error in {}, Description: {}", location,
+            CONDITION_WITHOUT_INSTRUCTION.message("A Condition tests the Instruction Line right in front of it, but there is none")))
+    };
+}
+
+#[cfg(test)]
+mod structured_tests {
+    use super::*;
+
+    fn compile(program: &str, used: &[InstructionAddress]) -> Result<Vec<Instruction>, String> {
+        let mut structured_program = StructuredProgram::new();
+        for (index, line) in program.lines().enumerate() {
+            if is_structured_line(line) {
+                structured_program.process_line(line, &SourceLocation::new("test", index + 1), index, Dialect::Standard)?;
+            }
+        }
+        let mut instructions: Vec<Instruction> = structured_program.compile(used)?.into_iter()
+            .map(|generated| generated.instruction)
+            .collect();
+        instructions.sort_by_key(|instruction| instruction.address.to_u8());
+        return Ok(instructions);
+    }
+
+    fn at(instructions: &[Instruction], address: u8) -> &Instruction {
+        return instructions.iter().find(|instruction| instruction.address.to_u8() == address).unwrap();
+    }
+
+    #[test]
+    fn test_straight_line_code_loops_to_start() {
+        let instructions = compile("R0 = FC\nR0 = (R0)\nR1 = R1 + R0", &[]).unwrap();
+        let next: Vec<u8> = instructions.iter().map(|instruction| instruction.next_address.to_u8()).collect();
+        assert_eq!(vec![1, 2, 0], next);
+    }
+
+    #[test]
+    fn test_if_else_targets_are_a_pair() {
+        let program = "
+            R1 = R1 + R0; FLAGS COPY
+            if zero {
+                R2 = 01
+            } else {
+                R2 = 00
+            }
+            R3 = R2";
+        let instructions = compile(program, &[]).unwrap();
+        assert_eq!(4, instructions.len());
+
+        let test = at(&instructions, 0);
+        let taken = test.next_address.to_u8() | 1;
+        assert_eq!((true, false), (test.address_control.ac1, test.address_control.ac0));
+        assert_eq!(at(&instructions, taken).next_address, at(&instructions, taken & !1).next_address);
+    }
+
+    #[test]
+    fn test_while_and_break() {
+        let program = "
+            R0 = R0 + FF; FLAGS COPY
+            while not zero {
+                R1 = R1 + R1; FLAGS COPY
+                if carry {
+                    break
+                }
+            }
+            loop {
+            }";
+        let instructions = compile(program, &[]).unwrap();
+        let halt = instructions.iter().find(|instruction| instruction.next_address == instruction.address).unwrap();
+
+        // Zero leaves the loop, so the exit is the odd address of the pair and the body the even one.
+        let test = at(&instructions, 0);
+        assert_eq!(halt.address.to_u8(), test.next_address.to_u8() | 1);
+
+        // The exit already is in a pair with the body, so the break reaches it through a NOP.
+        let body = at(&instructions, test.next_address.to_u8() & !1);
+        let trampoline = at(&instructions, body.next_address.to_u8() | 1);
+        assert_eq!(halt.address, trampoline.next_address);
+        assert_eq!(0, body.next_address.to_u8() & !1);
+    }
+
+    #[test]
+    fn test_shared_targets_get_a_trampoline() {
+        let program = "
+            R0 = R0 + R1; FLAGS COPY
+            if zero {
+                R1 = R1 + R1; FLAGS COPY
+                if negative {
+                    R2 = 00
+                }
+            }
+            R3 = 00";
+        let instructions = compile(program, &[]).unwrap();
+        assert_eq!(5, instructions.len());
+        assert_eq!(1, instructions.iter().filter(|instruction| instruction.alu_function.to_u8() == 0).count());
+    }
+
+    #[test]
+    fn test_statements_after_break_get_no_address() {
+        let instructions = compile("loop {\nR0 = 01\nbreak\nR0 = 02\n}\nR1 = 01", &[]).unwrap();
+        assert_eq!(2, instructions.len());
+    }
+
+    #[test]
+    fn test_structured_lines_are_recognised_by_their_content() {
+        assert!(is_structured_line("R0 = R0 + 01; FLAGS COPY # count"));
+        assert!(is_structured_line("IN R1, [R0]"));
+        assert!(is_structured_line("} else {"));
+        assert!(is_structured_line("PASSB R0 0001; WRITE A; BUS OFF; FLAGS KEEP"));
+        assert!(!is_structured_line("# comment"));
+        assert!(!is_structured_line("00000 NOP; JUMP 00001"));
+        assert!(!is_structured_line("PASSB R0 0001; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001"));
+        assert!(!is_structured_line("R0 = 01; goto 00001"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(compile("if zero {\nNOP\n}", &[]).is_err());
+        assert!(compile("NOP\nbreak", &[]).is_err());
+        assert!(compile("loop {\nNOP", &[]).is_err());
+        assert!(compile("NOP\n}", &[]).is_err());
+        assert!(compile("NOP", &[InstructionAddress::from_u8(0)]).is_err());
+
+        let too_long = "NOP\n".repeat(CONTROL_STORE_WORDS + 1);
+        assert!(compile(&too_long, &[]).unwrap_err().contains("33 Instructions"));
+    }
+}
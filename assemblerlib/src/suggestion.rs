@@ -0,0 +1,313 @@
+use crate::dialect::{Dialect, find_mnemonic, parse_dialect_directive};
+use std::convert::TryFrom;
+use crate::parsing::{parse_line, parse_operation_commands, RegisterOrConstant};
+use crate::pseudo::PSEUDO_MNEMONICS;
+use crate::structured::STRUCTURED_KEYWORDS;
+
+/// The number of single character insertions, deletions and substitutions that turn one word into the other, ignoring case.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_ascii_uppercase().chars().collect();
+    let b: Vec<char> = b.to_ascii_uppercase().chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + (a_char != b_char) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    return previous[b.len()];
+}
+
+/// The known spelling closest to a misspelled word, if it is close enough to be a typo and no other spelling is as close.
+/// Case and whitespace are ignored, so `WRITEA` suggests `WRITE A` and `bus off` suggests `BUS OFF`.
+pub(crate) fn closest<'k, I: IntoIterator<Item = &'k str>>(word: &str, known: I) -> Option<&'k str> {
+    let normalized = normalize(word);
+    if normalized.is_empty() {
+        return None;
+    }
+    let allowed_distance = (normalized.len() / 3).max(1);
+
+    let mut best: Option<(usize, &'k str)> = None;
+    let mut tied = false;
+    for candidate in known {
+        if candidate == word.trim() {
+            return None;
+        }
+        let distance = edit_distance(&normalized, &normalize(candidate));
+        match best {
+            Some((best_distance, best_candidate)) if distance == best_distance && normalize(best_candidate) != normalize(candidate) => tied = true,
+            Some((best_distance, _)) if distance >= best_distance => {},
+            _ => {
+                best = Some((distance, candidate));
+                tied = false;
+            }
+        }
+    }
+
+    return match best {
+        Some((distance, candidate)) if distance <= allowed_distance && !tied => Some(candidate),
+        _ => None
+    };
+}
+
+/// The end of a message that proposes the suggestion, empty if there is none.
+pub(crate) fn did_you_mean(suggestion: Option<&str>) -> String {
+    return match suggestion {
+        Some(suggestion) => format!(", did you mean {}?", suggestion),
+        None => String::new()
+    };
+}
+
+/// The ALU mnemonic or pseudo instruction the first words of a command were probably meant to be.
+/// Two words that form a mnemonic when joined, like `ADDS C`, are preferred over a typo in the first word.
+pub(crate) fn suggest_alu_mnemonic(words: &[&str], dialect: Dialect) -> Option<&'static str> {
+    return joined_alu_mnemonic(words, dialect).or_else(|| words.first().and_then(|word| closest(word,
+        dialect.alu_mnemonics().iter().map(|(name, _)| *name).chain(PSEUDO_MNEMONICS.iter().copied()))));
+}
+
+/// The ALU mnemonic spelled by the first two words without the space between them, if the second word is no operand.
+pub(crate) fn joined_alu_mnemonic(words: &[&str], dialect: Dialect) -> Option<&'static str> {
+    if words.len() < 2 || RegisterOrConstant::try_from(words[1]).is_ok() {
+        return None;
+    }
+    let joined = words[..2].concat();
+    return dialect.alu_mnemonics().iter().map(|(name, _)| *name).find(|name| *name == joined);
+}
+
+fn normalize(word: &str) -> String {
+    return word.chars().filter(|ch| !ch.is_whitespace()).collect::<String>().to_ascii_uppercase();
+}
+
+/// A line that `fix_source` rewrote, numbered from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    pub line: usize,
+    pub before: String,
+    pub after: String
+}
+
+/// Rewrites the instruction lines that do not parse, but do after fixing misspelled mnemonics, missing `;` separators
+/// or the order of the commands. Every other line, and lines that still do not parse after fixing, stay as they are.
+pub fn fix_source(source: &str, dialect: Dialect) -> (String, Vec<Fix>) {
+    let mut dialect = dialect;
+    let mut fixes = Vec::new();
+    let mut fixed_source = String::new();
+
+    // Every line keeps its own line ending, so files with CRLF stay CRLF.
+    for (i, source_line) in source.split_inclusive('\n').enumerate() {
+        let line = source_line.strip_suffix('\n').unwrap_or(source_line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(Ok(selected_dialect)) = parse_dialect_directive(line) {
+            dialect = selected_dialect;
+        }
+
+        let fixed_line = fix_line(line, dialect);
+        if let Some(fixed_line) = &fixed_line {
+            fixes.push(Fix { line: i + 1, before: line.to_string(), after: fixed_line.clone() });
+        }
+        fixed_source.push_str(fixed_line.as_deref().unwrap_or(line));
+        fixed_source.push_str(&source_line[line.len()..]);
+    }
+    return (fixed_source, fixes);
+}
+
+/// The fixed line, or `None` if the line parses already or can not be fixed.
+fn fix_line(line: &str, dialect: Dialect) -> Option<String> {
+    let (code, comment) = match line.split_once('#') {
+        Some((code, comment)) => (code, Some(comment)),
+        None => (line, None)
+    };
+    let indentation = &code[..code.len() - code.trim_start().len()];
+    let first_word = code.split_whitespace().next()?;
+    if first_word.starts_with('.') || first_word == "use" || STRUCTURED_KEYWORDS.contains(&first_word) {
+        return None;
+    }
+
+    let (address, commands) = match code.split_once(':') {
+        Some((address, commands)) => (Some(address.trim()), commands),
+        None => (None, code)
+    };
+    if parses(address, commands, dialect) {
+        return None;
+    }
+
+    let fixed_commands = fix_commands(commands, address.is_some(), dialect);
+    if fixed_commands == commands.trim() || !parses(address, &fixed_commands, dialect) {
+        return None;
+    }
+
+    let mut fixed_line = match address {
+        Some(address) => format!("{}{}: {}", indentation, address, fixed_commands),
+        None => format!("{}{}", indentation, fixed_commands)
+    };
+    if let Some(comment) = comment {
+        fixed_line.push_str(&format!(" #{}", comment));
+    }
+    return Some(fixed_line);
+}
+
+fn parses(address: Option<&str>, commands: &str, dialect: Dialect) -> bool {
+    return match address {
+        Some(address) => parse_line(&format!("{}: {}", address, commands), dialect).is_ok(),
+        None => parse_operation_commands(commands, dialect).is_ok()
+    };
+}
+
+fn fix_commands(commands: &str, has_jump: bool, dialect: Dialect) -> String {
+    let mut segments = split_missing_separators(commands, dialect);
+    let jump = match has_jump {
+        true => segments.pop().map(|jump| fix_jump(&jump, dialect)),
+        false => None
+    };
+
+    let mut segments: Vec<String> = segments.iter().map(|segment| fix_command(segment, dialect)).collect();
+    reorder_commands(&mut segments, dialect);
+    segments.extend(jump);
+    return segments.join("; ");
+}
+
+/// Splits the commands at `;` and in front of words that start a write, bus, flag or jump command without a `;` before them.
+fn split_missing_separators(commands: &str, dialect: Dialect) -> Vec<String> {
+    let starts: Vec<&str> = dialect.write_mnemonics().iter().map(|(name, _)| *name)
+        .chain(dialect.bus_mnemonics().iter().map(|(name, _)| *name))
+        .chain(dialect.flag_mnemonics().iter().map(|(name, _)| *name))
+        .chain(dialect.jump_mnemonics().iter().map(|(name, _)| *name))
+        .filter_map(|name| name.split(' ').next())
+        .chain(["goto", "if"])
+        .collect();
+
+    let mut segments = Vec::new();
+    for segment in commands.split(';') {
+        let mut words: Vec<&str> = Vec::new();
+        for word in segment.split_whitespace() {
+            // Jumps in transfer notation and mnemonics like `TEST CF JMP` contain words that start other commands.
+            let inside_jump = matches!(words.first(), Some(&"goto") | Some(&"if"));
+            let continues_mnemonic = dialect.jump_mnemonics().iter()
+                .any(|(name, _)| name.split(' ').take(words.len() + 1).eq(words.iter().copied().chain([word])));
+            if starts.contains(&word) && !words.is_empty() && !inside_jump && !continues_mnemonic {
+                segments.push(words.join(" "));
+                words.clear();
+            }
+            words.push(word);
+        }
+        segments.push(words.join(" "));
+    }
+    return segments;
+}
+
+/// Replaces a misspelled write, bus or flag command, or a misspelled ALU mnemonic or pseudo instruction in front of operands.
+fn fix_command(command: &str, dialect: Dialect) -> String {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    if words.is_empty() || command.contains('=') {
+        return command.to_string();
+    }
+
+    let whole_commands = || dialect.write_mnemonics().iter().map(|(name, _)| *name)
+        .chain(dialect.bus_mnemonics().iter().map(|(name, _)| *name))
+        .chain(dialect.flag_mnemonics().iter().map(|(name, _)| *name));
+    if whole_commands().any(|name| name.split(' ').eq(words.iter().copied())) {
+        return command.to_string();
+    }
+    if let Some(name) = closest(command, whole_commands()) {
+        return name.to_string();
+    }
+
+    if let Some(name) = joined_alu_mnemonic(&words, dialect) {
+        return [&[name], &words[2..]].concat().join(" ");
+    }
+    let is_known = |name: &str| name.split(' ').zip(words.iter()).filter(|(part, word)| part == *word).count() == name.split(' ').count();
+    if dialect.alu_mnemonics().iter().map(|(name, _)| *name).chain(PSEUDO_MNEMONICS.iter().copied()).any(is_known) {
+        return command.to_string();
+    }
+
+    return match suggest_alu_mnemonic(&words, dialect) {
+        Some(name) => [&[name], &words[1..]].concat().join(" "),
+        None => command.to_string()
+    };
+}
+
+fn fix_jump(jump: &str, dialect: Dialect) -> String {
+    let words: Vec<&str> = jump.split_whitespace().collect();
+    return match words.split_last() {
+        Some((address, mnemonic)) if !mnemonic.is_empty() && !matches!(words[0], "goto" | "if")
+            && find_mnemonic(dialect.jump_mnemonics(), mnemonic).is_none() =>
+            match closest(&mnemonic.join(" "), dialect.jump_mnemonics().iter().map(|(name, _)| *name)) {
+                Some(name) => format!("{} {}", name, address),
+                None => jump.to_string()
+            },
+        _ => jump.to_string()
+    };
+}
+
+/// Puts the four commands in front of the jump into the order ALU, write, bus, flag,
+/// and a flag command in front of a register transfer behind it.
+fn reorder_commands(segments: &mut [String], dialect: Dialect) {
+    let role = |segment: &String| {
+        let words: Vec<&str> = segment.split_whitespace().collect();
+        return match () {
+            _ if find_mnemonic(dialect.write_mnemonics(), &words).is_some() => 1,
+            _ if find_mnemonic(dialect.bus_mnemonics(), &words).is_some() => 2,
+            _ if find_mnemonic(dialect.flag_mnemonics(), &words).is_some() => 3,
+            _ => 0
+        };
+    };
+
+    let mut roles: Vec<usize> = segments.iter().map(role).collect();
+    roles.sort_unstable();
+    let expected: &[usize] = match segments.len() {
+        4 => &[0, 1, 2, 3],
+        2 if segments.iter().any(|segment| segment.contains('=')) => &[0, 3],
+        _ => return
+    };
+    if roles == expected {
+        segments.sort_by_key(role);
+    }
+}
+
+#[cfg(test)]
+mod suggestion_tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_known_spelling() {
+        let known = ["BHOLDC", "BSETC", "WRITE A", "WRITE B"];
+        assert_eq!(1, edit_distance("BHOLD", "bholdc"));
+        assert_eq!(Some("BHOLDC"), closest("BHOLD", known));
+        assert_eq!(Some("WRITE A"), closest("WRITEA", known));
+        assert_eq!(None, closest("WRITE C", known));
+        assert_eq!(None, closest("BSETC", known));
+        assert_eq!(None, closest("XYZ", known));
+        assert_eq!(Some("ADDSC"), suggest_alu_mnemonic(&["ADDS", "C", "R0", "R1"], Dialect::Standard));
+    }
+
+    #[test]
+    fn test_fix_source() {
+        let source = "# older sheets list BHOLD\n\
+            00000: BHOLD R0 R1; WRITEA; BUS OFF; FLAGS KEEP; JUMP 00001 # load\n\
+            00001: ADDS C R0 R1 WRITE A; BUS OFF; FLAGS KEEP; JUMPP 00010\n\
+            00010: ADD R0 R1; FLAGS COPY; WRITE A; BUS OFF; JUMP 00011\n\
+            R1 = R1 + 01 FLAGS COPY\n\
+            00011: R0 = TABLE; goto 00000";
+        let (fixed, fixes) = fix_source(source, Dialect::Standard);
+
+        assert_eq!(vec![2, 3, 4, 5], fixes.iter().map(|fix| fix.line).collect::<Vec<usize>>());
+        assert_eq!("# older sheets list BHOLD\n\
+            00000: BHOLDC R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001 # load\n\
+            00001: ADDSC R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00010\n\
+            00010: ADD R0 R1; WRITE A; BUS OFF; FLAGS COPY; JUMP 00011\n\
+            R1 = R1 + 01; FLAGS COPY\n\
+            00011: R0 = TABLE; goto 00000", fixed);
+    }
+
+    #[test]
+    fn test_fix_keeps_line_endings() {
+        let source = "# load\r\n00000: BHOLD R0 R1; WRITEA; BUS OFF; FLAGS KEEP; JUMP 00001\r\nNOP\n";
+        let (fixed, fixes) = fix_source(source, Dialect::Standard);
+
+        assert_eq!(1, fixes.len());
+        assert_eq!("# load\r\n00000: BHOLDC R0 R1; WRITE A; BUS OFF; FLAGS KEEP; JUMP 00001\r\nNOP\n", fixed);
+    }
+}
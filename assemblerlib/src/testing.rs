@@ -0,0 +1,240 @@
+use crate::coverage::Coverage;
+use crate::debugger::{parse_byte, Target};
+use crate::diagnostics::INVALID_TEST_CASE;
+use crate::emulator::{Cycle, Emulator, MachineState};
+use crate::source::SourceLocation;
+use crate::translated::TranslatedProgram;
+
+const TEST_PREFIX: &str = "TEST";
+
+/// The most cycles a test case runs for when it does not say `within N cycles`.
+pub const DEFAULT_TEST_CYCLES: u64 = 1000;
+
+/// A `# TEST name: SETUP -> EXPECTED within N cycles` comment. SETUP and EXPECTED are `TARGET=VALUE` pairs
+/// separated by whitespace, with the targets of the debugger like `R3`, `CF` or `mem[FC]` and hexadecimal values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub location: SourceLocation,
+    pub setup: Vec<(Target, u8)>,
+    pub expected: Vec<(Target, u8)>,
+    pub cycles: u64
+}
+
+/// How a test case ended.
+pub struct TestResult {
+    pub passed: bool,
+    pub cycles: u64,
+    pub state: MachineState,
+    /// Every expected value with the actual value at the end.
+    pub values: Vec<(Target, u8, u8)>,
+    /// Set if a cycle could not be executed before the expected values held, such a case never passes.
+    pub error: Option<String>,
+    pub coverage: Coverage
+}
+
+/// Reads the test cases of a source, `name` is used for their locations and for error messages.
+pub fn parse_test_cases(name: &str, source: &str, program: &TranslatedProgram) -> Result<Vec<TestCase>, String> {
+    let mut test_cases = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let comment = match line.split_once('#') {
+            Some((_, comment)) => comment.trim(),
+            None => continue
+        };
+        let is_test = matches!(comment.get(..TEST_PREFIX.len()), Some(prefix) if prefix.eq_ignore_ascii_case(TEST_PREFIX))
+            && comment[TEST_PREFIX.len()..].starts_with(char::is_whitespace);
+        if !is_test {
+            continue;
+        }
+
+        let location = SourceLocation::new(name, index + 1);
+        let test_case = parse_test_case(&comment[TEST_PREFIX.len()..], location.clone(), program)
+            .map_err(|error| format!("Error in {}, Description: {}", location, INVALID_TEST_CASE.message(error)))?;
+        test_cases.push(test_case);
+    }
+    return Ok(test_cases);
+}
+
+fn parse_test_case(text: &str, location: SourceLocation, program: &TranslatedProgram) -> Result<TestCase, String> {
+    let (name, text) = text.split_once(':')
+        .ok_or_else(|| "A Test Case is written like TEST name: mem[FC]=3 -> mem[FE]=8 within 200 cycles".to_string())?;
+    let (setup, expected) = text.split_once("->")
+        .ok_or_else(|| format!("The Test Case {} has no -> between the Setup and the expected Values", name.trim()))?;
+
+    let mut words: Vec<&str> = expected.split_whitespace().collect();
+    let cycles = match words.as_slice() {
+        [.., within, cycles, unit] if within.eq_ignore_ascii_case("within") && unit.to_ascii_lowercase().starts_with("cycle") => {
+            let cycles = cycles.parse().map_err(|_| format!("Invalid Number of Cycles {}", cycles))?;
+            words.truncate(words.len() - 3);
+            cycles
+        },
+        _ => DEFAULT_TEST_CYCLES
+    };
+    if words.is_empty() {
+        return Err(format!("The Test Case {} expects no Values", name.trim()));
+    }
+
+    let assignments = |words: &[&str]| -> Result<Vec<(Target, u8)>, String> {
+        return words.iter()
+            .map(|word| {
+                let (target, value) = word.split_once('=')
+                    .ok_or_else(|| format!("Expected TARGET=VALUE, got {}", word))?;
+                return Ok((Target::parse(target, &program.symbols)?, parse_byte(value, &program.symbols)?));
+            })
+            .collect();
+    };
+
+    return Ok(TestCase {
+        name: name.trim().to_string(),
+        location,
+        setup: assignments(&setup.split_whitespace().collect::<Vec<&str>>())?,
+        expected: assignments(&words)?,
+        cycles
+    });
+}
+
+/// Runs the program from the start with the setup applied. The case passes when every expected value is there after
+/// a cycle that wrote one of them, so programs that loop forever can be tested, or when the program halts or the cycles
+/// are used up. At least one cycle is executed, values that are there from the start do not pass on their own.
+pub fn run_test_case(program: &TranslatedProgram, test_case: &TestCase) -> TestResult {
+    let mut emulator = Emulator::new(program);
+    for (target, value) in &test_case.setup {
+        target.set(&mut emulator.state, *value);
+    }
+
+    let holds = |state: &MachineState| test_case.expected.iter().all(|(target, value)| target.value(state) == *value);
+    let mut passed = false;
+    let mut error = None;
+    let mut coverage = Coverage::new();
+    while emulator.state.cycles < test_case.cycles && (emulator.state.cycles == 0 || !emulator.is_halted()) {
+        match emulator.step() {
+            Ok(cycle) => {
+                coverage.record(&cycle);
+                if test_case.expected.iter().any(|(target, _)| writes(&cycle, target)) && holds(&emulator.state) {
+                    passed = true;
+                    break;
+                }
+            },
+            Err(step_error) => {
+                error = Some(step_error);
+                break;
+            }
+        }
+    }
+    if !passed && error.is_none() && emulator.state.cycles > 0 {
+        passed = holds(&emulator.state);
+    }
+
+    return TestResult {
+        passed,
+        cycles: emulator.state.cycles,
+        values: test_case.expected.iter()
+            .map(|(target, value)| (*target, *value, target.value(&emulator.state)))
+            .collect(),
+        state: emulator.state,
+        error,
+        coverage
+    };
+}
+
+/// Whether the cycle wrote the target, the flags are written by every cycle with MCHFLG.
+fn writes(cycle: &Cycle, target: &Target) -> bool {
+    return match target {
+        Target::Register(register) => cycle.written_register == Some(*register),
+        Target::CarryFlag | Target::ZeroFlag | Target::NegativeFlag => cycle.instruction.alu_control.cf,
+        Target::Bus(address) => matches!(cycle.bus_write, Some((written, _)) if written == *address)
+    };
+}
+
+/// The result line of a test case, for failures followed by the expected and actual values that differ,
+/// the error and the final machine state.
+pub fn format_test_result(test_case: &TestCase, result: &TestResult) -> String {
+    if result.passed {
+        return format!("PASS {} ({}) in {} Cycles\n", test_case.name, test_case.location, result.cycles);
+    }
+
+    let reason = match (&result.error, result.cycles >= test_case.cycles) {
+        (Some(_), _) => "stopped",
+        (None, true) => "ran out of Cycles",
+        (None, false) => "halted"
+    };
+    let mut text = format!("FAIL {} ({}) {} after {} Cycles\n", test_case.name, test_case.location, reason, result.cycles);
+    for (target, expected, actual) in result.values.iter().filter(|(_, expected, actual)| expected != actual) {
+        text.push_str(&format!("- {}={:02X}\n+ {}={:02X}\n", target, expected, target, actual));
+    }
+    if let Some(error) = &result.error {
+        text.push_str(&format!("{}\n", error));
+    }
+    for line in result.state.to_string().lines() {
+        text.push_str(&format!("    {}\n", line));
+    }
+    return text;
+}
+
+#[cfg(test)]
+mod testing_tests {
+    use super::*;
+    use crate::Assembler;
+
+    const PROGRAM: &str = "# TEST add: mem[FC]=3 mem[FD]=5 -> mem[FE]=8 within 20 cycles\n\
+        # test wrong: (FC)=1 (FD)=1 -> (FE)=3\n\
+        # TEST slow: (FC)=1 (FD)=1 -> (FE)=2 within 3 cycles\n\
+        loop {\n\
+            R0 = FC\n\
+            R1 = (R0)\n\
+            R0 = R0 + 01\n\
+            R1 = (R0) + R1\n\
+            R0 = R0 + 01\n\
+            (R0) = R1\n\
+        }";
+
+    #[test]
+    fn test_cases_pass_and_fail() {
+        let program = Assembler::new().translate_program(PROGRAM).unwrap();
+        let test_cases = parse_test_cases("add.2ia", PROGRAM, &program).unwrap();
+        assert_eq!(3, test_cases.len());
+        assert_eq!((20, DEFAULT_TEST_CYCLES), (test_cases[0].cycles, test_cases[1].cycles));
+
+        let result = run_test_case(&program, &test_cases[0]);
+        assert!(result.passed);
+        assert_eq!(6, result.cycles);
+        assert_eq!("PASS add (add.2ia:1) in 6 Cycles\n", format_test_result(&test_cases[0], &result));
+        assert_eq!(6, result.coverage.executions.iter().sum::<u64>());
+
+        let result = run_test_case(&program, &test_cases[1]);
+        assert!(!result.passed);
+        assert_eq!(DEFAULT_TEST_CYCLES, result.cycles);
+        assert!(format_test_result(&test_cases[1], &result).starts_with("FAIL wrong (add.2ia:2) ran out of Cycles after 1000 Cycles\n- (FE)=03\n+ (FE)=02\n"));
+
+        assert!(!run_test_case(&program, &test_cases[2]).passed);
+    }
+
+    #[test]
+    fn test_values_from_the_start_do_not_pass() {
+        let source = format!("# TEST z: -> mem[FE]=00\n# TEST one: mem[FC]=1 -> mem[FE]=00 within 20 cycles\n{}", PROGRAM);
+        let program = Assembler::new().translate_program(&source).unwrap();
+        let test_cases = parse_test_cases("add.2ia", &source, &program).unwrap();
+
+        let result = run_test_case(&program, &test_cases[0]);
+        assert!(result.passed);
+        assert_eq!(6, result.cycles);
+        let result = run_test_case(&program, &test_cases[1]);
+        assert!(!result.passed);
+        assert_eq!(20, result.cycles);
+
+        let halting = "# TEST halt: -> R0=00\nloop {\n}";
+        let program = Assembler::new().translate_program(halting).unwrap();
+        let result = run_test_case(&program, &parse_test_cases("halt.2ia", halting, &program).unwrap()[0]);
+        assert_eq!((true, 1), (result.passed, result.cycles));
+    }
+
+    #[test]
+    fn test_invalid_test_cases() {
+        let parse = |source: &str| parse_test_cases("example.2ia", source, &Assembler::new().translate_program(source).unwrap());
+        assert!(parse(INVALID_TEST_CASE.wrong).unwrap_err().contains(INVALID_TEST_CASE.code));
+        assert_eq!(1, parse(INVALID_TEST_CASE.right).unwrap().len());
+        assert!(parse("# TEST a: R8=1 -> R0=1").is_err());
+        assert!(parse("# TEST a: R1=1 -> R0=1 within many cycles").is_err());
+        assert!(parse("# TESTS are below").unwrap().is_empty());
+    }
+}
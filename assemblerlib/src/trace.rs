@@ -0,0 +1,259 @@
+use crate::emulator::{Cycle, Emulator, MachineState};
+use crate::translated::TranslatedProgram;
+
+/// The length of one cycle in the time unit of a Value Change Dump, the clock is high for the first half.
+const VCD_CYCLE_TIME: u64 = 10;
+const VCD_TIMESCALE: &str = "1ns";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    Csv,
+    Vcd
+}
+
+impl TraceFormat {
+    pub const ALL: [TraceFormat; 2] = [TraceFormat::Csv, TraceFormat::Vcd];
+
+    pub fn name(self) -> &'static str {
+        return match self {
+            TraceFormat::Csv => "csv",
+            TraceFormat::Vcd => "vcd"
+        };
+    }
+
+    pub fn from_name(name: &str) -> Result<TraceFormat, String> {
+        return TraceFormat::ALL.iter()
+            .find(|format| format.name() == name)
+            .copied()
+            .ok_or_else(|| format!("Unknown Trace Format {}", name));
+    }
+
+    /// The file type of traces in this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        return self.name();
+    }
+}
+
+/// One executed cycle with the machine state at its end.
+pub struct TraceStep {
+    pub cycle: Cycle,
+    pub state: MachineState
+}
+
+/// The cycles a program executed, from the state it started in.
+pub struct Trace {
+    pub initial: MachineState,
+    pub steps: Vec<TraceStep>
+}
+
+/// Where the value of a signal comes from.
+#[derive(Copy, Clone)]
+enum SignalSource {
+    /// A control signal or ALU value, it is valid during the cycle.
+    Cycle(fn(&Cycle) -> u32),
+    /// A register or flag, it changes with the clock edge at the end of the cycle.
+    State(fn(&MachineState) -> u32)
+}
+
+#[derive(Copy, Clone)]
+struct Signal {
+    name: &'static str,
+    width: usize,
+    source: SignalSource
+}
+
+const fn cycle_signal(name: &'static str, width: usize, value: fn(&Cycle) -> u32) -> Signal {
+    return Signal { name, width, source: SignalSource::Cycle(value) };
+}
+
+const fn state_signal(name: &'static str, width: usize, value: fn(&MachineState) -> u32) -> Signal {
+    return Signal { name, width, source: SignalSource::State(value) };
+}
+
+/// The signals of a trace, named like the fields and flags in `2iLanguage.txt`.
+const SIGNALS: [Signal; 32] = [
+    cycle_signal("ADDRESS", 5, |cycle| cycle.instruction.address.to_u8() as u32),
+    cycle_signal("MAC", 2, |cycle| (cycle.instruction.address_control.ac1 as u32) << 1 | cycle.instruction.address_control.ac0 as u32),
+    cycle_signal("NA", 5, |cycle| cycle.instruction.next_address.to_u8() as u32),
+    cycle_signal("BUSWR", 1, |cycle| cycle.instruction.bus_control.bus_wr as u32),
+    cycle_signal("BUSEN", 1, |cycle| cycle.instruction.bus_control.bus_en as u32),
+    cycle_signal("MRGAA", 3, |cycle| cycle.instruction.content_a.to_u8() as u32),
+    cycle_signal("MRGAB", 4, |cycle| cycle.instruction.content_b.to_u8() as u32),
+    cycle_signal("MRGWS", 1, |cycle| cycle.instruction.register_control.rws as u32),
+    cycle_signal("MRGWE", 1, |cycle| cycle.instruction.register_control.rwe as u32),
+    cycle_signal("MALUIA", 1, |cycle| cycle.instruction.register_address_control.aca as u32),
+    cycle_signal("MALUIB", 1, |cycle| cycle.instruction.register_address_control.acb as u32),
+    cycle_signal("MALUS", 4, |cycle| cycle.instruction.alu_function.to_u8() as u32),
+    cycle_signal("MCHFLG", 1, |cycle| cycle.instruction.alu_control.cf as u32),
+    cycle_signal("A", 8, |cycle| cycle.a as u32),
+    cycle_signal("B", 8, |cycle| cycle.b as u32),
+    cycle_signal("F", 8, |cycle| cycle.output.result as u32),
+    cycle_signal("CO", 1, |cycle| cycle.output.carry as u32),
+    cycle_signal("ZO", 1, |cycle| cycle.output.zero as u32),
+    cycle_signal("NO", 1, |cycle| cycle.output.negative as u32),
+    cycle_signal("BUSADDR", 8, |cycle| cycle.bus_read.or(cycle.bus_write).map_or(0, |(address, _)| address as u32)),
+    cycle_signal("BUSDATA", 8, |cycle| cycle.bus_read.or(cycle.bus_write).map_or(0, |(_, value)| value as u32)),
+    state_signal("R0", 8, |state| state.registers[0] as u32),
+    state_signal("R1", 8, |state| state.registers[1] as u32),
+    state_signal("R2", 8, |state| state.registers[2] as u32),
+    state_signal("R3", 8, |state| state.registers[3] as u32),
+    state_signal("R4", 8, |state| state.registers[4] as u32),
+    state_signal("R5", 8, |state| state.registers[5] as u32),
+    state_signal("R6", 8, |state| state.registers[6] as u32),
+    state_signal("R7", 8, |state| state.registers[7] as u32),
+    state_signal("CF", 1, |state| state.carry_flag as u32),
+    state_signal("ZF", 1, |state| state.zero_flag as u32),
+    state_signal("NF", 1, |state| state.negative_flag as u32)
+];
+
+/// Runs the program like `Emulator::run` and records every cycle.
+pub fn record_trace(program: &TranslatedProgram, cycles: u64) -> Result<Trace, String> {
+    let mut emulator = Emulator::new(program);
+    let mut trace = Trace { initial: emulator.state.clone(), steps: Vec::new() };
+    for _ in 0..cycles {
+        if emulator.is_halted() {
+            break;
+        }
+        let cycle = emulator.step()?;
+        trace.steps.push(TraceStep { cycle, state: emulator.state.clone() });
+    }
+    return Ok(trace);
+}
+
+pub fn format_trace(trace: &Trace, format: TraceFormat) -> String {
+    return match format {
+        TraceFormat::Csv => format_csv(trace),
+        TraceFormat::Vcd => format_vcd(trace)
+    };
+}
+
+/// Bytes are written in hexadecimal, narrower signals in binary like the fields of a `.2i` file.
+fn format_value(value: u32, width: usize) -> String {
+    return match width {
+        8 => format!("{:02X}", value),
+        _ => format!("{:0width$b}", value, width = width)
+    };
+}
+
+/// One row per cycle, registers and flags are the values at the end of the cycle.
+fn format_csv(trace: &Trace) -> String {
+    let mut csv = String::from("Cycle");
+    for signal in &SIGNALS {
+        csv.push(',');
+        csv.push_str(signal.name);
+    }
+    csv.push('\n');
+
+    for (index, step) in trace.steps.iter().enumerate() {
+        csv.push_str(&(index + 1).to_string());
+        for signal in &SIGNALS {
+            let value = match signal.source {
+                SignalSource::Cycle(value) => value(&step.cycle),
+                SignalSource::State(value) => value(&step.state)
+            };
+            csv.push(',');
+            csv.push_str(&format_value(value, signal.width));
+        }
+        csv.push('\n');
+    }
+    return csv;
+}
+
+/// The printable identifier of a signal in the dump, `CLK` comes first.
+fn vcd_identifier(index: usize) -> char {
+    return (b'!' + index as u8) as char;
+}
+
+fn vcd_change(value: u32, width: usize, identifier: char) -> String {
+    return match width {
+        1 => format!("{}{}\n", value, identifier),
+        _ => format!("b{:b} {}\n", value, identifier)
+    };
+}
+
+/// A dump with a clock and every signal. Cycle n starts with the rising clock edge at time n * 10,
+/// its control signals and ALU values change there, its results in the registers and flags change at the end of the cycle.
+fn format_vcd(trace: &Trace) -> String {
+    let mut vcd = format!("$version Assembler2i $end\n$timescale {} $end\n$scope module 2i $end\n", VCD_TIMESCALE);
+    vcd.push_str(&format!("$var wire 1 {} CLK $end\n", vcd_identifier(0)));
+    for (index, signal) in SIGNALS.iter().enumerate() {
+        let range = match signal.width {
+            1 => String::new(),
+            width => format!(" [{}:0]", width - 1)
+        };
+        vcd.push_str(&format!("$var wire {} {} {}{} $end\n", signal.width, vcd_identifier(index + 1), signal.name, range));
+    }
+    vcd.push_str("$upscope $end\n$enddefinitions $end\n");
+
+    let mut values: Vec<Option<u32>> = vec![None; SIGNALS.len()];
+    for time in 0..=trace.steps.len() {
+        let cycle = trace.steps.get(time).map(|step| &step.cycle);
+        let state = match time {
+            0 => &trace.initial,
+            _ => &trace.steps[time - 1].state
+        };
+
+        let mut changes = String::new();
+        for (index, signal) in SIGNALS.iter().enumerate() {
+            let value = match (signal.source, cycle) {
+                (SignalSource::Cycle(value), Some(cycle)) => value(cycle),
+                // The control signals keep their last value after the last cycle.
+                (SignalSource::Cycle(_), None) => continue,
+                (SignalSource::State(value), _) => value(state)
+            };
+            if values[index] != Some(value) {
+                values[index] = Some(value);
+                changes.push_str(&vcd_change(value, signal.width, vcd_identifier(index + 1)));
+            }
+        }
+
+        let start = time as u64 * VCD_CYCLE_TIME;
+        let clock = match cycle {
+            Some(_) => 1,
+            None => 0
+        };
+        vcd.push_str(&format!("#{}\n{}", start, vcd_change(clock, 1, vcd_identifier(0))));
+        vcd.push_str(&changes);
+        if cycle.is_some() {
+            vcd.push_str(&format!("#{}\n{}", start + VCD_CYCLE_TIME / 2, vcd_change(0, 1, vcd_identifier(0))));
+        }
+    }
+    return vcd;
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use super::*;
+    use crate::Assembler;
+
+    fn trace() -> Trace {
+        let program = Assembler::new().translate_program("R1 = 05\nR1 = R1 + FF; FLAGS COPY\nloop {\n}").unwrap();
+        return record_trace(&program, 100).unwrap();
+    }
+
+    #[test]
+    fn test_csv_has_a_row_per_cycle() {
+        let csv = format_trace(&trace(), TraceFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(3, lines.len());
+        assert!(lines[0].starts_with("Cycle,ADDRESS,MAC,NA,BUSWR,BUSEN,MRGAA,MRGAB"));
+        assert!(lines[0].ends_with(",R7,CF,ZF,NF"));
+        assert_eq!(SIGNALS.len() + 1, lines[1].split(',').count());
+        assert!(lines[1].starts_with("1,00000,00,00001,0,0,"));
+        assert!(lines[2].ends_with(",04,00,00,00,00,00,00,1,0,0"));
+    }
+
+    #[test]
+    fn test_vcd_dumps_changes() {
+        let vcd = format_trace(&trace(), TraceFormat::Vcd);
+
+        assert!(vcd.contains("$var wire 1 ! CLK $end\n$var wire 5 \" ADDRESS [4:0] $end\n"));
+        assert!(vcd.contains("$enddefinitions $end\n#0\n1!\n"));
+        // R1 is written at the end of the first cycle and decremented at the end of the second.
+        assert!(vcd.contains("#10\n1!\nb1 \"\n"));
+        assert!(vcd.contains("b101 8\n"));
+        assert!(vcd.contains("#20\n0!\nb100 8\n1?\n"));
+        assert!(!vcd.contains("#25"));
+    }
+}
@@ -1,17 +1,69 @@
-use crate::instruction::Instruction;
+use crate::diagnostics::{INVALID_ADDRESS, INVALID_WORD, MISSING_ADDRESS};
+use crate::expression::SymbolTable;
+use crate::instruction::{Instruction, InstructionAddress, INSTRUCTION_WORD_BITS};
+use crate::memory::MemoryImage;
+use crate::metadata::ProgramMetadata;
+use crate::source::SourceLocation;
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
 use std::fmt;
 
 pub struct TranslatedProgram {
-    pub lines: Vec<TranslatedLine>
+    pub lines: Vec<TranslatedLine>,
+    pub locations: Vec<SourceLocation>,
+    /// The `.data` bytes of the program, written as a separate memory image.
+    pub memory: MemoryImage,
+    pub metadata: ProgramMetadata,
+    /// The names of `.data` lines with the bus address they start at.
+    pub symbols: SymbolTable
 }
 
 impl TranslatedProgram {
     pub fn new() -> TranslatedProgram {
         return TranslatedProgram {
-            lines: Vec::<TranslatedLine>::new()
+            lines: Vec::<TranslatedLine>::new(),
+            locations: Vec::<SourceLocation>::new(),
+            memory: MemoryImage::new(),
+            metadata: ProgramMetadata::new(),
+            symbols: SymbolTable::new()
         }
     }
+
+    pub fn push(&mut self, line: TranslatedLine, location: SourceLocation) {
+        self.lines.push(line);
+        self.locations.push(location);
+    }
+
+    /// Reads a program in the `.2i` format written by the assembler back in.
+    /// The bits of an instruction may be grouped by any whitespace.
+    pub fn parse(name: &str, content: &str) -> Result<TranslatedProgram, String> {
+        let mut program = TranslatedProgram::new();
+        for (i, line) in content.lines().enumerate() {
+            let location = SourceLocation::new(name, i + 1);
+            let translated_line = parse_translated_line(line)
+                .map_err(|err| format!("Error in {}, Description: {}", location, err))?;
+            if let TranslatedLine::CommentLine(comment) = &translated_line {
+                program.metadata.read_comment(comment);
+            }
+            program.push(translated_line, location);
+        }
+        return Ok(program);
+    }
+
+    /// Renders every translated line prefixed with the file and line it was translated from.
+    pub fn listing(&self) -> String {
+        let mut listing = String::new();
+        for (line, location) in self.lines.iter().zip(self.locations.iter()) {
+            listing.push_str(&format!("{}\t{}\n", location, line));
+        }
+        return listing;
+    }
+}
+
+impl Default for TranslatedProgram {
+    fn default() -> Self {
+        return TranslatedProgram::new();
+    }
 }
 
 impl Display for TranslatedProgram {
@@ -23,6 +75,39 @@ impl Display for TranslatedProgram {
     }
 }
 
+fn parse_translated_line(line: &str) -> Result<TranslatedLine, String> {
+    let mut split_comment = line.splitn(2, '#');
+    let line_without_comment = split_comment.next().unwrap_or("").trim();
+    let comment = split_comment.next().map(|comment| comment.to_string());
+
+    if line_without_comment.is_empty() {
+        return Ok(match comment {
+            Some(comment) => TranslatedLine::CommentLine(comment),
+            None => TranslatedLine::EmptyLine
+        });
+    }
+
+    let mut split_instruction_line = line_without_comment.splitn(2, ':');
+    let address_string = split_instruction_line.next().unwrap_or("").trim();
+    let bits: String = split_instruction_line.next()
+        .ok_or_else(|| MISSING_ADDRESS.message("No Instruction Address in front of a colon"))?
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect();
+
+    let address = InstructionAddress::try_from(address_string.to_string())
+        .map_err(|err| INVALID_ADDRESS.message(format!("Error Parsing Instruction Address: {}", err.description)))?;
+
+    if bits.len() != INSTRUCTION_WORD_BITS as usize {
+        return Err(INVALID_WORD.message(format!("Expected {} Instruction Bits, got {}", INSTRUCTION_WORD_BITS, bits.len())));
+    }
+
+    let word = u32::from_str_radix(&bits, 2)
+        .map_err(|_| INVALID_WORD.message("Instruction Bits must be 0 or 1"))?;
+
+    return Ok(TranslatedLine::InstructionLine(Instruction::from_word(address, word), comment));
+}
+
 pub enum TranslatedLine {
     InstructionLine(Instruction, Option<String>),
     CommentLine(String),
@@ -40,4 +125,37 @@ impl Display for TranslatedLine {
             TranslatedLine::EmptyLine => write!(f,""),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod translated_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hand_written_program() {
+        let program = TranslatedProgram::parse("example", include_str!("../../Examples/Example 2i Code.2i")).unwrap();
+
+        assert_eq!(17, program.lines.len());
+        assert_eq!(vec!["A in FC", "B in FD"], program.metadata.inputs);
+        match &program.lines[4] {
+            TranslatedLine::InstructionLine(instruction, comment) => {
+                assert_eq!("00000: 00 00001 00 000 1100 01 01 1100 0", format!("{}", instruction));
+                assert_eq!(Some(" R0 = FC".to_string()), *comment);
+            },
+            _ => panic!("Expected an Instruction Line")
+        }
+    }
+
+    #[test]
+    fn test_parse_generated_program_round_trips() {
+        let content = include_str!("../../Examples/Example Generated 2i Code.2i");
+        let program = TranslatedProgram::parse("generated", content).unwrap();
+
+        assert_eq!(content.lines().collect::<Vec<&str>>(), format!("{}", program).lines().collect::<Vec<&str>>());
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_bit_count() {
+        assert!(TranslatedProgram::parse("short", "00000: 00 00001 00 000").is_err());
+    }
+}
@@ -1,61 +1,725 @@
 use std::string::ToString;
 use clap::{ArgMatches, App, Arg};
+use assemblerlib::{parse_definition, SymbolTable};
+use assemblerlib::coverage::CoverageFormat;
+use assemblerlib::debugger::parse_byte;
+use assemblerlib::dialect::Dialect;
+use assemblerlib::documentation::DocumentFormat;
+use assemblerlib::equivalence::{EquivalenceSettings, DEFAULT_SAMPLES};
+use assemblerlib::memory::IO_PORTS;
+use assemblerlib::trace::TraceFormat;
 use crate::information::ProgramInformation;
 
 pub struct ProgramArguments {
     pub copy_instructions: bool,
     pub copy_comments: bool,
+    pub rtl_comments: bool,
+    pub write_listing: bool,
+    pub fix: bool,
+    pub defines: SymbolTable,
+    pub dialect: Dialect,
     pub input_file_path: String,
     pub output_file_path: String
 }
 
+pub struct BoardArguments {
+    pub speed: u64,
+    pub dialect: Dialect,
+    pub input_file_path: String
+}
+
+pub struct DebugArguments {
+    pub dialect: Dialect,
+    pub input_file_path: String
+}
+
+pub struct DisassembleArguments {
+    pub dialect: Dialect,
+    pub input_file_path: String,
+    pub output_file_path: String
+}
+
+pub struct DocumentArguments {
+    pub format: DocumentFormat,
+    pub dialect: Dialect,
+    pub input_file_path: String,
+    pub output_file_path: String
+}
+
+pub struct EquivalenceArguments {
+    pub input_ports: Vec<u8>,
+    pub stimulus_file_paths: Vec<String>,
+    pub cycles: u64,
+    pub samples: usize,
+    pub dialect: Dialect,
+    pub input_file_paths: [String; 2]
+}
+
+pub struct ExplainArguments {
+    pub dialect: Dialect,
+    pub input: String
+}
+
+pub struct FormatArguments {
+    pub check: bool,
+    pub dialect: Dialect,
+    pub input_file_paths: Vec<String>
+}
+
+pub struct LanguageServerArguments {
+    pub dialect: Dialect
+}
+
+pub struct RunArguments {
+    pub cycles: u64,
+    pub stimulus_file_path: Option<String>,
+    pub dialect: Dialect,
+    pub input_file_path: String
+}
+
+pub struct TestArguments {
+    pub coverage: Option<CoverageFormat>,
+    pub coverage_file_path: Option<String>,
+    pub dialect: Dialect,
+    pub input_file_paths: Vec<String>
+}
+
+pub struct TraceArguments {
+    pub format: TraceFormat,
+    pub cycles: u64,
+    pub dialect: Dialect,
+    pub input_file_path: String,
+    pub output_file_path: String
+}
+
+pub struct ReplArguments {
+    pub dialect: Dialect
+}
+
+pub enum ProgramCommand {
+    Assemble(ProgramArguments),
+    Board(BoardArguments),
+    Debug(DebugArguments),
+    Disassemble(DisassembleArguments),
+    Document(DocumentArguments),
+    Equivalence(EquivalenceArguments),
+    Explain(ExplainArguments),
+    Format(FormatArguments),
+    LanguageServer(LanguageServerArguments),
+    Repl(ReplArguments),
+    Run(RunArguments),
+    Test(TestArguments),
+    Trace(TraceArguments)
+}
+
+/// The most cycles a program is executed for when no number is given.
+const DEFAULT_CYCLES: u64 = 1000;
+
+/// The cycles per second of the live board when no speed is given.
+const DEFAULT_SPEED: u64 = 100;
+
 fn get_app(information: ProgramInformation) -> App {
     return App::new(information.name)
         .about(information.description)
         .version(information.version)
         .author(information.author)
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(get_board_app())
+        .subcommand(get_debug_app())
+        .subcommand(get_disassemble_app())
+        .subcommand(get_document_app())
+        .subcommand(get_equivalence_app())
+        .subcommand(get_explain_app())
+        .subcommand(get_format_app())
+        .subcommand(get_language_server_app())
+        .subcommand(get_repl_app())
+        .subcommand(get_run_app())
+        .subcommand(get_test_app())
+        .subcommand(get_trace_app())
         .arg(
-            Arg::with_name("INPUT")
-                .about("The Input File to translate, must have .2ia file type.")
+            Arg::new("INPUT")
+                .help("The Input File to translate, must have .2ia file type.")
                 .required(true)
                 .index(1)
         )
         .arg(
-            Arg::with_name("OUTPUT")
-                .about("The Output File to write to, must have .2i file type.")
+            Arg::new("OUTPUT")
+                .help("The Output File to write to, must have .2i file type.")
                 .short('o')
                 .long("output")
                 .takes_value(true)
                 .required(false)
         )
         .arg(
-            Arg::with_name("COPY_INSTRUCTIONS")
-                .about("Copy the Instructions as Comments.")
+            Arg::new("COPY_INSTRUCTIONS")
+                .help("Copy the Instructions as Comments.")
                 .short('i')
                 .long("instructions")
                 .required(false)
         )
         .arg(
-            Arg::with_name("COPY_COMMENTS")
-                .about("Copy Comments and empty lines from the Input file.")
+            Arg::new("COPY_COMMENTS")
+                .help("Copy Comments and empty lines from the Input file.")
                 .short('c')
                 .long("comments")
                 .required(false)
-        );
+        )
+        .arg(
+            Arg::new("RTL_COMMENTS")
+                .help("Describe every Instruction as a Register Transfer in its Comment.")
+                .short('r')
+                .long("rtl")
+                .required(false)
+        )
+        .arg(
+            Arg::new("LISTING")
+                .help("Write a Listing with the Source File and Line of every translated Line next to the Output File.")
+                .short('l')
+                .long("listing")
+                .required(false)
+        )
+        .arg(
+            Arg::new("FIX")
+                .help("Fix misspelled Mnemonics, missing Separators and the Order of Commands in the Source File before translating it.")
+                .long("fix")
+                .required(false)
+        )
+        .arg(
+            Arg::new("DEFINE")
+                .help("Define a Symbol for Conditional Assembly, NAME alone defines it as 1.")
+                .short('D')
+                .long("define")
+                .value_name("NAME=VALUE")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .required(false)
+        )
+        .arg(get_dialect_arg("The Dialect of Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_dialect_arg(help: &str) -> Arg<'_> {
+    return Arg::new("DIALECT")
+        .help(help)
+        .long("dialect")
+        .value_name("NAME")
+        .takes_value(true)
+        .possible_values(["standard", "course"])
+        .required(false);
+}
+
+fn get_board_app<'t>() -> App<'t> {
+    return App::new("board")
+        .about("Runs a .2ia or .2i File in real time on a virtual Lab Board with Switches on FC, Buttons on FD, LEDs on FE and a 7-Segment Display on FF.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Program to run, must have .2ia or .2i file type.")
+                .required(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("SPEED")
+                .help("The Cycles per Second. Defaults to 100.")
+                .long("speed")
+                .value_name("HZ")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(get_dialect_arg("The Dialect of .2ia Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_debug_app<'t>() -> App<'t> {
+    return App::new("debug")
+        .about("Steps through a .2ia or .2i File one Cycle at a time, with Breakpoints, Watchpoints and the Fields and ALU Values of every Instruction.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Program to debug, must have .2ia or .2i file type.")
+                .required(true)
+                .index(1)
+        )
+        .arg(get_dialect_arg("The Dialect of .2ia Files that do not select one with .dialect and of the shown Instructions, standard or course."));
+}
+
+fn get_cycles_arg<'t>() -> Arg<'t> {
+    return Arg::new("CYCLES")
+        .help("The most Cycles to execute, the Program stops earlier at an Instruction that jumps to itself. Defaults to 1000.")
+        .short('n')
+        .long("cycles")
+        .value_name("N")
+        .takes_value(true)
+        .required(false);
+}
+
+fn get_disassemble_app<'t>() -> App<'t> {
+    return App::new("disassemble")
+        .about("Translates a .2i File back into .2ia Source, using Pseudo Instructions where they fit.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Input File to disassemble, must have .2i file type.")
+                .required(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .help("The Output File to write to, must have .2ia file type.")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(get_dialect_arg("The Dialect to write the Source in, standard or course."));
+}
+
+fn get_document_app<'t>() -> App<'t> {
+    return App::new("doc")
+        .about("Writes a Page with the Metadata, the Listing and a Table of every Instruction of a .2ia or .2i File.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Program to document, must have .2ia or .2i file type.")
+                .required(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .help("The Page to write to, defaults to the Input File with .md or .html file type.")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(
+            Arg::new("FORMAT")
+                .help("The Format of the Page, markdown or html.")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(["markdown", "html"])
+                .required(false)
+        )
+        .arg(get_dialect_arg("The Dialect of .2ia Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_equivalence_app<'t>() -> App<'t> {
+    return App::new("equiv")
+        .about("Runs two .2ia or .2i Files over the same Input Port Values and Interrupt Schedules and compares what they write to the I/O Ports.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The two Programs to compare, must have .2ia or .2i file type.")
+                .required(true)
+                .number_of_values(2)
+                .index(1)
+        )
+        .arg(
+            Arg::new("PORTS")
+                .help("The Input Ports whose Values are tried, separated by Commas. Defaults to FC,FD.")
+                .short('p')
+                .long("ports")
+                .value_name("PORTS")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(
+            Arg::new("STIMULUS")
+                .help("A Stimulus File with an Interrupt Schedule that is tried after the Schedule without Interrupts, can be given multiple times.")
+                .short('s')
+                .long("stimulus")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .required(false)
+        )
+        .arg(
+            Arg::new("SAMPLES")
+                .help("The random Input Combinations that are tried when there are more than 65536. Defaults to 10000.")
+                .long("samples")
+                .value_name("N")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(get_cycles_arg())
+        .arg(get_dialect_arg("The Dialect of .2ia Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_explain_app<'t>() -> App<'t> {
+    return App::new("explain")
+        .about("Explains every Field of an Instruction, given as .2ia Line or as Word in binary or hexadecimal (0x...), or an Error Code like E0003.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The .2ia Line, the Word or the Error Code, the Words of the Input are joined by Spaces.")
+                .required(true)
+                .multiple_values(true)
+                .allow_hyphen_values(true)
+                .index(1)
+        )
+        .arg(get_dialect_arg("The Dialect of the .2ia Line and the Source in the Explanation, standard or course."));
+}
+
+fn get_format_app<'t>() -> App<'t> {
+    return App::new("fmt")
+        .about("Rewrites .2ia Files into the canonical Layout with aligned Columns, without changing the translated Program.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Files to format, must have .2ia file type.")
+                .required(true)
+                .multiple_values(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("CHECK")
+                .help("Only check that the Files are formatted, list the others and fail if there are any.")
+                .long("check")
+                .required(false)
+        )
+        .arg(get_dialect_arg("The Dialect of Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_language_server_app<'t>() -> App<'t> {
+    return App::new("lsp")
+        .about("Serves the Language Server Protocol over stdin and stdout, for Diagnostics, Hover, Completion, Definitions, Renames and Symbols in Editors.")
+        .arg(get_dialect_arg("The Dialect of Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_repl_app<'t>() -> App<'t> {
+    return App::new("repl")
+        .about("Translates and explains every typed .2ia Line, keeping the Lines before it. Supports :list, :save FILE and :load FILE.")
+        .arg(get_dialect_arg("The Dialect of the typed Lines that do not select one with .dialect, standard or course."));
+}
+
+fn get_run_app<'t>() -> App<'t> {
+    return App::new("run")
+        .about("Executes a .2ia or .2i File on the emulated 2i and prints the Registers, Flags and Memory at the End.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Program to run, must have .2ia or .2i file type.")
+                .required(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("STIMULUS")
+                .help("A Stimulus File with the Input Port Values and Interrupts over time and the expected Output Port Writes, fails if the Writes differ.")
+                .short('s')
+                .long("stimulus")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(get_cycles_arg())
+        .arg(get_dialect_arg("The Dialect of .2ia Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_test_app<'t>() -> App<'t> {
+    return App::new("test")
+        .about("Runs the # TEST Cases of .2ia or .2i Files on the emulated 2i and reports which pass.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Programs to test, must have .2ia or .2i file type.")
+                .required(true)
+                .multiple_values(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("COVERAGE")
+                .help("Reports which Instructions the Test Cases executed and which Successors of every conditional Jump they took, as an annotated listing or as lcov.")
+                .long("coverage")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(["listing", "lcov"])
+                .required(false)
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .help("The File to write the Coverage Report to, it is printed after the Test Results if not given.")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .requires("COVERAGE")
+                .required(false)
+        )
+        .arg(get_dialect_arg("The Dialect of Files that do not select one with .dialect, standard or course."));
+}
+
+fn get_trace_app<'t>() -> App<'t> {
+    return App::new("trace")
+        .about("Executes a .2ia or .2i File and records every Control Signal, the ALU Values, the Registers and the Flags of every Cycle.")
+        .arg(
+            Arg::new("INPUT")
+                .help("The Program to trace, must have .2ia or .2i file type.")
+                .required(true)
+                .index(1)
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .help("The Trace to write to, defaults to the Input File with .csv or .vcd file type.")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .required(false)
+        )
+        .arg(
+            Arg::new("FORMAT")
+                .help("The Format of the Trace, csv (a Table with a Row per Cycle) or vcd (a Value Change Dump for Waveform Viewers).")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(["csv", "vcd"])
+                .required(false)
+        )
+        .arg(get_cycles_arg())
+        .arg(get_dialect_arg("The Dialect of .2ia Files that do not select one with .dialect, standard or course."));
 }
 
-pub fn get_program_arguments(information: ProgramInformation) -> Result<ProgramArguments, String> {
+pub fn get_program_command(information: ProgramInformation) -> Result<ProgramCommand, String> {
     let app = get_app(information);
 
     let matches = app.get_matches();
 
-    return args_to_program_arguments(&matches);
+    return match matches.subcommand() {
+        Some(("board", args)) => Ok(ProgramCommand::Board(args_to_board_arguments(args)?)),
+        Some(("debug", args)) => Ok(ProgramCommand::Debug(args_to_debug_arguments(args)?)),
+        Some(("disassemble", args)) => Ok(ProgramCommand::Disassemble(args_to_disassemble_arguments(args)?)),
+        Some(("doc", args)) => Ok(ProgramCommand::Document(args_to_document_arguments(args)?)),
+        Some(("equiv", args)) => Ok(ProgramCommand::Equivalence(args_to_equivalence_arguments(args)?)),
+        Some(("explain", args)) => Ok(ProgramCommand::Explain(ExplainArguments {
+            dialect: args_to_dialect(args)?,
+            input: args.values_of("INPUT").into_iter().flatten().collect::<Vec<&str>>().join(" ")
+        })),
+        Some(("fmt", args)) => Ok(ProgramCommand::Format(args_to_format_arguments(args)?)),
+        Some(("lsp", args)) => Ok(ProgramCommand::LanguageServer(LanguageServerArguments {
+            dialect: args_to_dialect(args)?
+        })),
+        Some(("repl", args)) => Ok(ProgramCommand::Repl(ReplArguments {
+            dialect: args_to_dialect(args)?
+        })),
+        Some(("run", args)) => Ok(ProgramCommand::Run(args_to_run_arguments(args)?)),
+        Some(("test", args)) => Ok(ProgramCommand::Test(args_to_test_arguments(args)?)),
+        Some(("trace", args)) => Ok(ProgramCommand::Trace(args_to_trace_arguments(args)?)),
+        _ => Ok(ProgramCommand::Assemble(args_to_program_arguments(&matches)?))
+    };
+}
+
+fn args_to_board_arguments(args: &ArgMatches) -> Result<BoardArguments, String> {
+    let input_file_path = args.value_of("INPUT")
+        .ok_or("Input File was not provided".to_string())?;
+
+    if !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i") {
+        return Err("Input File does not have .2ia or .2i File Type".to_string());
+    }
+
+    let speed = match args.value_of("SPEED") {
+        None => DEFAULT_SPEED,
+        Some(speed) => match speed.parse() {
+            Ok(speed) if speed > 0 => speed,
+            _ => return Err(format!("Invalid Speed {}, expected Cycles per Second", speed))
+        }
+    };
+
+    return Ok(BoardArguments {
+        speed,
+        dialect: args_to_dialect(args)?,
+        input_file_path: input_file_path.to_string()
+    });
+}
+
+fn args_to_debug_arguments(args: &ArgMatches) -> Result<DebugArguments, String> {
+    let input_file_path = args.value_of("INPUT")
+        .ok_or("Input File was not provided".to_string())?;
+
+    if !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i") {
+        return Err("Input File does not have .2ia or .2i File Type".to_string());
+    }
+
+    return Ok(DebugArguments {
+        dialect: args_to_dialect(args)?,
+        input_file_path: input_file_path.to_string()
+    });
+}
+
+fn args_to_disassemble_arguments(args: &ArgMatches) -> Result<DisassembleArguments, String> {
+    let input_file_path = args.value_of("INPUT")
+        .ok_or("Input File was not provided".to_string())?;
+
+    if !input_file_path.ends_with(".2i") {
+        return Err("Input File does not have .2i File Type".to_string());
+    }
+
+    let output_file_path = match args.value_of("OUTPUT") {
+        None => format!("{}a", input_file_path),
+        Some(file_path) => file_path.to_string()
+    };
+
+    if !output_file_path.ends_with(".2ia") {
+        return Err("Output File does not have .2ia File Type".to_string());
+    }
+
+    return Ok(DisassembleArguments {
+        dialect: args_to_dialect(args)?,
+        input_file_path: input_file_path.to_string(),
+        output_file_path
+    });
+}
+
+fn args_to_format_arguments(args: &ArgMatches) -> Result<FormatArguments, String> {
+    let input_file_paths: Vec<String> = args.values_of("INPUT").into_iter().flatten()
+        .map(|input_file_path| input_file_path.to_string())
+        .collect();
+
+    if let Some(input_file_path) = input_file_paths.iter().find(|input_file_path| !input_file_path.ends_with(".2ia")) {
+        return Err(format!("Input File {} does not have .2ia File Type", input_file_path));
+    }
+
+    return Ok(FormatArguments {
+        check: args.is_present("CHECK"),
+        dialect: args_to_dialect(args)?,
+        input_file_paths
+    });
+}
+
+fn args_to_document_arguments(args: &ArgMatches) -> Result<DocumentArguments, String> {
+    let input_file_path = args.value_of("INPUT")
+        .ok_or("Input File was not provided".to_string())?;
+
+    if !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i") {
+        return Err("Input File does not have .2ia or .2i File Type".to_string());
+    }
+
+    let format = match args.value_of("FORMAT") {
+        None => DocumentFormat::Markdown,
+        Some(name) => DocumentFormat::from_name(name)?
+    };
+
+    let output_file_path = match args.value_of("OUTPUT") {
+        None => {
+            let stem = input_file_path.trim_end_matches(".2ia").trim_end_matches(".2i");
+            format!("{}.{}", stem, format.extension())
+        },
+        Some(file_path) => file_path.to_string()
+    };
+
+    return Ok(DocumentArguments {
+        format,
+        dialect: args_to_dialect(args)?,
+        input_file_path: input_file_path.to_string(),
+        output_file_path
+    });
+}
+
+fn args_to_run_arguments(args: &ArgMatches) -> Result<RunArguments, String> {
+    let input_file_path = args.value_of("INPUT")
+        .ok_or("Input File was not provided".to_string())?;
+
+    if !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i") {
+        return Err("Input File does not have .2ia or .2i File Type".to_string());
+    }
+
+    return Ok(RunArguments {
+        cycles: args_to_cycles(args)?,
+        stimulus_file_path: args.value_of("STIMULUS").map(|file_path| file_path.to_string()),
+        dialect: args_to_dialect(args)?,
+        input_file_path: input_file_path.to_string()
+    });
+}
+
+fn args_to_equivalence_arguments(args: &ArgMatches) -> Result<EquivalenceArguments, String> {
+    let input_file_paths: Vec<String> = args.values_of("INPUT").into_iter().flatten()
+        .map(|input_file_path| input_file_path.to_string())
+        .collect();
+
+    if let Some(input_file_path) = input_file_paths.iter().find(|input_file_path| !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i")) {
+        return Err(format!("Input File {} does not have .2ia or .2i File Type", input_file_path));
+    }
+    let input_file_paths = match input_file_paths.as_slice() {
+        [first, second] => [first.clone(), second.clone()],
+        _ => return Err("Expected two Input Files to compare".to_string())
+    };
+
+    let input_ports = match args.value_of("PORTS") {
+        None => EquivalenceSettings::new().input_ports,
+        Some(ports) => ports.split(',')
+            .map(|port| match parse_byte(port.trim(), &SymbolTable::new()) {
+                Ok(port) if IO_PORTS.contains(&port) => Ok(port),
+                _ => Err(format!("Invalid Input Port {}, the Ports are FC to FF", port.trim()))
+            })
+            .collect::<Result<Vec<u8>, String>>()?
+    };
+
+    let samples = match args.value_of("SAMPLES") {
+        None => DEFAULT_SAMPLES,
+        Some(samples) => samples.parse().map_err(|_| format!("Invalid Number of Samples {}", samples))?
+    };
+
+    return Ok(EquivalenceArguments {
+        input_ports,
+        stimulus_file_paths: args.values_of("STIMULUS").into_iter().flatten().map(|file_path| file_path.to_string()).collect(),
+        cycles: args_to_cycles(args)?,
+        samples,
+        dialect: args_to_dialect(args)?,
+        input_file_paths
+    });
+}
+
+fn args_to_test_arguments(args: &ArgMatches) -> Result<TestArguments, String> {
+    let input_file_paths: Vec<String> = args.values_of("INPUT").into_iter().flatten()
+        .map(|input_file_path| input_file_path.to_string())
+        .collect();
+
+    if let Some(input_file_path) = input_file_paths.iter().find(|input_file_path| !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i")) {
+        return Err(format!("Input File {} does not have .2ia or .2i File Type", input_file_path));
+    }
+
+    let coverage = match args.value_of("COVERAGE") {
+        None => None,
+        Some(name) => Some(CoverageFormat::from_name(name)?)
+    };
+
+    return Ok(TestArguments {
+        coverage,
+        coverage_file_path: args.value_of("OUTPUT").map(|file_path| file_path.to_string()),
+        dialect: args_to_dialect(args)?,
+        input_file_paths
+    });
+}
+
+fn args_to_trace_arguments(args: &ArgMatches) -> Result<TraceArguments, String> {
+    let input_file_path = args.value_of("INPUT")
+        .ok_or("Input File was not provided".to_string())?;
+
+    if !input_file_path.ends_with(".2ia") && !input_file_path.ends_with(".2i") {
+        return Err("Input File does not have .2ia or .2i File Type".to_string());
+    }
+
+    let format = match args.value_of("FORMAT") {
+        None => TraceFormat::Csv,
+        Some(name) => TraceFormat::from_name(name)?
+    };
+
+    let output_file_path = match args.value_of("OUTPUT") {
+        None => {
+            let stem = input_file_path.trim_end_matches(".2ia").trim_end_matches(".2i");
+            format!("{}.{}", stem, format.extension())
+        },
+        Some(file_path) => file_path.to_string()
+    };
+
+    return Ok(TraceArguments {
+        format,
+        cycles: args_to_cycles(args)?,
+        dialect: args_to_dialect(args)?,
+        input_file_path: input_file_path.to_string(),
+        output_file_path
+    });
 }
 
 fn args_to_program_arguments(args: &ArgMatches) -> Result<ProgramArguments, String> {
 
     let copy_instructions: bool = args_to_copy_instructions(args);
     let copy_comments: bool = args_to_copy_comments(args);
+    let rtl_comments: bool = args_to_rtl_comments(args);
+    let write_listing: bool = args_to_write_listing(args);
+    let fix: bool = args_to_fix(args);
+    let defines = args_to_defines(args)?;
+    let dialect = args_to_dialect(args)?;
 
     let input_file_path = args_to_input_file_path(args)?;
     let output_file_path = args_to_output_file_path(&input_file_path, args)?;
@@ -63,6 +727,11 @@ fn args_to_program_arguments(args: &ArgMatches) -> Result<ProgramArguments, Stri
     let program_arguments = ProgramArguments {
         copy_instructions,
         copy_comments,
+        rtl_comments,
+        write_listing,
+        fix,
+        defines,
+        dialect,
         input_file_path,
         output_file_path
     };
@@ -78,6 +747,42 @@ fn args_to_copy_comments(args: &ArgMatches) -> bool {
     return args.is_present("COPY_COMMENTS");
 }
 
+fn args_to_rtl_comments(args: &ArgMatches) -> bool {
+    return args.is_present("RTL_COMMENTS");
+}
+
+fn args_to_write_listing(args: &ArgMatches) -> bool {
+    return args.is_present("LISTING");
+}
+
+fn args_to_fix(args: &ArgMatches) -> bool {
+    return args.is_present("FIX");
+}
+
+fn args_to_defines(args: &ArgMatches) -> Result<SymbolTable, String> {
+    let mut defines = SymbolTable::new();
+    for definition in args.values_of("DEFINE").into_iter().flatten() {
+        let (name, value) = parse_definition(definition)
+            .map_err(|err| format!("Invalid Define {}: {}", definition, err))?;
+        defines.insert(name, value);
+    }
+    return Ok(defines);
+}
+
+fn args_to_cycles(args: &ArgMatches) -> Result<u64, String> {
+    return match args.value_of("CYCLES") {
+        None => Ok(DEFAULT_CYCLES),
+        Some(cycles) => cycles.parse().map_err(|_| format!("Invalid Number of Cycles {}", cycles))
+    };
+}
+
+fn args_to_dialect(args: &ArgMatches) -> Result<Dialect, String> {
+    return match args.value_of("DIALECT") {
+        None => Ok(Dialect::Standard),
+        Some(name) => Dialect::from_name(name)
+    };
+}
+
 fn args_to_input_file_path(args: &ArgMatches) -> Result<String, String> {
     let input_file_path = args.value_of("INPUT")
         .ok_or("Input File was not provided".to_string())?;
@@ -102,6 +807,18 @@ fn args_to_output_file_path(input_file_path: &str, args: &ArgMatches) -> Result<
     return Ok(output_file_path);
 }
 
+pub fn listing_file_path_from_output_file_path(output_file_path: &str) -> String {
+    let mut listing_file_path = output_file_path.to_string();
+    listing_file_path.replace_range(listing_file_path.len() - 3..listing_file_path.len(), ".lst");
+    return listing_file_path;
+}
+
+pub fn memory_file_path_from_output_file_path(output_file_path: &str) -> String {
+    let mut memory_file_path = output_file_path.to_string();
+    memory_file_path.replace_range(memory_file_path.len() - 3..memory_file_path.len(), ".mem");
+    return memory_file_path;
+}
+
 fn output_file_path_from_input_file_path(input_file_path: &str) -> String {
     let mut output_file_path = input_file_path.to_string();
     output_file_path.replace_range(output_file_path.len() - 4..output_file_path.len(), ".2i");
@@ -0,0 +1,129 @@
+use std::io;
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use console::{style, Key, Term};
+use assemblerlib::devices::standard_board;
+use assemblerlib::emulator::Emulator;
+use crate::arguments::BoardArguments;
+use crate::load_program;
+
+/// How long one frame of the live view is shown.
+const FRAME_TIME: Duration = Duration::from_millis(50);
+
+/// The key that ends the live view and Ctrl+C, which a terminal in raw mode passes on as a character.
+const QUIT_KEYS: [char; 2] = ['q', '\u{3}'];
+
+const HELP: &str = "0-7 Switches  a-d Buttons  i INTA  Space INTB  q Quit";
+
+pub fn board(board_arguments: BoardArguments) {
+    let program = match load_program(&board_arguments.input_file_path, board_arguments.dialect) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            return;
+        }
+    };
+    let mut emulator = Emulator::new(&program);
+    standard_board().into_iter().for_each(|device| emulator.attach(device));
+
+    let term = Term::stdout();
+    if !term.is_term() {
+        eprintln!("{}", style("The Board needs a Terminal").red());
+        return;
+    }
+
+    let name = Path::new(&board_arguments.input_file_path).file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| board_arguments.input_file_path.clone());
+    if let Err(error) = show_board(&term, &name, &mut emulator, board_arguments.speed) {
+        eprintln!("{}", style(error).red());
+    }
+    let _ = term.show_cursor();
+}
+
+/// Runs the program at the given cycles per second and redraws the board every frame until the quit key is pressed.
+/// A stopped program stays on the screen, so the terminal is never left in raw mode by a pending key read.
+fn show_board(term: &Term, name: &str, emulator: &mut Emulator, speed: u64) -> io::Result<()> {
+    let keys = read_keys();
+    let start = Instant::now();
+    let mut error = None;
+
+    term.hide_cursor()?;
+    term.clear_screen()?;
+    loop {
+        loop {
+            match keys.try_recv() {
+                Ok(key) if QUIT_KEYS.contains(&key) => return term.move_cursor_to(0, draw(term, name, emulator, &error)?),
+                Ok('i') => emulator.state.interrupt_a = !emulator.state.interrupt_a,
+                Ok(' ') => {
+                    emulator.set_interrupt_b(true);
+                    emulator.set_interrupt_b(false);
+                },
+                Ok(key) => {
+                    emulator.press(key);
+                },
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(())
+            }
+        }
+
+        let due = (start.elapsed().as_secs_f64() * speed as f64) as u64;
+        if error.is_none() {
+            if let Err(run_error) = emulator.run(due.saturating_sub(emulator.state.cycles)) {
+                error = Some(run_error);
+            }
+        }
+        draw(term, name, emulator, &error)?;
+        thread::sleep(FRAME_TIME);
+    }
+}
+
+/// Reads the keys in the background, the terminal blocks until a key is pressed.
+fn read_keys() -> Receiver<char> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let term = Term::stdout();
+        loop {
+            let key = match term.read_key() {
+                Ok(Key::Char(key)) => key,
+                Ok(Key::Escape) => QUIT_KEYS[0],
+                Ok(_) => continue,
+                Err(_) => break
+            };
+            if sender.send(key).is_err() || QUIT_KEYS.contains(&key) {
+                break;
+            }
+        }
+    });
+    return receiver;
+}
+
+/// Draws every line at its own position, while a key is read the terminal does not move to the start of a new line.
+/// Returns the number of lines.
+fn draw(term: &Term, name: &str, emulator: &Emulator, error: &Option<String>) -> io::Result<usize> {
+    let status = match (error, emulator.is_halted()) {
+        (Some(error), _) => style(error.to_string()).red().to_string(),
+        (None, true) => style("The Program is halted").yellow().to_string(),
+        (None, false) => String::new()
+    };
+    let mut lines = vec![
+        style(format!("Assembler2i Board: {}", name)).bold().to_string(),
+        format!("Cycle {}  Address {}  INTA {}", emulator.state.cycles, emulator.state.address, emulator.state.interrupt_a as u8),
+        String::new()
+    ];
+    lines.extend(emulator.devices().iter().flat_map(|device| device.render()));
+    lines.push(String::new());
+    lines.push(style(HELP).dim().to_string());
+    lines.push(status);
+
+    for (row, line) in lines.iter().enumerate() {
+        term.move_cursor_to(0, row)?;
+        term.clear_line()?;
+        term.write_str(line)?;
+    }
+    term.flush()?;
+    return Ok(lines.len());
+}
@@ -0,0 +1,158 @@
+use std::io;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use console::style;
+use assemblerlib::debugger::{format_cycle, Debugger};
+use assemblerlib::source::{SourceLine, SourceLocation};
+use crate::arguments::DebugArguments;
+use crate::{load_program, program_assembler};
+use crate::repl::print_instruction;
+
+const PROMPT: &str = "2i> ";
+
+/// The most cycles `continue` executes when it is not given a number, so that a program without a halt returns.
+const CONTINUE_CYCLES: u64 = 100_000;
+
+const HELP: &str = "step [N]            Executes one or N Cycles and shows what every Cycle did\n\
+    continue [N]        Runs until a Breakpoint, a Watchpoint, a Halt or N Cycles (default 100000)\n\
+    break LABEL         Stops before the Instruction at an Address (00011) or of a Source Line (12 or FILE:12)\n\
+    delete LABEL        Removes a Breakpoint\n\
+    watch TARGET        Stops after a Cycle that changes R0..R7, CF, ZF, NF or a Bus Address like (10) or a .data Name\n\
+    unwatch TARGET      Removes a Watchpoint\n\
+    set TARGET VALUE    Sets a Register, a Flag or a Bus Address to a hexadecimal Value\n\
+    inta on|off         Sets the Level of the Level Interrupt INTA\n\
+    intb                Raises the Edge Interrupt INTB, it stays set until a Jump tests it\n\
+    show                Shows the next Instruction with its Fields and what it will do\n\
+    state               Shows the Registers, the Flags and the Memory\n\
+    list                Lists the Breakpoints and Watchpoints\n\
+    help                Shows this Help\n\
+    quit                Leaves the Debugger";
+
+pub fn debug(debug_arguments: DebugArguments) {
+    let program = match load_program(&debug_arguments.input_file_path, debug_arguments.dialect) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            return;
+        }
+    };
+    let sources = match debug_arguments.input_file_path.ends_with(".2ia") {
+        true => program_assembler(debug_arguments.dialect).load_file(Path::new(&debug_arguments.input_file_path)).unwrap_or_default(),
+        false => program.locations.iter().zip(program.lines.iter())
+            .map(|(location, line)| SourceLine { location: location.clone(), text: line.to_string() })
+            .collect()
+    };
+
+    let mut debugger = Debugger::new(program, &debug_arguments.input_file_path, sources);
+    println!("Assembler2i Debugger, type help for the Commands");
+    show(&debugger, &debug_arguments);
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    loop {
+        print!("{}", PROMPT);
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        match run_command(&mut debugger, &debug_arguments, line.trim()) {
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(error) => eprintln!("{}", style(error).red())
+        }
+    }
+}
+
+/// Runs a debugger command, returns whether the debugger goes on.
+fn run_command(debugger: &mut Debugger, debug_arguments: &DebugArguments, command: &str) -> Result<bool, String> {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let count = |default: u64| -> Result<u64, String> {
+        return match words.get(1) {
+            Some(count) => count.parse().map_err(|_| format!("Invalid Number of Cycles {}", count)),
+            None => Ok(default)
+        };
+    };
+
+    match words.as_slice() {
+        [] => {},
+        ["step" | "s", ..] => {
+            for _ in 0..count(1)? {
+                let address = debugger.emulator.state.address;
+                let cycle = debugger.step()?;
+                println!("{}", style(format!("Cycle {} at {}", debugger.emulator.state.cycles, address)).bold());
+                print!("{}", format_cycle(&cycle));
+            }
+            show(debugger, debug_arguments);
+        },
+        ["continue" | "c", ..] => {
+            let stop = debugger.resume(count(CONTINUE_CYCLES)?)?;
+            println!("{}", style(stop).yellow().bold());
+            show(debugger, debug_arguments);
+        },
+        ["break" | "b", label] => println!("Breakpoint at {}", debugger.add_breakpoint(label)?),
+        ["delete" | "d", label] => println!("Removed the Breakpoint at {}", debugger.remove_breakpoint(label)?),
+        ["watch" | "w", target] => println!("Watching {}", debugger.add_watchpoint(target)?),
+        ["unwatch", target] => println!("Stopped watching {}", debugger.remove_watchpoint(target)?),
+        ["set", target, value] => {
+            let target = debugger.poke(target, value)?;
+            println!("{} = {:02X}", target, target.value(&debugger.emulator.state));
+        },
+        ["inta", level @ ("on" | "off")] => {
+            debugger.emulator.state.interrupt_a = *level == "on";
+            println!("INTA is {}", level);
+        },
+        ["intb"] => {
+            debugger.emulator.set_interrupt_b(true);
+            debugger.emulator.set_interrupt_b(false);
+            println!("INTB is raised");
+        },
+        ["show"] => show(debugger, debug_arguments),
+        ["state"] => print!("{}", debugger.emulator.state),
+        ["list"] => {
+            let breakpoints: Vec<String> = debugger.breakpoints().iter().map(|address| address.to_string()).collect();
+            let watchpoints: Vec<String> = debugger.watchpoints().iter().map(|target| target.to_string()).collect();
+            println!("Breakpoints: {}", breakpoints.join(" "));
+            println!("Watchpoints: {}", watchpoints.join(" "));
+        },
+        ["help"] => println!("{}", HELP),
+        ["quit" | "q"] => return Ok(false),
+        _ => return Err(format!("Unknown Command {}, type help for the Commands", command))
+    }
+    return Ok(true);
+}
+
+/// Shows the source line and the fields of the next instruction and what it will do with the current state.
+fn show(debugger: &Debugger, debug_arguments: &DebugArguments) {
+    let address = debugger.emulator.state.address;
+    let instruction = match debugger.emulator.instruction() {
+        Some(instruction) => *instruction,
+        None => {
+            eprintln!("{}", style(format!("There is no Instruction at {}", address)).red());
+            return;
+        }
+    };
+
+    println!();
+    if let Some((location, text)) = debugger.source_line(address) {
+        println!("{}  {}", style(location_name(location)).dim(), style(text).bold());
+    }
+    print_instruction(&instruction, debug_arguments.dialect);
+    match debugger.preview() {
+        Ok(cycle) => print!("{}", format_cycle(&cycle)),
+        Err(error) => eprintln!("{}", style(error).red())
+    }
+    if debugger.emulator.is_halted() {
+        println!("{}", style("The Program is halted").yellow());
+    }
+}
+
+fn location_name(location: &SourceLocation) -> String {
+    let file = Path::new(&location.file).file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| location.file.clone());
+    return format!("{}:{}", file, location.line);
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use serde_json::{json, Value};
+use assemblerlib::analysis::{CompletionKind, Position, Range, SourceDocument};
+use crate::arguments::LanguageServerArguments;
+
+/// The JSON-RPC error code for requests the server does not know.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// The JSON-RPC error code for requests that could not be done, like renaming something that is no label.
+const REQUEST_FAILED: i64 = -32803;
+
+/// Serves the Language Server Protocol over stdin and stdout until the client sends `exit`.
+pub fn language_server(arguments: LanguageServerArguments) {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut server = LanguageServer { arguments, documents: HashMap::new(), output: std::io::stdout() };
+
+    while let Some(message) = read_message(&mut input) {
+        let method = message["method"].as_str().unwrap_or("").to_string();
+        if method == "exit" {
+            return;
+        }
+
+        let result = server.handle(&method, &message["params"]);
+        if let Some(id) = message.get("id") {
+            let response = match result {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, error)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": error } })
+            };
+            server.send(&response);
+        }
+    }
+}
+
+struct LanguageServer {
+    arguments: LanguageServerArguments,
+    documents: HashMap<String, SourceDocument>,
+    output: std::io::Stdout
+}
+
+impl LanguageServer {
+    fn handle(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+        let position = Position {
+            line: params["position"]["line"].as_u64().unwrap_or(0) as usize,
+            character: params["position"]["character"].as_u64().unwrap_or(0) as usize
+        };
+
+        return match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "completionProvider": {},
+                    "definitionProvider": true,
+                    "renameProvider": true,
+                    "documentSymbolProvider": true
+                },
+                "serverInfo": { "name": "Assembler2i" }
+            })),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/didOpen" => {
+                let path = uri_to_path(&uri);
+                let base_directory = Path::new(&path).parent().unwrap_or_else(|| Path::new("."));
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.documents.insert(uri.clone(), SourceDocument::new(&path, base_directory, text, self.arguments.dialect));
+                self.publish_diagnostics(&uri);
+                Ok(Value::Null)
+            },
+            "textDocument/didChange" => {
+                // The server asks for full synchronisation, so the last change is the whole text.
+                let text = params["contentChanges"].as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(document), Some(text)) = (self.documents.get_mut(&uri), text) {
+                    document.set_text(text);
+                }
+                self.publish_diagnostics(&uri);
+                Ok(Value::Null)
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                self.send(&json!({ "jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": { "uri": uri, "diagnostics": [] } }));
+                Ok(Value::Null)
+            },
+            "textDocument/hover" => Ok(match self.document(&uri)?.hover(position) {
+                Some(hover) => json!({ "contents": { "kind": "markdown", "value": format!("```\n{}```", hover) } }),
+                None => Value::Null
+            }),
+            "textDocument/completion" => Ok(Value::Array(self.document(&uri)?.completions().iter()
+                .map(|completion| json!({ "label": completion.label, "kind": completion_kind(completion.kind), "detail": completion.detail }))
+                .collect())),
+            "textDocument/definition" => Ok(match self.document(&uri)?.definition(position) {
+                Some(range) => json!({ "uri": uri, "range": range_to_json(range) }),
+                None => Value::Null
+            }),
+            "textDocument/rename" => {
+                let new_name = params["newName"].as_str().unwrap_or("");
+                let edits = self.document(&uri)?.rename(position, new_name).map_err(|error| (REQUEST_FAILED, error))?;
+                let edits: Vec<Value> = edits.iter()
+                    .map(|edit| json!({ "range": range_to_json(edit.range), "newText": edit.new_text }))
+                    .collect();
+                Ok(json!({ "changes": { uri: edits } }))
+            },
+            "textDocument/documentSymbol" => Ok(Value::Array(self.document(&uri)?.symbols().iter()
+                .map(|symbol| json!({
+                    // Symbol kind 14 is a constant, the closest of the protocol to an instruction address.
+                    "name": symbol.name, "detail": symbol.detail, "kind": 14,
+                    "range": range_to_json(symbol.range), "selectionRange": range_to_json(symbol.range)
+                }))
+                .collect())),
+            _ if method.starts_with("$/") || method == "initialized" => Ok(Value::Null),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown Method {}", method)))
+        };
+    }
+
+    fn document(&self, uri: &str) -> Result<&SourceDocument, (i64, String)> {
+        return self.documents.get(uri)
+            .ok_or_else(|| (REQUEST_FAILED, format!("The Document {} is not open", uri)));
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str) {
+        let diagnostics: Vec<Value> = match self.documents.get(uri) {
+            Some(document) => document.diagnostics().iter()
+                .map(|diagnostic| json!({
+                    "range": range_to_json(diagnostic.range),
+                    "severity": 1,
+                    "code": diagnostic.code,
+                    "source": "Assembler2i",
+                    "message": diagnostic.message
+                }))
+                .collect(),
+            None => Vec::new()
+        };
+        self.send(&json!({ "jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": { "uri": uri, "diagnostics": diagnostics } }));
+    }
+
+    fn send(&mut self, message: &Value) {
+        let content = message.to_string();
+        let mut output = self.output.lock();
+        // A closed client can not be told about the error, the server stops when its input ends.
+        let _ = write!(output, "Content-Length: {}\r\n\r\n{}", content.len(), content);
+        let _ = output.flush();
+    }
+}
+
+/// Reads one message with its `Content-Length` header, `None` at the end of the input.
+fn read_message<R: BufRead>(input: &mut R) -> Option<Value> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let mut content = vec![0; content_length?];
+    input.read_exact(&mut content).ok()?;
+    return Some(serde_json::from_slice(&content).unwrap_or(Value::Null));
+}
+
+fn uri_to_path(uri: &str) -> String {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut decoded = Vec::new();
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        let escaped = match byte {
+            b'%' => bytes.next().zip(bytes.next())
+                .and_then(|(high, low)| u8::from_str_radix(&format!("{}{}", high as char, low as char), 16).ok()),
+            _ => None
+        };
+        decoded.push(escaped.unwrap_or(byte));
+    }
+    return String::from_utf8_lossy(&decoded).to_string();
+}
+
+fn range_to_json(range: Range) -> Value {
+    return json!({
+        "start": { "line": range.start.line, "character": range.start.character },
+        "end": { "line": range.end.line, "character": range.end.character }
+    });
+}
+
+/// The numbers of the protocol's completion item kinds.
+fn completion_kind(kind: CompletionKind) -> u8 {
+    return match kind {
+        CompletionKind::Mnemonic => 3,
+        CompletionKind::Register => 6,
+        CompletionKind::Label => 21,
+        CompletionKind::Jump => 24,
+        CompletionKind::Keyword => 14
+    };
+}
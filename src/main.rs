@@ -1,154 +1,440 @@
-use clap::{clap_app, ArgMatches};
-use std::fs::File;
-use std::io::{Error, ErrorKind, Read, Write};
+#![allow(clippy::needless_return)]
+
+use std::fs;
 use std::path::Path;
 use console::style;
-use dialoguer::Confirm;
-use std::iter::FromIterator;
-use crate::parsing::{parse_line, CodeLine};
+use assemblerlib::{Assembler, AssemblerSettings};
+use assemblerlib::coverage::{format_coverage, Coverage};
+use assemblerlib::dialect::Dialect;
+use assemblerlib::diagnostics::{find_diagnostic, format_diagnostic};
+use assemblerlib::disassembly::disassemble_program;
+use assemblerlib::documentation::document_program;
+use assemblerlib::emulator::Emulator;
+use assemblerlib::equivalence::{check_equivalence, format_difference, Equivalence, EquivalenceSettings};
+use assemblerlib::suggestion::fix_source;
+use assemblerlib::explanation::{format_explanation, parse_explain_input};
+use assemblerlib::formatting::format_program;
+use assemblerlib::stimulus::Stimulus;
+use assemblerlib::testing::{format_test_result, parse_test_cases, run_test_case};
+use assemblerlib::trace::{format_trace, record_trace};
+use assemblerlib::translated::TranslatedProgram;
+use crate::arguments::{get_program_command, listing_file_path_from_output_file_path, memory_file_path_from_output_file_path, ProgramArguments, ProgramCommand, DisassembleArguments, DocumentArguments, EquivalenceArguments, ExplainArguments, FormatArguments, RunArguments, TestArguments, TraceArguments};
+use crate::files::{try_create_output_file, write_string_to_file};
+use crate::information::CURRENT_INFORMATION;
 
-mod instruction;
-mod parsing;
+mod arguments;
+mod board;
+mod debug;
+mod files;
+mod information;
+mod lsp;
+mod repl;
 
 fn main() {
-    let matches = clap_app!(assembler2i =>
-        (version: "1.0")
-        (author: "Erik Schulze <erikmail3333@gmail.com>")
-        (about: "Assembles 2ia files into 2i files")
-        (@arg INPUT: +required "Sets the Input File, must have .2ia file Type")
-        (@arg OUTPUT: -o +takes_value !required "Sets the Output File, must have .2i file Type")
-        (@arg COPY_INSTRUCTIONS: -i !required "Copy Instructions as Comments into the 2i file")
-        (@arg COPY_COMMENTS: -c !required "Copy Comments into the 2i file")
-    ).get_matches();
-
-    let args = match args_to_program_arguments(matches) {
-        Ok(args) => {args},
-        Err(error) =>
-            {
-                eprintln!("{}",style(error).red());
-                return;
-            }
-    };
-
-    let mut files = match open_files(args.input_file_path.as_str(), args.output_file_path.as_str()) {
-        Ok(files) => {files},
+    let program_command = match get_program_command(CURRENT_INFORMATION) {
+        Ok(program_command) => program_command,
         Err(error) => {
-            eprintln!("{}",style(error).red());
+            eprintln!("{}", style(error).red());
             return;
         }
     };
-    let mut input: String = String::new();
-    match files.input_file.read_to_string(&mut input) {
-        Ok(_) => {},
-        Err(_) => {
-            eprintln!("{}",style("Error reading Input File").red());
+
+    match program_command {
+        ProgramCommand::Assemble(program_arguments) => assemble(program_arguments),
+        ProgramCommand::Board(board_arguments) => board::board(board_arguments),
+        ProgramCommand::Debug(debug_arguments) => debug::debug(debug_arguments),
+        ProgramCommand::Disassemble(disassemble_arguments) => disassemble(disassemble_arguments),
+        ProgramCommand::Document(document_arguments) => document(document_arguments),
+        ProgramCommand::Equivalence(equivalence_arguments) => equivalence(equivalence_arguments),
+        ProgramCommand::Explain(explain_arguments) => explain(explain_arguments),
+        ProgramCommand::Format(format_arguments) => format(format_arguments),
+        ProgramCommand::LanguageServer(language_server_arguments) => lsp::language_server(language_server_arguments),
+        ProgramCommand::Repl(repl_arguments) => repl::repl(repl_arguments),
+        ProgramCommand::Run(run_arguments) => run(run_arguments),
+        ProgramCommand::Test(test_arguments) => test(test_arguments),
+        ProgramCommand::Trace(trace_arguments) => trace(trace_arguments)
+    }
+}
+
+fn assemble(program_arguments: ProgramArguments) {
+    if program_arguments.fix {
+        if let Err(error) = fix(&program_arguments) {
+            eprintln!("{}", style(error).red());
             return;
         }
     }
 
-    let input_lines = input.lines();
-    let mut code_lines = Vec::<CodeLine>::new();
-    for (i,line) in input_lines.enumerate() {
-        let code_line = match parse_line(line) {
-            Ok(code_line) => {code_line},
-            Err(error) => {
-                eprintln!("Error in Line {}, Description: {}", i, error);
-                return;
-            }
-        };
-        code_lines.push(code_line);
-    }
+    let assembler = Assembler::new_with_settings(AssemblerSettings {
+        copy_comments: program_arguments.copy_comments,
+        copy_instructions: program_arguments.copy_instructions,
+        rtl_comments: program_arguments.rtl_comments,
+        defines: program_arguments.defines.clone(),
+        dialect: program_arguments.dialect
+    });
+
+    let program = match assembler.translate_file(Path::new(&program_arguments.input_file_path)) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            return;
+        }
+    };
 
     let mut output_string = String::new();
     output_string.push_str("# Generated by Assembler2i\r\n");
     output_string.push_str("\r\n");
-    for code_line in code_lines {
-        output_string.push_str(format!("{}\r\n", code_line).as_str());
+    for line in &program.lines {
+        output_string.push_str(format!("{}\r\n", line).as_str());
     }
-    match files.output_file.write_all(output_string.as_bytes()) {
-        Ok(_) => {},
-        Err(_) => {
-            eprintln!("{}", style("Error writing to Output File").red());
+
+    if let Err(error) = write_output_file(&program_arguments.output_file_path, output_string) {
+        eprintln!("{}", style(error).red());
+        return;
+    }
+
+    if program_arguments.write_listing {
+        let listing_file_path = listing_file_path_from_output_file_path(&program_arguments.output_file_path);
+        if let Err(error) = write_output_file(&listing_file_path, program.listing()) {
+            eprintln!("{}", style(error).red());
             return;
-        },
+        }
     }
+
+    if !program.memory.is_empty() {
+        let memory_file_path = memory_file_path_from_output_file_path(&program_arguments.output_file_path);
+        if let Err(error) = write_output_file(&memory_file_path, program.memory.to_string()) {
+            eprintln!("{}", style(error).red());
+            return;
+        }
+    }
+
     println!("Successfully translated the Program");
 }
 
+fn disassemble(disassemble_arguments: DisassembleArguments) {
+    let input = match fs::read_to_string(&disassemble_arguments.input_file_path) {
+        Ok(input) => input,
+        Err(_) => {
+            eprintln!("{}", style("Error reading Input File").red());
+            std::process::exit(1);
+        }
+    };
+
+    let source = TranslatedProgram::parse(&disassemble_arguments.input_file_path, &input)
+        .and_then(|program| disassemble_program(&program, disassemble_arguments.dialect));
+
+    let source = match source {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
+        }
+    };
 
-struct Files {
-    input_file: File,
-    output_file: File,
+    if let Err(error) = write_output_file(&disassemble_arguments.output_file_path, source) {
+        eprintln!("{}", style(error).red());
+        std::process::exit(1);
+    }
+
+    println!("Successfully disassembled the Program");
 }
 
-fn open_files(input_file_path: &str, output_file_path: &str) -> Result<Files,String> {
-    let input_file = match File::open(input_file_path) {
-        Ok(file) => {file},
-        Err(err) => {
-            return match err.kind() {
-                ErrorKind::NotFound => { Err("Input File was not found".to_string()) },
-                _ => { Err("Could not open Input File".to_string()) }
-            }
+fn document(document_arguments: DocumentArguments) {
+    let input_path = Path::new(&document_arguments.input_file_path);
+    let program = match load_program(&document_arguments.input_file_path, document_arguments.dialect) {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
         }
     };
 
-    if Path::new(output_file_path).exists() {
-        match Confirm::new().with_prompt("Output file already exists, overwrite?").interact() {
-            Ok(confirm) => {
-                if !confirm {
-                    return Err("Could not open Output File".to_string())
-                }
-            },
-            Err(_) => {return Err("Could not open Output File".to_string())}
+    let title = input_path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| document_arguments.input_file_path.clone());
+    let page = document_program(&title, &program, document_arguments.format);
+
+    if let Err(error) = write_output_file(&document_arguments.output_file_path, page) {
+        eprintln!("{}", style(error).red());
+        std::process::exit(1);
+    }
+
+    println!("Successfully documented the Program");
+}
+
+fn explain(explain_arguments: ExplainArguments) {
+    if let Some(diagnostic) = find_diagnostic(&explain_arguments.input) {
+        print!("{}", format_diagnostic(diagnostic));
+        return;
+    }
+    // Words are written with 0x when they look like an Error Code.
+    let input = explain_arguments.input.trim();
+    if input.len() == 5 && input.to_ascii_uppercase().starts_with('E') && input[1..].chars().all(|ch| ch.is_ascii_digit()) {
+        eprintln!("{}", style(format!("Unknown Error Code {}, write 0x{} for the Word", input, input)).red());
+        std::process::exit(1);
+    }
+
+    match parse_explain_input(&explain_arguments.input, explain_arguments.dialect) {
+        Ok(instruction) => print!("{}", format_explanation(&instruction, explain_arguments.dialect)),
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
         }
     }
+}
 
-    let output_file = match File::create(output_file_path) {
-        Ok(file) => {file},
-        Err(_) => {return Err("Could not open Output File!".to_string())},
+/// Compares two programs over the input values and interrupt schedules, fails with the smallest input they differ for.
+fn equivalence(equivalence_arguments: EquivalenceArguments) {
+    let [first_path, second_path] = &equivalence_arguments.input_file_paths;
+    let schedules = equivalence_arguments.stimulus_file_paths.iter()
+        .map(|stimulus_file_path| fs::read_to_string(stimulus_file_path)
+            .map_err(|_| format!("Error reading Stimulus File {}", stimulus_file_path))
+            .and_then(|content| Stimulus::parse(stimulus_file_path, &content))
+            .map(|stimulus| (stimulus_file_path.clone(), stimulus)))
+        .collect::<Result<Vec<(String, Stimulus)>, String>>();
+    let loaded = schedules.and_then(|schedules| load_program(first_path, equivalence_arguments.dialect)
+        .and_then(|first| load_program(second_path, equivalence_arguments.dialect).map(|second| (first, second, schedules))));
+
+    let (first, second, schedules) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
+        }
     };
 
-    return Ok(Files {
-        input_file,
-        output_file
-    });
+    let settings = EquivalenceSettings {
+        input_ports: equivalence_arguments.input_ports,
+        schedules,
+        cycles: equivalence_arguments.cycles,
+        samples: equivalence_arguments.samples,
+        ..EquivalenceSettings::new()
+    };
+    match check_equivalence([&first, &second], &settings) {
+        Equivalence::Equivalent { checked, exhaustive: true } => println!("{}", style(format!("Equivalent for all {} Inputs", checked)).green()),
+        Equivalence::Equivalent { checked, exhaustive: false } => println!("{}", style(format!("Equivalent for {} random Inputs", checked)).green()),
+        Equivalence::Different(difference) => {
+            eprint!("{}", style(format_difference([first_path, second_path], &difference)).red());
+            std::process::exit(1);
+        }
+    }
 }
 
-struct ProgramArguments {
-    copy_instructions: bool,
-    copy_comments: bool,
-    input_file_path: String,
-    output_file_path: String
-}
+/// Runs the program until it halts or the cycles are used up and prints the machine state.
+/// With a stimulus file, it also prints the output port writes and exits with a failure if they differ from the expected ones.
+fn run(run_arguments: RunArguments) {
+    let stimulus = match &run_arguments.stimulus_file_path {
+        Some(stimulus_file_path) => fs::read_to_string(stimulus_file_path)
+            .map_err(|_| format!("Error reading Stimulus File {}", stimulus_file_path))
+            .and_then(|content| Stimulus::parse(stimulus_file_path, &content))
+            .map(Some),
+        None => Ok(None)
+    };
+    let loaded = stimulus.and_then(|stimulus| load_program(&run_arguments.input_file_path, run_arguments.dialect)
+        .map(|program| (program, stimulus)));
 
-fn args_to_program_arguments(args: ArgMatches) -> Result<ProgramArguments, String> {
-    let copy_instructions = args.is_present("COPY_INSTRUCTIONS");
-    let copy_comments = args.is_present("COPY_COMMENTS");
+    let (program, stimulus) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
+        }
+    };
 
-    let input_file_path = match args.value_of("INPUT") {
-        None =>
-            {
-                return Err("Input File was not provided".to_string());
-            },
-        Some(file_path) => {file_path},
+    let mut emulator = Emulator::new(&program);
+    let result = match &stimulus {
+        Some(stimulus) => stimulus.run(&mut emulator, run_arguments.cycles).map(Some),
+        None => emulator.run(run_arguments.cycles).map(|_| None)
+    };
+    print!("{}", emulator.state);
+    let writes = match result {
+        Ok(writes) => writes,
+        Err(error) => {
+            eprintln!("{}", style(format!("Error in {}, Description: {}", run_arguments.input_file_path, error)).red());
+            std::process::exit(1);
+        }
     };
-    if !input_file_path.ends_with(".2ia") {
-        return Err("Input File does not have .2ia File Type".to_string());
+
+    match emulator.is_halted() {
+        true => println!("Halted at {}", emulator.state.address),
+        false => println!("Stopped after {} Cycles without halting", run_arguments.cycles)
+    }
+
+    if let (Some(stimulus), Some(writes)) = (stimulus, writes) {
+        for write in &writes {
+            println!("Output {:02X}={:02X} in Cycle {}", write.port, write.value, write.cycle);
+        }
+        let differences = stimulus.check(&writes);
+        if !differences.is_empty() {
+            for difference in &differences {
+                eprintln!("{}", style(difference).red());
+            }
+            std::process::exit(1);
+        }
+        println!("{}", style("The Output Port Writes match the Stimulus").green());
     }
-    let mut output_file_path = input_file_path.split_at(input_file_path.len() - 4).0.to_string();
-    output_file_path.push_str(".2i");
+}
 
-    match args.value_of("OUTPUT") {
-        None => {},
-        Some(file_path) => { output_file_path = file_path.to_string()},
+/// Runs the test cases of every file and prints their results, with a coverage format also what the test cases of each program executed.
+/// Exits with a failure if a file could not be tested or a test case fails, so that it can be used in CI.
+fn test(test_arguments: TestArguments) {
+    let (mut passed, mut failed) = (0, 0);
+    let mut report = String::new();
+    for input_file_path in &test_arguments.input_file_paths {
+        let test_cases = load_program(input_file_path, test_arguments.dialect)
+            .and_then(|program| fs::read_to_string(input_file_path)
+                .map_err(|_| format!("Error reading Input File {}", input_file_path))
+                .and_then(|source| parse_test_cases(input_file_path, &source, &program))
+                .map(|test_cases| (program, test_cases)));
+
+        let (program, test_cases) = match test_cases {
+            Ok(test_cases) => test_cases,
+            Err(error) => {
+                eprintln!("{}", style(error).red());
+                failed += 1;
+                continue;
+            }
+        };
+        if test_cases.is_empty() {
+            println!("{} has no Test Cases", input_file_path);
+        }
+
+        let mut coverage = Coverage::new();
+        for test_case in &test_cases {
+            let result = run_test_case(&program, test_case);
+            coverage.merge(&result.coverage);
+            let text = format_test_result(test_case, &result);
+            match result.passed {
+                true => {
+                    passed += 1;
+                    print!("{}", style(text).green());
+                },
+                false => {
+                    failed += 1;
+                    print!("{}", style(text).red());
+                }
+            }
+        }
+        if let Some(format) = test_arguments.coverage {
+            report.push_str(&format_coverage(&program, &coverage, format));
+        }
+    }
+
+    println!("{} passed, {} failed", passed, failed);
+    match (&test_arguments.coverage_file_path, test_arguments.coverage) {
+        (Some(coverage_file_path), _) => if let Err(error) = write_output_file(coverage_file_path, report) {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
+        },
+        (None, Some(_)) => print!("\n{}", report),
+        (None, None) => {}
+    }
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Runs the program like `run` and writes every cycle to the trace file, exits with a failure if that is not possible.
+fn trace(trace_arguments: TraceArguments) {
+    let trace = load_program(&trace_arguments.input_file_path, trace_arguments.dialect)
+        .and_then(|program| record_trace(&program, trace_arguments.cycles)
+            .map_err(|error| format!("Error in {}, Description: {}", trace_arguments.input_file_path, error)));
+
+    let trace = match trace {
+        Ok(trace) => trace,
+        Err(error) => {
+            eprintln!("{}", style(error).red());
+            std::process::exit(1);
+        }
     };
 
-    let program_arguments = ProgramArguments {
-        copy_instructions,
-        copy_comments,
-        input_file_path: input_file_path.to_string(),
-        output_file_path
+    if let Err(error) = write_output_file(&trace_arguments.output_file_path, format_trace(&trace, trace_arguments.format)) {
+        eprintln!("{}", style(error).red());
+        std::process::exit(1);
+    }
+
+    println!("Successfully traced {} Cycles", trace.steps.len());
+}
+
+/// Formats every file in place, or with `--check` lists the files that are not formatted.
+/// Exits with a failure if a file could not be formatted or is not formatted, so that it can be used in CI.
+fn format(format_arguments: FormatArguments) {
+    let mut assembler = Assembler::new();
+    assembler.dialect(format_arguments.dialect);
+
+    let mut failed = false;
+    for input_file_path in &format_arguments.input_file_paths {
+        let path = Path::new(input_file_path);
+        let formatted = fs::read_to_string(path)
+            .map_err(|_| format!("Error reading Input File {}", input_file_path))
+            .and_then(|source| {
+                let base_directory = path.parent().unwrap_or_else(|| Path::new("."));
+                return format_program(&assembler, input_file_path, &source, base_directory)
+                    .map(|formatted| (source, formatted));
+            });
+
+        let result = match formatted {
+            Ok((source, formatted)) if source == formatted => Ok(()),
+            Ok(_) if format_arguments.check => {
+                println!("{} is not formatted", input_file_path);
+                failed = true;
+                Ok(())
+            },
+            Ok((_, formatted)) => fs::write(path, formatted)
+                .map(|_| println!("Formatted {}", input_file_path))
+                .map_err(|_| format!("Error writing Input File {}", input_file_path)),
+            Err(error) => Err(error)
+        };
+
+        if let Err(error) = result {
+            eprintln!("{}", style(error).red());
+            failed = true;
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Rewrites the input file in place with the fixes that make its lines translate.
+fn fix(program_arguments: &ProgramArguments) -> Result<(), String> {
+    let input_file_path = &program_arguments.input_file_path;
+    let source = fs::read_to_string(input_file_path)
+        .map_err(|_| "Error reading Input File".to_string())?;
+
+    let (fixed_source, fixes) = fix_source(&source, program_arguments.dialect);
+    if fixes.is_empty() {
+        return Ok(());
+    }
+
+    fs::write(input_file_path, fixed_source)
+        .map_err(|_| "Error writing the fixed Input File".to_string())?;
+    for fix in &fixes {
+        println!("Fixed {}:{}: {} -> {}", input_file_path, fix.line, fix.before.trim(), fix.after.trim());
+    }
+    return Ok(());
+}
+
+/// Translates a `.2ia` file or parses an already translated `.2i` file.
+fn load_program(input_file_path: &str, dialect: Dialect) -> Result<TranslatedProgram, String> {
+    return match input_file_path.ends_with(".2ia") {
+        true => program_assembler(dialect).translate_file(Path::new(input_file_path)),
+        false => fs::read_to_string(input_file_path)
+            .map_err(|_| "Error reading Input File".to_string())
+            .and_then(|input| TranslatedProgram::parse(input_file_path, &input))
     };
+}
 
-    return Ok(program_arguments);
-}
\ No newline at end of file
+/// The assembler that `load_program` translates `.2ia` files with.
+fn program_assembler(dialect: Dialect) -> Assembler {
+    let mut assembler = Assembler::new();
+    assembler.dialect(dialect);
+    return assembler;
+}
+
+fn write_output_file(output_file_path: &str, content: String) -> Result<(), String> {
+    let mut output_file = try_create_output_file(output_file_path)?;
+    return write_string_to_file(content, &mut output_file)
+        .map_err(|_| "Error writing to Output File".to_string());
+}
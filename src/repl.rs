@@ -0,0 +1,128 @@
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+use console::{style, Color};
+use assemblerlib::Assembler;
+use assemblerlib::dialect::Dialect;
+use assemblerlib::explanation::{explain_instruction, format_source_lines};
+use assemblerlib::instruction::Instruction;
+use assemblerlib::repl::{ReplOutcome, ReplSession};
+use crate::arguments::ReplArguments;
+use crate::files::{try_create_output_file, write_string_to_file};
+
+const PROMPT: &str = "2ia> ";
+const CONTINUATION_PROMPT: &str = "...> ";
+
+/// The colours of the fields of an instruction word, from MAC1..MAC0 down to MCHFLG, repeated after MRGWS/MRGWE.
+const FIELD_COLORS: [Color; 6] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan
+];
+
+const HELP: &str = "Every .2ia Line is translated with the Lines before it and its Instructions are explained.\n\
+    Blocks of Structured Code are translated once they are closed.\n\
+    :list         Shows the Lines entered so far\n\
+    :save FILE    Writes the Lines entered so far to a .2ia File\n\
+    :load FILE    Replaces the Lines with the Lines of a .2ia File\n\
+    :help         Shows this Help\n\
+    :quit         Leaves the REPL";
+
+pub fn repl(repl_arguments: ReplArguments) {
+    let mut assembler = Assembler::new();
+    assembler.dialect(repl_arguments.dialect);
+    let mut session = ReplSession::new(assembler);
+    println!("Assembler2i REPL, type :help for the Commands");
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    loop {
+        print!("{}", match session.is_pending() {
+            true => CONTINUATION_PROMPT,
+            false => PROMPT
+        });
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = line.trim_end();
+
+        if !session.is_pending() && line.trim_start().starts_with(':') {
+            match run_command(&mut session, line.trim()) {
+                Ok(true) => continue,
+                Ok(false) => break,
+                Err(error) => {
+                    eprintln!("{}", style(error).red());
+                    continue;
+                }
+            }
+        }
+
+        match session.enter(line) {
+            Ok(ReplOutcome::Pending) => {},
+            Ok(ReplOutcome::Accepted(instructions)) => instructions.iter()
+                .for_each(|instruction| print_instruction(instruction, repl_arguments.dialect)),
+            Err(error) => eprintln!("{}", style(error).red())
+        }
+    }
+}
+
+/// Runs a `:` command, returns whether the REPL goes on.
+fn run_command(session: &mut ReplSession, command: &str) -> Result<bool, String> {
+    let (name, argument) = match command.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (command, "")
+    };
+
+    match name {
+        ":list" => session.lines().iter().enumerate()
+            .for_each(|(i, line)| println!("{:4}  {}", i + 1, line)),
+        ":save" => {
+            if argument.is_empty() {
+                return Err("Expected the File to save to".to_string());
+            }
+            let mut output_file = try_create_output_file(argument)?;
+            write_string_to_file(session.source(), &mut output_file)
+                .map_err(|_| "Error writing to Output File".to_string())?;
+            println!("Saved {} Lines to {}", session.lines().len(), argument);
+        },
+        ":load" => {
+            if argument.is_empty() {
+                return Err("Expected the File to load".to_string());
+            }
+            let content = fs::read_to_string(argument)
+                .map_err(|_| format!("Error reading {}", argument))?;
+            let instructions = session.load(&content)?;
+            println!("Loaded {} Lines with {} Instructions from {}", session.lines().len(), instructions, argument);
+        },
+        ":help" => println!("{}", HELP),
+        ":quit" | ":q" => return Ok(false),
+        _ => return Err(format!("Unknown Command {}, type :help for the Commands", name))
+    }
+    return Ok(true);
+}
+
+/// Prints the word with a colour per field, followed by the fields in the same colours.
+pub(crate) fn print_instruction(instruction: &Instruction, dialect: Dialect) {
+    let fields = explain_instruction(instruction);
+    let word: Vec<String> = fields.iter().zip(FIELD_COLORS.iter().cycle())
+        .map(|(field, color)| style(&field.value).fg(*color).bold().to_string())
+        .collect();
+    println!("{}: {}", instruction.address, word.join(" "));
+
+    let name_width = fields.iter().map(|field| field.name.len()).max().unwrap_or(0);
+    let value_width = fields.iter().map(|field| field.value.len()).max().unwrap_or(0);
+    for (field, color) in fields.iter().zip(FIELD_COLORS.iter().cycle()) {
+        println!("  {}  {}  {}",
+            style(format!("{:width$}", field.name, width = name_width)).fg(*color),
+            style(format!("{:width$}", field.value, width = value_width)).fg(*color).bold(),
+            field.meaning);
+    }
+    print!("{}", format_source_lines(instruction, dialect));
+}